@@ -15,6 +15,9 @@ import {
 import {
   TokenSale,
   TOKEN_SALE_ACCOUNT_DATA_LAYOUT,
+  PRICE_MULTIPLIER,
+  SaleStatus,
+  TOKEN_SALE_VERSION,
   TokenSaleLayout,
 } from '../client/token-sale';
 import {newAccountWithLamports} from '../client/util/new-account-with-lamports';
@@ -57,6 +60,11 @@ const FUND_AMOUNT = 1000000*DECIMAL_MULTIPLIER;
 const PURCHASE_AMOUNT = 250*DECIMAL_MULTIPLIER;
 const SALE_PRICE = 0.1;
 const SALE_TIMESTAMP = Math.floor(Date.now()/1000) + 30; // setting sale time to 30secs from now...
+const SALE_END_TIMESTAMP = SALE_TIMESTAMP + 3600; // closing the sale an hour after it goes live
+const VESTING_TGE_PERCENT = 100; // purchased tokens are unlocked immediately
+const SOFT_CAP_AMOUNT = 0; // sale proceeds go straight to the pool account
+const SALE_MODE = 0; // first come first served
+const PRICING_MODE = 0; // fixed token sale price
 
 function assert(condition, message) {
   if (!condition) {
@@ -141,6 +149,18 @@ export async function InitTokenSale(): Promise<void> {
     USD_MAX_AMOUNT,
     SALE_PRICE,
     SALE_TIMESTAMP,
+    SALE_END_TIMESTAMP,
+    VESTING_TGE_PERCENT,
+    SALE_END_TIMESTAMP,
+    SALE_END_TIMESTAMP,
+    SALE_END_TIMESTAMP,
+    SOFT_CAP_AMOUNT,
+    SALE_MODE,
+    PRICING_MODE,
+    0,
+    0,
+    0,
+    0,
   );
 
   await sleep(500);
@@ -154,7 +174,7 @@ export async function InitTokenSale(): Promise<void> {
       throw new Error("Could not find token sale account at given address!");
   }
   const tokenSaleLayout = TOKEN_SALE_ACCOUNT_DATA_LAYOUT.decode(tokenSaleState);
-  assert(tokenSaleLayout.isInitialized);
+  assert(TOKEN_SALE_VERSION == tokenSaleLayout.version);
   assert(payer.publicKey.toBase58() == new PublicKey(tokenSaleLayout.initPubkey).toBase58());
   assert(saleTokenAccountSOLR.toBase58() == new PublicKey(tokenSaleLayout.saleTokenAccountPubkey).toBase58());
   assert(poolTokenAccountUSDT.toBase58() == new PublicKey(tokenSaleLayout.poolTokenAccountPubkey).toBase58());
@@ -163,8 +183,16 @@ export async function InitTokenSale(): Promise<void> {
   assert(INIT_FUND_AMOUNT == new BN(tokenSaleLayout.tokenSaleAmount, 10, "le").toNumber());
   assert(USD_MIN_AMOUNT == new BN(tokenSaleLayout.minAmount, 10, "le").toNumber());
   assert(USD_MAX_AMOUNT == new BN(tokenSaleLayout.maxAmount, 10, "le").toNumber());
-  assert((1/SALE_PRICE) == new BN(tokenSaleLayout.tokenSalePrice, 10, "le").toNumber());
+  assert((SALE_PRICE*PRICE_MULTIPLIER) == new BN(tokenSaleLayout.tokenSalePrice, 10, "le").toNumber());
+  assert(6 == tokenSaleLayout.saleMintDecimals);
+  assert(6 == tokenSaleLayout.usdMintDecimals);
   assert(SALE_TIMESTAMP == new BN(tokenSaleLayout.tokenSaleTime, 10, "le").toNumber());
+  assert(SALE_END_TIMESTAMP == new BN(tokenSaleLayout.tokenSaleEndTime, 10, "le").toNumber());
+  assert(VESTING_TGE_PERCENT == tokenSaleLayout.vestingTgePercent);
+  assert(SOFT_CAP_AMOUNT == new BN(tokenSaleLayout.softCapAmount, 10, "le").toNumber());
+  assert(SALE_MODE == tokenSaleLayout.saleMode);
+  assert(PRICING_MODE == tokenSaleLayout.pricingMode);
+  assert(SaleStatus.Initialized == tokenSaleLayout.status);
 }
 
 export async function FundTokenSale(): Promise<void> {
@@ -215,6 +243,7 @@ export async function ExecuteTokenSale(): Promise<void> {
     poolTokenAccountUSDT,
     tokenWhitelistAccount,
     PURCHASE_AMOUNT,
+    0,
   );
 
   await sleep(500);
@@ -64,8 +64,29 @@ export class Numberu64 extends BN {
   }
 }
 
+/**
+ * Prices are stored on chain as usd per whole token for sale multiplied by this value
+ */
+export const PRICE_MULTIPLIER = 1000000;
+
+/**
+ * Layout version stored in the first byte of an initialized token sale account
+ */
+export const TOKEN_SALE_VERSION = 2;
+
+export const SaleStatus = {
+  Initialized: 0,
+  Funded: 1,
+  Scheduled: 2,
+  Live: 3,
+  Paused: 4,
+  Ended: 5,
+  Finalized: 6,
+  Cancelled: 7,
+};
+
 export const TOKEN_SALE_ACCOUNT_DATA_LAYOUT = BufferLayout.struct([
-  BufferLayout.u8("isInitialized"),
+  BufferLayout.u8("version"),
   Layout.publicKey("initPubkey"),
   Layout.publicKey("saleTokenAccountPubkey"),
   Layout.publicKey("poolTokenAccountPubkey"),
@@ -76,12 +97,38 @@ export const TOKEN_SALE_ACCOUNT_DATA_LAYOUT = BufferLayout.struct([
   Layout.uint64("maxAmount"),
   Layout.uint64("tokenSalePrice"),
   Layout.uint64("tokenSaleTime"),
-  BufferLayout.u8("tokenSalePaused"),
-  BufferLayout.u8("tokenSaleEnded"),
+  Layout.uint64("tokenSaleEndTime"),
+  BufferLayout.u8("status"),
+  BufferLayout.u8("bumpSeed"),
+  BufferLayout.u8("vestingTgePercent"),
+  Layout.uint64("vestingStartTime"),
+  Layout.uint64("vestingCliffTime"),
+  Layout.uint64("vestingEndTime"),
+  Layout.uint64("vestingLockedAmount"),
+  Layout.uint64("softCapAmount"),
+  Layout.uint64("usdRaisedAmount"),
+  BufferLayout.u8("saleMode"),
+  Layout.uint64("usdWithdrawnAmount"),
+  Layout.uint64("tokenSettledAmount"),
+  BufferLayout.u8("pricingMode"),
+  Layout.uint64("auctionStartPrice"),
+  Layout.uint64("auctionFloorPrice"),
+  Layout.uint64("auctionDecayAmount"),
+  Layout.uint64("auctionDecayInterval"),
+  BufferLayout.u8("saleMintDecimals"),
+  BufferLayout.u8("usdMintDecimals"),
+  Layout.publicKey("pendingAdminPubkey"),
+  Layout.publicKey("guardianPubkey"),
+  Layout.publicKey("operatorPubkey"),
+  Layout.publicKey("oraclePubkey"),
+  Layout.uint64("oracleMaxAge"),
+  BufferLayout.u16("oracleMaxConfidenceBps"),
+  Layout.publicKey("voucherSignerPubkey"),
+  BufferLayout.blob(32, "merkleRoot"),
 ]);
 
 export interface TokenSaleLayout {
-  isInitialized: number,
+  version: number,
   initPubkey: Uint8Array,
   saleTokenAccountPubkey: Uint8Array,
   poolTokenAccountPubkey: Uint8Array,
@@ -92,8 +139,34 @@ export interface TokenSaleLayout {
   maxAmount: Uint8Array,
   tokenSalePrice: Uint8Array,
   tokenSaleTime: Uint8Array,
-  tokenSalePaused: number,
-  tokenSaleEnded: number,
+  tokenSaleEndTime: Uint8Array,
+  status: number,
+  bumpSeed: number,
+  vestingTgePercent: number,
+  vestingStartTime: Uint8Array,
+  vestingCliffTime: Uint8Array,
+  vestingEndTime: Uint8Array,
+  vestingLockedAmount: Uint8Array,
+  softCapAmount: Uint8Array,
+  usdRaisedAmount: Uint8Array,
+  saleMode: number,
+  usdWithdrawnAmount: Uint8Array,
+  tokenSettledAmount: Uint8Array,
+  pricingMode: number,
+  auctionStartPrice: Uint8Array,
+  auctionFloorPrice: Uint8Array,
+  auctionDecayAmount: Uint8Array,
+  auctionDecayInterval: Uint8Array,
+  saleMintDecimals: number,
+  usdMintDecimals: number,
+  pendingAdminPubkey: Uint8Array,
+  guardianPubkey: Uint8Array,
+  operatorPubkey: Uint8Array,
+  oraclePubkey: Uint8Array,
+  oracleMaxAge: Uint8Array,
+  oracleMaxConfidenceBps: number,
+  voucherSignerPubkey: Uint8Array,
+  merkleRoot: Uint8Array,
 }
 
 /**
@@ -241,8 +314,20 @@ export class TokenSale {
    * @param amount Number of tokens for sale
    * @param minAmount Minimum allocation amount
    * @param maxAmount Maximum allocation amount
-   * @param price Token sale price
+   * @param price Token sale price in usd per whole token
    * @param timestamp Token sale time to go-live
+   * @param endTimestamp Token sale time after which purchases are closed
+   * @param vestingTgePercent Percentage of purchased tokens unlocked at vesting start (100 disables vesting)
+   * @param vestingStartTime Time when the TGE share unlocks and linear vesting begins
+   * @param vestingCliffTime Time before which nothing beyond the TGE share unlocks
+   * @param vestingEndTime Time when all purchased tokens are unlocked
+   * @param softCapAmount Minimum raise below which buyers are refunded (0 disables soft cap)
+   * @param saleMode 0 for first come first served purchases, 1 for pro-rata commitments
+   * @param pricingMode 0 for a fixed price, 1 for a dutch auction
+   * @param auctionStartPrice Auction price at go-live in usd per whole token
+   * @param auctionFloorPrice Lowest auction price in usd per whole token
+   * @param auctionDecayAmount Auction price decrease per decay interval in usd per whole token
+   * @param auctionDecayInterval Seconds between auction price decreases
    */
   async initTokenSale(
     poolTransferAuthority: Account,
@@ -251,8 +336,20 @@ export class TokenSale {
     amount: number | Numberu64,
     minAmount: number | Numberu64,
     maxAmount: number | Numberu64,
-    price: number | Numberu64,
+    price: number,
     timestamp: number | Numberu64,
+    endTimestamp: number | Numberu64,
+    vestingTgePercent: number,
+    vestingStartTime: number | Numberu64,
+    vestingCliffTime: number | Numberu64,
+    vestingEndTime: number | Numberu64,
+    softCapAmount: number | Numberu64,
+    saleMode: number,
+    pricingMode: number,
+    auctionStartPrice: number,
+    auctionFloorPrice: number,
+    auctionDecayAmount: number,
+    auctionDecayInterval: number | Numberu64,
   ): Promise<TransactionSignature> {
 
     const createSaleAccountInstruction = SystemProgram.createAccount({
@@ -277,6 +374,18 @@ export class TokenSale {
           maxAmount,
           price,
           timestamp,
+          endTimestamp,
+          vestingTgePercent,
+          vestingStartTime,
+          vestingCliffTime,
+          vestingEndTime,
+          softCapAmount,
+          saleMode,
+          pricingMode,
+          auctionStartPrice,
+          auctionFloorPrice,
+          auctionDecayAmount,
+          auctionDecayInterval,
           poolTransferAuthority.publicKey,
           this.tokenSaleAccount.publicKey,
           poolDestination,
@@ -284,6 +393,8 @@ export class TokenSale {
           this.tokenProgramId,
           this.tokenWhitelistProgramId,
           this.tokenWhitelistMap,
+          this.mintSOLRPubkey,
+          this.mintUSDTPubkey,
         ),
       ),
       this.payer,
@@ -297,8 +408,20 @@ export class TokenSale {
     amount: number | Numberu64,
     minAmount: number | Numberu64,
     maxAmount: number | Numberu64,
-    price: number | Numberu64,
+    price: number,
     timestamp: number | Numberu64,
+    endTimestamp: number | Numberu64,
+    vestingTgePercent: number,
+    vestingStartTime: number | Numberu64,
+    vestingCliffTime: number | Numberu64,
+    vestingEndTime: number | Numberu64,
+    softCapAmount: number | Numberu64,
+    saleMode: number,
+    pricingMode: number,
+    auctionStartPrice: number,
+    auctionFloorPrice: number,
+    auctionDecayAmount: number,
+    auctionDecayInterval: number | Numberu64,
     poolTransferAuthority: PublicKey,
     tokenSaleAccount: PublicKey,
     poolDestination: PublicKey,
@@ -306,6 +429,8 @@ export class TokenSale {
     tokenProgramId: PublicKey,
     tokenWhitelistProgramId: PublicKey,
     tokenWhitelistMap: PublicKey,
+    saleMint: PublicKey,
+    usdMint: PublicKey,
   ): TransactionInstruction {
     const dataLayout = BufferLayout.struct([
       BufferLayout.u8('instruction'),
@@ -314,11 +439,22 @@ export class TokenSale {
       Layout.uint64('usd_max_amount'),
       Layout.uint64('token_sale_price'),
       Layout.uint64('token_sale_time'),
+      Layout.uint64('token_sale_end_time'),
+      BufferLayout.u8('vesting_tge_percent'),
+      Layout.uint64('vesting_start_time'),
+      Layout.uint64('vesting_cliff_time'),
+      Layout.uint64('vesting_end_time'),
+      Layout.uint64('soft_cap_amount'),
+      BufferLayout.u8('sale_mode'),
+      BufferLayout.u8('pricing_mode'),
+      Layout.uint64('auction_start_price'),
+      Layout.uint64('auction_floor_price'),
+      Layout.uint64('auction_decay_amount'),
+      Layout.uint64('auction_decay_interval'),
     ]);
 
-    // price is converted for easy arithmetic inside token sale program
-    // number of tokens to distribute = purchase amount * inverse_price
-    let inverse_price = 1/price;
+    // prices are converted to fixed point, the program accounts for the decimals of both mints
+    const toFixedPoint = (value: number) => new Numberu64(Math.round(value * PRICE_MULTIPLIER)).toBuffer();
 
     const data = Buffer.alloc(dataLayout.span); 
     dataLayout.encode(
@@ -327,8 +463,20 @@ export class TokenSale {
         token_sale_amount: new Numberu64(amount).toBuffer(),
         usd_min_amount: new Numberu64(minAmount).toBuffer(),
         usd_max_amount: new Numberu64(maxAmount).toBuffer(),
-        token_sale_price: new Numberu64(inverse_price).toBuffer(), 
+        token_sale_price: toFixedPoint(price),
         token_sale_time: new Numberu64(timestamp).toBuffer(),
+        token_sale_end_time: new Numberu64(endTimestamp).toBuffer(),
+        vesting_tge_percent: vestingTgePercent,
+        vesting_start_time: new Numberu64(vestingStartTime).toBuffer(),
+        vesting_cliff_time: new Numberu64(vestingCliffTime).toBuffer(),
+        vesting_end_time: new Numberu64(vestingEndTime).toBuffer(),
+        soft_cap_amount: new Numberu64(softCapAmount).toBuffer(),
+        sale_mode: saleMode,
+        pricing_mode: pricingMode,
+        auction_start_price: toFixedPoint(auctionStartPrice),
+        auction_floor_price: toFixedPoint(auctionFloorPrice),
+        auction_decay_amount: toFixedPoint(auctionDecayAmount),
+        auction_decay_interval: new Numberu64(auctionDecayInterval).toBuffer(),
       },
       data,
     );
@@ -336,12 +484,14 @@ export class TokenSale {
     const keys = [
       {pubkey: poolTransferAuthority, isSigner: true, isWritable: false},
       {pubkey: tokenSaleAccount, isSigner: false, isWritable: true},
-      {pubkey: poolDestination, isSigner: false, isWritable: false},
+      {pubkey: poolDestination, isSigner: false, isWritable: true},
       {pubkey: saleTokenAccount, isSigner: false, isWritable: true},
       {pubkey: tokenWhitelistMap, isSigner: false, isWritable: false},
       {pubkey: tokenProgramId, isSigner: false, isWritable: false},
       {pubkey: tokenWhitelistProgramId, isSigner: false, isWritable: false},
       {pubkey: SYSVAR_RENT_PUBKEY, isSigner: false, isWritable: false},
+      {pubkey: saleMint, isSigner: false, isWritable: false},
+      {pubkey: usdMint, isSigner: false, isWritable: false},
     ];
     return new TransactionInstruction({
       keys,
@@ -408,7 +558,7 @@ export class TokenSale {
 
     const keys = [
       {pubkey: poolTransferAuthority, isSigner: true, isWritable: false},
-      {pubkey: tokenSaleAccount, isSigner: false, isWritable: false},
+      {pubkey: tokenSaleAccount, isSigner: false, isWritable: true},
       {pubkey: poolAccount, isSigner: false, isWritable: true},
       {pubkey: saleTokenAccount, isSigner: false, isWritable: true},
       {pubkey: tokenProgramId, isSigner: false, isWritable: false},
@@ -424,12 +574,15 @@ export class TokenSale {
    * Sale token A for token B
    *
    * @param userAccount Account delegated to transfer user tokens
-   * @param userSource User's source token account
+   * @param userSource User's source token account, or the user's wallet to pay in native SOL
    * @param userDestination User's destination token account
    * @param saleTokenAccount Pool's source token account
    * @param poolDestination Pool's destination token account
    * @param tokenWhitelistAccount Account holding token whitelist info
    * @param amount Number of tokens to transfer from source account
+   * @param maxPrice Highest acceptable auction price in usd per whole token (ignored for fixed price sales)
+   * @param oracle Oracle price account of the sale, needed to pay in native SOL when it has one
+   * @param merkleProof Packed allocation and merkle proof of the user, needed in sales with a merkle root
    */
   async executeTokenSale(
     userAccount: Account,
@@ -439,10 +592,21 @@ export class TokenSale {
     poolDestination: PublicKey,
     tokenWhitelistAccount: PublicKey,
     amount: number | Numberu64,
+    maxPrice: number,
+    oracle?: PublicKey,
+    merkleProof?: Buffer,
   ): Promise<TransactionSignature> {
 
-    const saleProgramDerivedAddress = await PublicKey.findProgramAddress([Buffer.from("solrsale")], TOKEN_SALE_PROGRAM_ID);
+    const saleProgramDerivedAddress = await PublicKey.findProgramAddress([this.tokenSaleAccount.publicKey.toBuffer()], TOKEN_SALE_PROGRAM_ID);
     console.log('>>>>> saleProgramDerivedAddress: ', saleProgramDerivedAddress[0].toString());
+    const userPurchaseRecord = await PublicKey.findProgramAddress(
+      [Buffer.from("purchase"), this.tokenSaleAccount.publicKey.toBuffer(), userAccount.publicKey.toBuffer()],
+      TOKEN_SALE_PROGRAM_ID,
+    );
+    const paymentConfig = await PublicKey.findProgramAddress(
+      [Buffer.from("payment"), this.tokenSaleAccount.publicKey.toBuffer()],
+      TOKEN_SALE_PROGRAM_ID,
+    );
 
     return await sendAndConfirmTransaction(
       'executeTokenSale',
@@ -451,6 +615,7 @@ export class TokenSale {
         TokenSale.executeTokenSaleInstruction(
           this.tokenSaleProgramId,
           amount,
+          maxPrice,
           userAccount.publicKey,
           this.tokenSaleAccount.publicKey,
           userSource,
@@ -462,6 +627,10 @@ export class TokenSale {
           this.tokenWhitelistProgramId,
           this.tokenWhitelistMap,
           tokenWhitelistAccount,
+          userPurchaseRecord[0],
+          paymentConfig[0],
+          oracle,
+          merkleProof,
         ),
       ),
       userAccount,
@@ -471,6 +640,7 @@ export class TokenSale {
   static executeTokenSaleInstruction(
     tokenSaleProgramId: PublicKey,
     amount: number | Numberu64,
+    maxPrice: number,
     userAccount: PublicKey,
     tokenSaleAccount: PublicKey,
     userSource: PublicKey,
@@ -482,34 +652,49 @@ export class TokenSale {
     tokenWhitelistProgramId: PublicKey,
     tokenWhitelistMap: PublicKey,
     tokenWhitelistAccount: PublicKey,
+    userPurchaseRecord: PublicKey,
+    paymentConfig: PublicKey,
+    oracle?: PublicKey,
+    merkleProof?: Buffer,
   ): TransactionInstruction {
     const dataLayout = BufferLayout.struct([
       BufferLayout.u8('instruction'),
       Layout.uint64('usd_amount'),
+      Layout.uint64('max_price'),
     ]);
 
-    const data = Buffer.alloc(dataLayout.span);
+    let data = Buffer.alloc(dataLayout.span);
     dataLayout.encode(
       {
         instruction: 2, // Execute Sale instruction
         usd_amount: new Numberu64(amount).toBuffer(),
+        max_price: new Numberu64(Math.round(maxPrice * PRICE_MULTIPLIER)).toBuffer(),
       },
       data,
     );
+    if (merkleProof) {
+      data = Buffer.concat([data, merkleProof]);
+    }
 
     const keys = [
-      {pubkey: userAccount, isSigner: true, isWritable: false},
-      {pubkey: tokenSaleAccount, isSigner: false, isWritable: false},
+      {pubkey: userAccount, isSigner: true, isWritable: true},
+      {pubkey: tokenSaleAccount, isSigner: false, isWritable: true},
       {pubkey: saleTokenAccount, isSigner: false, isWritable: true},
       {pubkey: userDestination, isSigner: false, isWritable: true},
       {pubkey: userSource, isSigner: false, isWritable: true},
       {pubkey: poolDestination, isSigner: false, isWritable: true},
       {pubkey: salePDA, isSigner: false, isWritable: false},
       {pubkey: tokenProgramId, isSigner: false, isWritable: false},
-      {pubkey: tokenWhitelistMap, isSigner: false, isWritable: true},
-      {pubkey: tokenWhitelistAccount, isSigner: false, isWritable: true},
+      {pubkey: tokenWhitelistMap, isSigner: false, isWritable: false},
+      {pubkey: tokenWhitelistAccount, isSigner: false, isWritable: false},
       {pubkey: tokenWhitelistProgramId, isSigner: false, isWritable: false},
+      {pubkey: userPurchaseRecord, isSigner: false, isWritable: true},
+      {pubkey: SystemProgram.programId, isSigner: false, isWritable: false},
+      {pubkey: paymentConfig, isSigner: false, isWritable: false},
     ];
+    if (oracle) {
+      keys.push({pubkey: oracle, isSigner: false, isWritable: false});
+    }
     return new TransactionInstruction({
       keys,
       programId: tokenSaleProgramId,
@@ -0,0 +1,36 @@
+//! Builds the merkle whitelist of a sale from a CSV file of `user,allocation` lines.
+//! Prints the root to set with SetMerkleRoot, then one `user,allocation,proof` line per
+//! user with the proof in hex as appended to ExecuteTokenSale and CommitToSale data.
+//!
+//! cargo run --example merkle_whitelist -- allocations.csv
+
+use std::{env, fs, process};
+
+use solana_program::hash::Hash;
+use solr_token_sale::merkle::MerkleTree;
+
+fn main() {
+    let path = match env::args().nth(1) {
+        Some(path) => path,
+        None => {
+            eprintln!("usage: merkle_whitelist <allocations.csv>");
+            process::exit(1);
+        }
+    };
+    let csv = fs::read_to_string(&path).unwrap_or_else(|error| {
+        eprintln!("{}: {}", path, error);
+        process::exit(1);
+    });
+    let tree = MerkleTree::from_csv(&csv).unwrap_or_else(|error| {
+        eprintln!("{}: {}", path, error);
+        process::exit(1);
+    });
+
+    println!("root {}", Hash::new_from_array(tree.root()));
+    for (user, allocation_amount) in tree.leaves() {
+        let mut proof_data = vec![];
+        tree.proof(user).unwrap().pack_into(&mut proof_data);
+        let proof_hex: String = proof_data.iter().map(|byte| format!("{:02x}", byte)).collect();
+        println!("{},{},{}", user, allocation_amount, proof_hex);
+    }
+}
@@ -46,6 +46,144 @@ pub enum TokenSaleError {
     /// Token Sale Ended
     #[error("Token Sale Ended")]
     TokenSaleEnded,
+    /// Invalid Program Address
+    #[error("Invalid program address generated from bump seed and key")]
+    InvalidProgramAddress,
+    /// Token Sale Not Ended
+    #[error("Token Sale Not Ended")]
+    TokenSaleNotEnded,
+    /// Token Sale Withdrawn
+    #[error("Token Sale Withdrawn")]
+    TokenSaleWithdrawn,
+    /// Token Sale Closed
+    #[error("Token Sale Closed")]
+    TokenSaleClosed,
+    /// Invalid Vesting Schedule
+    #[error("Invalid Vesting Schedule")]
+    InvalidVestingSchedule,
+    /// No Vested Tokens To Claim
+    #[error("No Vested Tokens To Claim")]
+    NothingToClaim,
+    /// Soft Cap Not Reached
+    #[error("Soft Cap Not Reached")]
+    SoftCapNotReached,
+    /// Soft Cap Reached
+    #[error("Soft Cap Reached")]
+    SoftCapReached,
+    /// Purchase Already Refunded
+    #[error("Purchase Already Refunded")]
+    PurchaseRefunded,
+    /// Invalid Sale Mode
+    #[error("Invalid Sale Mode")]
+    InvalidSaleMode,
+    /// Commitment Already Settled
+    #[error("Commitment Already Settled")]
+    CommitmentSettled,
+    /// Invalid Auction Schedule
+    #[error("Invalid Auction Schedule")]
+    InvalidAuctionSchedule,
+    /// Price Exceeds Maximum
+    #[error("Price Exceeds Maximum")]
+    PriceExceedsMaximum,
+    /// Arithmetic Overflow
+    #[error("Arithmetic Overflow")]
+    MathOverflow,
+    /// Token Sale Not Funded
+    #[error("Token Sale Not Funded")]
+    TokenSaleNotFunded,
+    /// Token Sale Not Paused
+    #[error("Token Sale Not Paused")]
+    TokenSaleNotPaused,
+    /// Invalid Account Version
+    #[error("Invalid Account Version")]
+    InvalidAccountVersion,
+    /// Token Sale Started
+    #[error("Token Sale Started")]
+    TokenSaleStarted,
+    /// Invalid Purchase Limits
+    #[error("Invalid Purchase Limits")]
+    InvalidPurchaseLimits,
+    /// Invalid Token Sale Price
+    #[error("Invalid Token Sale Price")]
+    InvalidTokenSalePrice,
+    /// Invalid Token Sale Time
+    #[error("Invalid Token Sale Time")]
+    InvalidTokenSaleTime,
+    /// No Pending Admin
+    #[error("No Pending Admin")]
+    NoPendingAdmin,
+    /// Token Sale Cancelled
+    #[error("Token Sale Cancelled")]
+    TokenSaleCancelled,
+    /// Token Account Not Empty
+    #[error("Token Account Not Empty")]
+    TokenAccountNotEmpty,
+    /// Invalid Token Program
+    #[error("Invalid Token Program")]
+    InvalidTokenProgram,
+    /// Invalid System Program
+    #[error("Invalid System Program")]
+    InvalidSystemProgram,
+    /// Invalid Account Owner
+    #[error("Invalid Account Owner")]
+    InvalidAccountOwner,
+    /// Invalid Signer
+    #[error("Invalid Signer")]
+    InvalidSigner,
+    /// Sale Token Account Mismatch
+    #[error("Sale Token Account Mismatch")]
+    SaleTokenAccountMismatch,
+    /// Pool Token Account Mismatch
+    #[error("Pool Token Account Mismatch")]
+    PoolTokenAccountMismatch,
+    /// Token Account Mint Mismatch
+    #[error("Token Account Mint Mismatch")]
+    MintMismatch,
+    /// Purchase Record Mismatch
+    #[error("Purchase Record Mismatch")]
+    PurchaseRecordMismatch,
+    /// Invalid Whitelist Account
+    #[error("Invalid Whitelist Account")]
+    InvalidWhitelist,
+    /// Duplicate Account
+    #[error("Duplicate Account")]
+    DuplicateAccount,
+    /// Invalid Token Sale Amount
+    #[error("Invalid Token Sale Amount")]
+    InvalidTokenSaleAmount,
+    /// Invalid Sale Token Balance
+    #[error("Invalid Sale Token Balance")]
+    InvalidSaleTokenBalance,
+    /// Payment Mint Not Accepted
+    #[error("Payment Mint Not Accepted")]
+    PaymentMintNotAccepted,
+    /// Too Many Payment Mints
+    #[error("Too Many Payment Mints")]
+    TooManyPaymentMints,
+    /// Invalid Oracle
+    #[error("Invalid Oracle")]
+    InvalidOracle,
+    /// Stale Oracle Price
+    #[error("Stale Oracle Price")]
+    StaleOraclePrice,
+    /// Oracle Confidence Too Wide
+    #[error("Oracle Confidence Too Wide")]
+    OracleConfidenceTooWide,
+    /// Invalid Voucher
+    #[error("Invalid Voucher")]
+    InvalidVoucher,
+    /// Voucher Expired
+    #[error("Voucher Expired")]
+    VoucherExpired,
+    /// Invalid Merkle Proof
+    #[error("Invalid Merkle Proof")]
+    InvalidMerkleProof,
+    /// Sale Proceeds Not Escrowed
+    #[error("Sale Proceeds Not Escrowed")]
+    ProceedsNotEscrowed,
+    /// Invalid Sale Role
+    #[error("Invalid Sale Role")]
+    InvalidSaleRole,
 }
 
 impl From<TokenSaleError> for ProgramError {
@@ -1,8 +1,16 @@
-use solana_program::program_error::ProgramError;
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_program, sysvar,
+};
 use std::convert::TryInto;
 use std::mem::size_of;
 
 use crate::error::TokenSaleError::InvalidInstruction;
+use crate::merkle::MerkleProof;
+use crate::processor::Processor;
+use crate::state::{PricingMode, SaleMode, SaleRole};
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum TokenSaleInstruction {
@@ -14,26 +22,42 @@ pub enum TokenSaleInstruction {
     ///
     /// 0. `[signer]` The account initialising the sale
     /// 1. `[writable]` Account holding token sale init info
-    /// 2. `[writable]` Pool token account for receiving funds from sale
-    /// 3. `[writable]` Sale token account for holding the tokens for sale
+    /// 2. `[writable]` Pool token account for receiving funds from sale, handed to the
+    ///    Sale program derived address as escrow if the sale has a soft cap or is pro-rata
+    /// 3. `[writable]` Empty sale token account for holding the tokens for sale
     /// 4. `[]` Account holding token whitelist info
     /// 5. `[]` The token program
     /// 6. `[]` The token whitelist program
     /// 7. `[]` SYSVAR_RENT_PUBKEY
+    /// 8. `[]` Mint of the tokens for sale
+    /// 9. `[]` Mint of the usd tokens accepted as payment
     InitTokenSale {
-        token_sale_amount: u64, // amount of tokens for sale, to be deposited into sale
-        usd_min_amount: u64, // minimum purchase amount in usd
-        usd_max_amount: u64, // maximum purchase amount in usd
-        token_sale_price: u64, // token sale price (multiplied by 100 for easy arithmetic)
-        token_sale_time: u64, // time when token sale goes live
+        token_sale_amount: u64, // amount of tokens for sale, to be deposited into sale (greater than 0)
+        usd_min_amount: u64, // minimum purchase amount in usd, at most usd_max_amount
+        usd_max_amount: u64, // maximum purchase amount in usd (greater than 0)
+        token_sale_price: u64, // usd per whole token for sale, multiplied by PRICE_MULTIPLIER (greater than 0 at a fixed price)
+        token_sale_time: u64, // time when token sale goes live, not in the past and before token_sale_end_time
+        token_sale_end_time: u64, // time after which purchases are no longer accepted
+        vesting_tge_percent: u8, // percentage of purchased tokens unlocked at vesting start (100 disables vesting)
+        vesting_start_time: u64, // time when the TGE share unlocks and linear vesting begins
+        vesting_cliff_time: u64, // time before which nothing beyond the TGE share unlocks
+        vesting_end_time: u64, // time when all purchased tokens are unlocked
+        soft_cap_amount: u64, // minimum raise in usd below which buyers are refunded (0 disables soft cap)
+        sale_mode: SaleMode, // first come first served purchases or pro-rata commitments
+        pricing_mode: PricingMode, // fixed token sale price or dutch auction
+        auction_start_price: u64, // auction price when token sale goes live, in the same units as token_sale_price
+        auction_floor_price: u64, // lowest auction price, in the same units as token_sale_price
+        auction_decay_amount: u64, // auction price decrease per decay interval
+        auction_decay_interval: u64, // seconds between auction price decreases
     },
 
-    /// Instruction to fund token sale account with tokens
+    /// Instruction to fund token sale account with tokens. A sale accepts purchases
+    /// and commitments only once it has been funded.
     ///
     /// Accounts expected by FundTokenSale
     ///
     /// 0. `[signer]` The account funding the sale
-    /// 1. `[]` Account holding token sale init info
+    /// 1. `[writable]` Account holding token sale init info
     /// 2. `[writable]` Pool token account containing tokens for sale
     /// 3. `[writable]` Sale token account for holding the tokens for sale
     /// 4. `[]` The token program
@@ -43,29 +67,42 @@ pub enum TokenSaleInstruction {
 
     /// Instruction to execute token sale. User purchases tokens from token sale
     /// account and transfer USDT to the pool account. It is done via atomic swap.
+    /// If the sale vests purchased tokens, they stay in the sale token account and
+    /// are credited to the user purchase record instead. Paying in one of the payment
+    /// mints set with SetPaymentMint uses the price and pool token account of that mint.
+    /// Passing the user wallet as the funds account pays in native SOL, which is wrapped
+    /// into the pool token account set for the native mint. In sales with a voucher signer
+    /// the allocation is read from the voucher verified by the previous instruction, and in
+    /// sales with a merkle root from the merkle proof trailing the instruction data.
     ///
     /// Accounts expected by ExecuteTokenSale
     ///
-    /// 0. `[signer]` The account buying from the sale
-    /// 1. `[]` Account holding sale init info
+    /// 0. `[signer, writable]` The account buying from the sale, pays for the purchase record
+    /// 1. `[writable]` Account holding sale init info
     /// 2. `[writable]` Sale token account containing tokens for sale
     /// 3. `[writable]` User token account for receiving tokens purchased
-    /// 4. `[writable]` User token account for sending funds
+    /// 4. `[writable]` User token account for sending funds, or the user wallet to pay in SOL
     /// 5. `[writable]` Pool token account for receiving user funds
     /// 6. `[]` The Sale program derived address
     /// 7. `[]` The token program
     /// 8. `[]` Account holding token whitelist map
-    /// 9. `[writable]` Account holding token whitelist info
+    /// 9. `[]` Account holding token whitelist info, or the instructions sysvar with a voucher
     /// 10. `[]` The token whitelist program
+    /// 11. `[writable]` User purchase record program derived address
+    /// 12. `[]` The system program
+    /// 13. `[]` Payment config program derived address, read when paying in one of its payment mints
+    /// 14. `[]` Oracle price account, read when paying in native SOL in a sale with an oracle
     ExecuteTokenSale {
-        usd_amount: u64, // purchase amount in usd
+        usd_amount: u64, // purchase amount in base units of the mint of the user token account for sending funds
+        max_price: u64, // highest acceptable auction price, ignored at fixed price
+        merkle_proof: Option<MerkleProof>, // allocation proof, required in sales with a merkle root
     },
 
     /// Instruction to pause token sale
     ///
     /// Accounts expected by PauseTokenSale
     ///
-    /// 0. `[signer]` The account which owns token sale init
+    /// 0. `[signer]` The guardian of the token sale
     /// 1. `[writable]` Account holding token sale init info
     PauseTokenSale {
     },
@@ -74,7 +111,7 @@ pub enum TokenSaleInstruction {
     ///
     /// Accounts expected by ResumeTokenSale
     ///
-    /// 0. `[signer]` The account which owns token sale init
+    /// 0. `[signer]` The operator of the token sale
     /// 1. `[writable]` Account holding token sale init info
     ResumeTokenSale {
     },
@@ -83,10 +120,272 @@ pub enum TokenSaleInstruction {
     ///
     /// Accounts expected by EndTokenSale
     ///
-    /// 0. `[signer]` The account which owns token sale init
+    /// 0. `[signer]` The operator of the token sale
     /// 1. `[writable]` Account holding token sale init info
     EndTokenSale {
     },
+
+    /// Instruction to withdraw tokens left unsold in the sale token account
+    /// once the token sale has ended. Tokens returned by later refunds can be
+    /// withdrawn by repeating the instruction.
+    ///
+    /// Accounts expected by WithdrawUnsoldTokens
+    ///
+    /// 0. `[signer]` The account which owns token sale init
+    /// 1. `[writable]` Account holding token sale init info
+    /// 2. `[writable]` Sale token account containing unsold tokens
+    /// 3. `[writable]` Token account for receiving unsold tokens
+    /// 4. `[]` The Sale program derived address
+    /// 5. `[]` The token program
+    WithdrawUnsoldTokens {
+    },
+
+    /// Instruction to claim purchased tokens unlocked by the vesting schedule
+    ///
+    /// Accounts expected by ClaimVestedTokens
+    ///
+    /// 0. `[signer]` The account which purchased from the sale
+    /// 1. `[writable]` Account holding token sale init info
+    /// 2. `[writable]` User purchase record program derived address
+    /// 3. `[writable]` Sale token account containing vested tokens
+    /// 4. `[writable]` User token account for receiving claimed tokens
+    /// 5. `[]` The Sale program derived address
+    /// 6. `[]` The token program
+    ClaimVestedTokens {
+    },
+
+    /// Instruction to refund a purchase once the sale has ended below its soft cap.
+    /// The user gets the USDT paid back and returns the tokens delivered so far,
+    /// tokens still vesting are released back to the sale.
+    ///
+    /// Accounts expected by RefundPurchase
+    ///
+    /// 0. `[signer]` The account which purchased from the sale
+    /// 1. `[writable]` Account holding token sale init info
+    /// 2. `[writable]` User purchase record program derived address
+    /// 3. `[writable]` Pool token account holding the escrowed funds
+    /// 4. `[writable]` User token account for receiving the refund
+    /// 5. `[writable]` Sale token account for receiving returned tokens
+    /// 6. `[writable]` User token account for returning delivered tokens
+    /// 7. `[]` The Sale program derived address
+    /// 8. `[]` The token program
+    RefundPurchase {
+    },
+
+    /// Instruction to withdraw escrowed sale proceeds once the soft cap is reached.
+    /// Proceeds of a pro-rata sale can be withdrawn once its commit window closes, and
+    /// cover the fills of the commitments settled so far.
+    ///
+    /// Accounts expected by WithdrawSaleProceeds
+    ///
+    /// 0. `[signer]` The account which owns token sale init
+    /// 1. `[writable]` Account holding token sale init info
+    /// 2. `[writable]` Pool token account holding the escrowed funds
+    /// 3. `[writable]` Token account for receiving the proceeds
+    /// 4. `[]` The Sale program derived address
+    /// 5. `[]` The token program
+    WithdrawSaleProceeds {
+    },
+
+    /// Instruction to commit USDT to a pro-rata sale while its commit window is open.
+    /// Commitments accumulate up to the user allocation and are held in escrow.
+    /// In sales with a voucher signer the allocation is read from the voucher verified
+    /// by the previous instruction, and in sales with a merkle root from the merkle proof
+    /// trailing the instruction data.
+    ///
+    /// Accounts expected by CommitToSale
+    ///
+    /// 0. `[signer, writable]` The account committing to the sale
+    /// 1. `[writable]` Account holding sale init info
+    /// 2. `[writable]` User token account for sending funds
+    /// 3. `[writable]` Pool token account holding the escrowed funds
+    /// 4. `[writable]` User purchase record program derived address
+    /// 5. `[]` The token program
+    /// 6. `[]` Account holding token whitelist map
+    /// 7. `[]` Account holding token whitelist info, or the instructions sysvar with a voucher
+    /// 8. `[]` The token whitelist program
+    /// 9. `[]` The system program
+    CommitToSale {
+        usd_amount: u64, // commitment amount in usd
+        merkle_proof: Option<MerkleProof>, // allocation proof, required in sales with a merkle root
+    },
+
+    /// Instruction to settle a pro-rata commitment once the commit window has closed.
+    /// The user receives the pro-rata share of tokens for sale (credited to the purchase
+    /// record if the sale vests) and the unfilled USDT back from escrow.
+    ///
+    /// Accounts expected by SettleCommitment
+    ///
+    /// 0. `[signer]` The account which committed to the sale
+    /// 1. `[writable]` Account holding token sale init info
+    /// 2. `[writable]` User purchase record program derived address
+    /// 3. `[writable]` Pool token account holding the escrowed funds
+    /// 4. `[writable]` User token account for receiving the unfilled funds
+    /// 5. `[writable]` Sale token account containing tokens for sale
+    /// 6. `[writable]` User token account for receiving tokens purchased
+    /// 7. `[]` The Sale program derived address
+    /// 8. `[]` The token program
+    SettleCommitment {
+    },
+
+    /// Instruction to move a token sale account stored in an older layout to the
+    /// current layout. The account is reallocated in place, keeping every stored pubkey
+    /// and amount, and version 1 sale token accounts are handed from the shared version 1
+    /// authority to the sale program derived address. Version 1 sales whose sale token
+    /// account holds less than the token sale amount still need FundTokenSale.
+    ///
+    /// Accounts expected by MigrateTokenSale
+    ///
+    /// 0. `[signer, writable]` The account which owns token sale init, pays for the larger account
+    /// 1. `[writable]` Account holding token sale init info
+    /// 2. `[writable]` Sale token account containing tokens for sale
+    /// 3. `[]` Pool token account for receiving funds from sale
+    /// 4. `[]` Mint of the tokens for sale
+    /// 5. `[]` Mint of the usd token paid for the sale
+    /// 6. `[]` The version 1 Sale program derived address
+    /// 7. `[]` The token program
+    /// 8. `[]` The system program
+    MigrateTokenSale {
+    },
+
+    /// Instruction to correct the purchase limits, price and start time of a sale
+    /// before it goes live
+    ///
+    /// Accounts expected by UpdateSaleParameters
+    ///
+    /// 0. `[signer]` The operator of the token sale
+    /// 1. `[writable]` Account holding token sale init info
+    UpdateSaleParameters {
+        usd_min_amount: u64, // minimum purchase amount in usd
+        usd_max_amount: u64, // maximum purchase amount in usd
+        token_sale_price: u64, // usd per whole token for sale, multiplied by PRICE_MULTIPLIER
+        token_sale_time: u64, // time when token sale goes live
+    },
+
+    /// Instruction to propose a new owner of the token sale. Ownership moves only once
+    /// the proposed account accepts it, proposing the default pubkey cancels the proposal.
+    ///
+    /// Accounts expected by ProposeAdmin
+    ///
+    /// 0. `[signer]` The account which owns token sale init
+    /// 1. `[writable]` Account holding token sale init info
+    ProposeAdmin {
+        new_admin_pubkey: Pubkey, // account proposed as the new owner of the sale
+    },
+
+    /// Instruction to accept ownership of the token sale proposed by ProposeAdmin.
+    /// The guardian and operator roles held by the previous owner move to the new owner.
+    ///
+    /// Accounts expected by AcceptAdmin
+    ///
+    /// 0. `[signer]` The account proposed as the new owner of the sale
+    /// 1. `[writable]` Account holding token sale init info
+    AcceptAdmin {
+    },
+
+    /// Instruction to assign the guardian or operator role of the token sale.
+    /// Both roles are held by the account initialising the sale until reassigned.
+    ///
+    /// Accounts expected by SetSaleRole
+    ///
+    /// 0. `[signer]` The account which owns token sale init
+    /// 1. `[writable]` Account holding token sale init info
+    SetSaleRole {
+        role: SaleRole, // guardian or operator, the admin changes through ProposeAdmin
+        role_pubkey: Pubkey, // account assigned the role
+    },
+
+    /// Instruction to cancel a token sale before it goes live. Every token in the
+    /// sale token account is returned and the sale accepts no further instructions.
+    ///
+    /// Accounts expected by CancelTokenSale
+    ///
+    /// 0. `[signer]` The account which owns token sale init
+    /// 1. `[writable]` Account holding token sale init info
+    /// 2. `[writable]` Sale token account containing tokens for sale
+    /// 3. `[writable]` Token account for receiving the returned tokens
+    /// 4. `[]` The Sale program derived address
+    /// 5. `[]` The token program
+    CancelTokenSale {
+    },
+
+    /// Instruction to close an ended or cancelled token sale once its sale token account
+    /// is empty. The sale token account, and the pool token account if it is held in
+    /// escrow, are closed and all rent is returned to the owner of the sale.
+    ///
+    /// Accounts expected by CloseTokenSale
+    ///
+    /// 0. `[signer, writable]` The account which owns token sale init, receives the rent
+    /// 1. `[writable]` Account holding token sale init info
+    /// 2. `[writable]` Sale token account, must be empty
+    /// 3. `[writable]` Pool token account, must be empty if held in escrow
+    /// 4. `[]` The Sale program derived address
+    /// 5. `[]` The token program
+    /// 6. `[writable]` Payment config program derived address, closed if it exists
+    CloseTokenSale {
+    },
+
+    /// Instruction to accept purchases in another payment mint before the sale goes live.
+    /// Purchases in the mint are paid into its own pool token account at its own price
+    /// and counted in usd at the token sale price. A zero price stops accepting the mint.
+    /// Setting the native mint with a wrapped SOL pool token account accepts native SOL.
+    /// Sales with a fixed price and without escrow only.
+    ///
+    /// Accounts expected by SetPaymentMint
+    ///
+    /// 0. `[signer, writable]` The account which owns token sale init, pays for the payment config
+    /// 1. `[writable]` Account holding token sale init info
+    /// 2. `[writable]` Payment config program derived address
+    /// 3. `[]` The payment mint
+    /// 4. `[]` Pool token account for receiving payments in the payment mint
+    /// 5. `[]` The system program
+    SetPaymentMint {
+        price: u64, // payment mint per whole token for sale, multiplied by PRICE_MULTIPLIER
+    },
+
+    /// Instruction to price payments in native SOL with a Pyth price account quoting SOL
+    /// in usd before the sale goes live. Purchases are rejected while the price is older
+    /// than `max_age` or its confidence interval is wider than `max_confidence_bps` of the
+    /// price. A zero max age removes the oracle. Native SOL is still accepted with
+    /// SetPaymentMint, which sets its pool token account.
+    ///
+    /// Accounts expected by SetOracle
+    ///
+    /// 0. `[signer]` The account which owns token sale init
+    /// 1. `[writable]` Account holding token sale init info
+    /// 2. `[]` Pyth price account quoting SOL in usd, owned by the Pyth program
+    SetOracle {
+        max_age: u64, // seconds
+        max_confidence_bps: u16,
+    },
+
+    /// Instruction to read allocations from vouchers signed by `voucher_signer_pubkey`
+    /// instead of the whitelist program before the sale goes live. A voucher is the packed
+    /// (sale, user, usd allocation, expiry time) verified by an Ed25519 program instruction
+    /// right before the purchase, and purchases against it are tracked in the user purchase
+    /// record. The default pubkey reads allocations from the whitelist program again.
+    ///
+    /// Accounts expected by SetVoucherSigner
+    ///
+    /// 0. `[signer]` The account which owns token sale init
+    /// 1. `[writable]` Account holding token sale init info
+    SetVoucherSigner {
+        voucher_signer_pubkey: Pubkey,
+    },
+
+    /// Instruction to read allocations from a merkle whitelist instead of the whitelist
+    /// program before the sale goes live. Leaves hash the (user, usd allocation) of every
+    /// buyer, who passes the allocation and its proof with each purchase; purchases against
+    /// it are tracked in the user purchase record. A root of all zeros reads allocations
+    /// from the whitelist program again.
+    ///
+    /// Accounts expected by SetMerkleRoot
+    ///
+    /// 0. `[signer]` The account which owns token sale init
+    /// 1. `[writable]` Account holding token sale init info
+    SetMerkleRoot {
+        merkle_root: [u8; 32],
+    },
 }
 
 impl TokenSaleInstruction {
@@ -96,40 +395,124 @@ impl TokenSaleInstruction {
 
         Ok(match tag {
             0 => {
-                let (token_sale_amount, rest) = rest.split_at(8);
+                let token_sale_amount = rest.get(..8).ok_or(InvalidInstruction)?;
+                let rest = &rest[8..];
                 let token_sale_amount = token_sale_amount
                     .try_into()
                     .ok()
                     .map(u64::from_le_bytes)
                     .ok_or(InvalidInstruction)?;
 
-                let (usd_min_amount, rest) = rest.split_at(8);
+                let usd_min_amount = rest.get(..8).ok_or(InvalidInstruction)?;
+                let rest = &rest[8..];
                 let usd_min_amount = usd_min_amount
                     .try_into()
                     .ok()
                     .map(u64::from_le_bytes)
                     .ok_or(InvalidInstruction)?;
 
-                let (usd_max_amount, rest) = rest.split_at(8);
+                let usd_max_amount = rest.get(..8).ok_or(InvalidInstruction)?;
+                let rest = &rest[8..];
                 let usd_max_amount = usd_max_amount
                     .try_into()
                     .ok()
                     .map(u64::from_le_bytes)
                     .ok_or(InvalidInstruction)?;
 
-                let (token_sale_price, rest) = rest.split_at(8);
+                let token_sale_price = rest.get(..8).ok_or(InvalidInstruction)?;
+                let rest = &rest[8..];
                 let token_sale_price = token_sale_price
                     .try_into()
                     .ok()
                     .map(u64::from_le_bytes)
                     .ok_or(InvalidInstruction)?;
 
-                let (token_sale_time, _rest) = rest.split_at(8);
+                let token_sale_time = rest.get(..8).ok_or(InvalidInstruction)?;
+                let rest = &rest[8..];
                 let token_sale_time = token_sale_time
                     .try_into()
                     .ok()
                     .map(u64::from_le_bytes)
                     .ok_or(InvalidInstruction)?;
+
+                let token_sale_end_time = rest.get(..8).ok_or(InvalidInstruction)?;
+                let rest = &rest[8..];
+                let token_sale_end_time = token_sale_end_time
+                    .try_into()
+                    .ok()
+                    .map(u64::from_le_bytes)
+                    .ok_or(InvalidInstruction)?;
+
+                let (&vesting_tge_percent, rest) = rest.split_first().ok_or(InvalidInstruction)?;
+
+                let vesting_start_time = rest.get(..8).ok_or(InvalidInstruction)?;
+                let rest = &rest[8..];
+                let vesting_start_time = vesting_start_time
+                    .try_into()
+                    .ok()
+                    .map(u64::from_le_bytes)
+                    .ok_or(InvalidInstruction)?;
+
+                let vesting_cliff_time = rest.get(..8).ok_or(InvalidInstruction)?;
+                let rest = &rest[8..];
+                let vesting_cliff_time = vesting_cliff_time
+                    .try_into()
+                    .ok()
+                    .map(u64::from_le_bytes)
+                    .ok_or(InvalidInstruction)?;
+
+                let vesting_end_time = rest.get(..8).ok_or(InvalidInstruction)?;
+                let rest = &rest[8..];
+                let vesting_end_time = vesting_end_time
+                    .try_into()
+                    .ok()
+                    .map(u64::from_le_bytes)
+                    .ok_or(InvalidInstruction)?;
+
+                let soft_cap_amount = rest.get(..8).ok_or(InvalidInstruction)?;
+                let rest = &rest[8..];
+                let soft_cap_amount = soft_cap_amount
+                    .try_into()
+                    .ok()
+                    .map(u64::from_le_bytes)
+                    .ok_or(InvalidInstruction)?;
+
+                let (&sale_mode, rest) = rest.split_first().ok_or(InvalidInstruction)?;
+                let sale_mode = SaleMode::from_u8(sale_mode).ok_or(InvalidInstruction)?;
+
+                let (&pricing_mode, rest) = rest.split_first().ok_or(InvalidInstruction)?;
+                let pricing_mode = PricingMode::from_u8(pricing_mode).ok_or(InvalidInstruction)?;
+
+                let auction_start_price = rest.get(..8).ok_or(InvalidInstruction)?;
+                let rest = &rest[8..];
+                let auction_start_price = auction_start_price
+                    .try_into()
+                    .ok()
+                    .map(u64::from_le_bytes)
+                    .ok_or(InvalidInstruction)?;
+
+                let auction_floor_price = rest.get(..8).ok_or(InvalidInstruction)?;
+                let rest = &rest[8..];
+                let auction_floor_price = auction_floor_price
+                    .try_into()
+                    .ok()
+                    .map(u64::from_le_bytes)
+                    .ok_or(InvalidInstruction)?;
+
+                let auction_decay_amount = rest.get(..8).ok_or(InvalidInstruction)?;
+                let rest = &rest[8..];
+                let auction_decay_amount = auction_decay_amount
+                    .try_into()
+                    .ok()
+                    .map(u64::from_le_bytes)
+                    .ok_or(InvalidInstruction)?;
+
+                let auction_decay_interval = rest.get(..8).ok_or(InvalidInstruction)?;
+                let auction_decay_interval = auction_decay_interval
+                    .try_into()
+                    .ok()
+                    .map(u64::from_le_bytes)
+                    .ok_or(InvalidInstruction)?;
                     
                 Self::InitTokenSale {
                     token_sale_amount,
@@ -137,10 +520,22 @@ impl TokenSaleInstruction {
                     usd_max_amount,
                     token_sale_price,
                     token_sale_time,
+                    token_sale_end_time,
+                    vesting_tge_percent,
+                    vesting_start_time,
+                    vesting_cliff_time,
+                    vesting_end_time,
+                    soft_cap_amount,
+                    sale_mode,
+                    pricing_mode,
+                    auction_start_price,
+                    auction_floor_price,
+                    auction_decay_amount,
+                    auction_decay_interval,
                 }
             },
             1 => {
-                let (token_sale_amount, _rest) = rest.split_at(8);
+                let token_sale_amount = rest.get(..8).ok_or(InvalidInstruction)?;
                 let token_sale_amount = token_sale_amount
                     .try_into()
                     .ok()
@@ -150,14 +545,25 @@ impl TokenSaleInstruction {
                 Self::FundTokenSale {token_sale_amount}
             },
             2 => {
-                let (usd_amount, _rest) = rest.split_at(8);
+                let usd_amount = rest.get(..8).ok_or(InvalidInstruction)?;
+                let rest = &rest[8..];
                 let usd_amount = usd_amount
                     .try_into()
                     .ok()
                     .map(u64::from_le_bytes)
                     .ok_or(InvalidInstruction)?;
 
-                Self::ExecuteTokenSale {usd_amount}
+                let max_price = rest.get(..8).ok_or(InvalidInstruction)?;
+                let rest = &rest[8..];
+                let max_price = max_price
+                    .try_into()
+                    .ok()
+                    .map(u64::from_le_bytes)
+                    .ok_or(InvalidInstruction)?;
+
+                let merkle_proof = MerkleProof::unpack(rest).map_err(|_| InvalidInstruction)?;
+
+                Self::ExecuteTokenSale {usd_amount, max_price, merkle_proof}
             },
             3 => {
                 Self::PauseTokenSale {}
@@ -168,6 +574,155 @@ impl TokenSaleInstruction {
             5 => {
                 Self::EndTokenSale {}
             },
+            6 => {
+                Self::WithdrawUnsoldTokens {}
+            },
+            7 => {
+                Self::ClaimVestedTokens {}
+            },
+            8 => {
+                Self::RefundPurchase {}
+            },
+            9 => {
+                Self::WithdrawSaleProceeds {}
+            },
+            10 => {
+                let usd_amount = rest.get(..8).ok_or(InvalidInstruction)?;
+                let rest = &rest[8..];
+                let usd_amount = usd_amount
+                    .try_into()
+                    .ok()
+                    .map(u64::from_le_bytes)
+                    .ok_or(InvalidInstruction)?;
+
+                let merkle_proof = MerkleProof::unpack(rest).map_err(|_| InvalidInstruction)?;
+
+                Self::CommitToSale {usd_amount, merkle_proof}
+            },
+            11 => {
+                Self::SettleCommitment {}
+            },
+            12 => {
+                Self::MigrateTokenSale {}
+            },
+            13 => {
+                let usd_min_amount = rest.get(..8).ok_or(InvalidInstruction)?;
+                let rest = &rest[8..];
+                let usd_min_amount = usd_min_amount
+                    .try_into()
+                    .ok()
+                    .map(u64::from_le_bytes)
+                    .ok_or(InvalidInstruction)?;
+
+                let usd_max_amount = rest.get(..8).ok_or(InvalidInstruction)?;
+                let rest = &rest[8..];
+                let usd_max_amount = usd_max_amount
+                    .try_into()
+                    .ok()
+                    .map(u64::from_le_bytes)
+                    .ok_or(InvalidInstruction)?;
+
+                let token_sale_price = rest.get(..8).ok_or(InvalidInstruction)?;
+                let rest = &rest[8..];
+                let token_sale_price = token_sale_price
+                    .try_into()
+                    .ok()
+                    .map(u64::from_le_bytes)
+                    .ok_or(InvalidInstruction)?;
+
+                let token_sale_time = rest.get(..8).ok_or(InvalidInstruction)?;
+                let token_sale_time = token_sale_time
+                    .try_into()
+                    .ok()
+                    .map(u64::from_le_bytes)
+                    .ok_or(InvalidInstruction)?;
+
+                Self::UpdateSaleParameters {
+                    usd_min_amount,
+                    usd_max_amount,
+                    token_sale_price,
+                    token_sale_time,
+                }
+            },
+            14 => {
+                let new_admin_pubkey = rest.get(..32).ok_or(InvalidInstruction)?;
+                let new_admin_pubkey = new_admin_pubkey
+                    .try_into()
+                    .ok()
+                    .map(Pubkey::new_from_array)
+                    .ok_or(InvalidInstruction)?;
+
+                Self::ProposeAdmin {new_admin_pubkey}
+            },
+            15 => {
+                Self::AcceptAdmin {}
+            },
+            16 => {
+                let (&role, rest) = rest.split_first().ok_or(InvalidInstruction)?;
+                let role = SaleRole::from_u8(role).ok_or(InvalidInstruction)?;
+
+                let role_pubkey = rest.get(..32).ok_or(InvalidInstruction)?;
+                let role_pubkey = role_pubkey
+                    .try_into()
+                    .ok()
+                    .map(Pubkey::new_from_array)
+                    .ok_or(InvalidInstruction)?;
+
+                Self::SetSaleRole {role, role_pubkey}
+            },
+            17 => {
+                Self::CancelTokenSale {}
+            },
+            18 => {
+                Self::CloseTokenSale {}
+            },
+            19 => {
+                let price = rest.get(..8).ok_or(InvalidInstruction)?;
+                let price = price
+                    .try_into()
+                    .ok()
+                    .map(u64::from_le_bytes)
+                    .ok_or(InvalidInstruction)?;
+
+                Self::SetPaymentMint {price}
+            },
+            20 => {
+                let max_age = rest.get(..8).ok_or(InvalidInstruction)?;
+                let rest = &rest[8..];
+                let max_age = max_age
+                    .try_into()
+                    .ok()
+                    .map(u64::from_le_bytes)
+                    .ok_or(InvalidInstruction)?;
+
+                let max_confidence_bps = rest.get(..2).ok_or(InvalidInstruction)?;
+                let max_confidence_bps = max_confidence_bps
+                    .try_into()
+                    .ok()
+                    .map(u16::from_le_bytes)
+                    .ok_or(InvalidInstruction)?;
+
+                Self::SetOracle {max_age, max_confidence_bps}
+            },
+            21 => {
+                let voucher_signer_pubkey = rest.get(..32).ok_or(InvalidInstruction)?;
+                let voucher_signer_pubkey = voucher_signer_pubkey
+                    .try_into()
+                    .ok()
+                    .map(Pubkey::new_from_array)
+                    .ok_or(InvalidInstruction)?;
+
+                Self::SetVoucherSigner {voucher_signer_pubkey}
+            },
+            22 => {
+                let merkle_root = rest.get(..32).ok_or(InvalidInstruction)?;
+                let merkle_root = merkle_root
+                    .try_into()
+                    .ok()
+                    .ok_or(InvalidInstruction)?;
+
+                Self::SetMerkleRoot {merkle_root}
+            },
             _ => return Err(InvalidInstruction.into()),
         })
     }
@@ -182,6 +737,18 @@ impl TokenSaleInstruction {
                 usd_max_amount,
                 token_sale_price,
                 token_sale_time,
+                token_sale_end_time,
+                vesting_tge_percent,
+                vesting_start_time,
+                vesting_cliff_time,
+                vesting_end_time,
+                soft_cap_amount,
+                sale_mode,
+                pricing_mode,
+                auction_start_price,
+                auction_floor_price,
+                auction_decay_amount,
+                auction_decay_interval,
             } => {
                 buf.push(0);
                 buf.extend_from_slice(&token_sale_amount.to_le_bytes());
@@ -189,14 +756,30 @@ impl TokenSaleInstruction {
                 buf.extend_from_slice(&usd_max_amount.to_le_bytes());
                 buf.extend_from_slice(&token_sale_price.to_le_bytes());
                 buf.extend_from_slice(&token_sale_time.to_le_bytes());
+                buf.extend_from_slice(&token_sale_end_time.to_le_bytes());
+                buf.push(vesting_tge_percent);
+                buf.extend_from_slice(&vesting_start_time.to_le_bytes());
+                buf.extend_from_slice(&vesting_cliff_time.to_le_bytes());
+                buf.extend_from_slice(&vesting_end_time.to_le_bytes());
+                buf.extend_from_slice(&soft_cap_amount.to_le_bytes());
+                buf.push(sale_mode as u8);
+                buf.push(pricing_mode as u8);
+                buf.extend_from_slice(&auction_start_price.to_le_bytes());
+                buf.extend_from_slice(&auction_floor_price.to_le_bytes());
+                buf.extend_from_slice(&auction_decay_amount.to_le_bytes());
+                buf.extend_from_slice(&auction_decay_interval.to_le_bytes());
             }
             Self::FundTokenSale { token_sale_amount } => {
                 buf.push(1);
                 buf.extend_from_slice(&token_sale_amount.to_le_bytes());
             }
-            Self::ExecuteTokenSale { usd_amount } => {
+            Self::ExecuteTokenSale { usd_amount, max_price, ref merkle_proof } => {
                 buf.push(2);
                 buf.extend_from_slice(&usd_amount.to_le_bytes());
+                buf.extend_from_slice(&max_price.to_le_bytes());
+                if let Some(merkle_proof) = merkle_proof {
+                    merkle_proof.pack_into(&mut buf);
+                }
             }
             Self::PauseTokenSale {} => {
                 buf.push(3);
@@ -207,11 +790,782 @@ impl TokenSaleInstruction {
             Self::EndTokenSale {} => {
                 buf.push(5);
             }
+            Self::WithdrawUnsoldTokens {} => {
+                buf.push(6);
+            }
+            Self::ClaimVestedTokens {} => {
+                buf.push(7);
+            }
+            Self::RefundPurchase {} => {
+                buf.push(8);
+            }
+            Self::WithdrawSaleProceeds {} => {
+                buf.push(9);
+            }
+            Self::CommitToSale { usd_amount, ref merkle_proof } => {
+                buf.push(10);
+                buf.extend_from_slice(&usd_amount.to_le_bytes());
+                if let Some(merkle_proof) = merkle_proof {
+                    merkle_proof.pack_into(&mut buf);
+                }
+            }
+            Self::SettleCommitment {} => {
+                buf.push(11);
+            }
+            Self::MigrateTokenSale {} => {
+                buf.push(12);
+            }
+            Self::UpdateSaleParameters {
+                usd_min_amount,
+                usd_max_amount,
+                token_sale_price,
+                token_sale_time,
+            } => {
+                buf.push(13);
+                buf.extend_from_slice(&usd_min_amount.to_le_bytes());
+                buf.extend_from_slice(&usd_max_amount.to_le_bytes());
+                buf.extend_from_slice(&token_sale_price.to_le_bytes());
+                buf.extend_from_slice(&token_sale_time.to_le_bytes());
+            }
+            Self::ProposeAdmin { new_admin_pubkey } => {
+                buf.push(14);
+                buf.extend_from_slice(new_admin_pubkey.as_ref());
+            }
+            Self::AcceptAdmin {} => {
+                buf.push(15);
+            }
+            Self::SetSaleRole { role, role_pubkey } => {
+                buf.push(16);
+                buf.push(role as u8);
+                buf.extend_from_slice(role_pubkey.as_ref());
+            }
+            Self::CancelTokenSale {} => {
+                buf.push(17);
+            }
+            Self::CloseTokenSale {} => {
+                buf.push(18);
+            }
+            Self::SetPaymentMint { price } => {
+                buf.push(19);
+                buf.extend_from_slice(&price.to_le_bytes());
+            }
+            Self::SetOracle { max_age, max_confidence_bps } => {
+                buf.push(20);
+                buf.extend_from_slice(&max_age.to_le_bytes());
+                buf.extend_from_slice(&max_confidence_bps.to_le_bytes());
+            }
+            Self::SetVoucherSigner { voucher_signer_pubkey } => {
+                buf.push(21);
+                buf.extend_from_slice(voucher_signer_pubkey.as_ref());
+            }
+            Self::SetMerkleRoot { merkle_root } => {
+                buf.push(22);
+                buf.extend_from_slice(&merkle_root);
+            }
         };
         buf
     }
 }
 
+/// Creates an [InitTokenSale](enum.TokenSaleInstruction.html) instruction
+#[allow(clippy::too_many_arguments)]
+pub fn init_token_sale(
+    program_id: &Pubkey,
+    init_pubkey: &Pubkey,
+    token_sale_pubkey: &Pubkey,
+    pool_token_pubkey: &Pubkey,
+    sale_token_pubkey: &Pubkey,
+    whitelist_map_pubkey: &Pubkey,
+    whitelist_program_pubkey: &Pubkey,
+    sale_mint_pubkey: &Pubkey,
+    usd_mint_pubkey: &Pubkey,
+    token_sale_amount: u64,
+    usd_min_amount: u64,
+    usd_max_amount: u64,
+    token_sale_price: u64,
+    token_sale_time: u64,
+    token_sale_end_time: u64,
+    vesting_tge_percent: u8,
+    vesting_start_time: u64,
+    vesting_cliff_time: u64,
+    vesting_end_time: u64,
+    soft_cap_amount: u64,
+    sale_mode: SaleMode,
+    pricing_mode: PricingMode,
+    auction_start_price: u64,
+    auction_floor_price: u64,
+    auction_decay_amount: u64,
+    auction_decay_interval: u64,
+) -> Instruction {
+    let data = TokenSaleInstruction::InitTokenSale {
+        token_sale_amount,
+        usd_min_amount,
+        usd_max_amount,
+        token_sale_price,
+        token_sale_time,
+        token_sale_end_time,
+        vesting_tge_percent,
+        vesting_start_time,
+        vesting_cliff_time,
+        vesting_end_time,
+        soft_cap_amount,
+        sale_mode,
+        pricing_mode,
+        auction_start_price,
+        auction_floor_price,
+        auction_decay_amount,
+        auction_decay_interval,
+    }
+    .pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*init_pubkey, true),
+        AccountMeta::new(*token_sale_pubkey, false),
+        AccountMeta::new(*pool_token_pubkey, false),
+        AccountMeta::new(*sale_token_pubkey, false),
+        AccountMeta::new_readonly(*whitelist_map_pubkey, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(*whitelist_program_pubkey, false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(*sale_mint_pubkey, false),
+        AccountMeta::new_readonly(*usd_mint_pubkey, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a [FundTokenSale](enum.TokenSaleInstruction.html) instruction
+pub fn fund_token_sale(
+    program_id: &Pubkey,
+    funder_pubkey: &Pubkey,
+    token_sale_pubkey: &Pubkey,
+    source_token_pubkey: &Pubkey,
+    sale_token_pubkey: &Pubkey,
+    token_sale_amount: u64,
+) -> Instruction {
+    let data = TokenSaleInstruction::FundTokenSale { token_sale_amount }.pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*funder_pubkey, true),
+        AccountMeta::new(*token_sale_pubkey, false),
+        AccountMeta::new(*source_token_pubkey, false),
+        AccountMeta::new(*sale_token_pubkey, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates an [ExecuteTokenSale](enum.TokenSaleInstruction.html) instruction
+#[allow(clippy::too_many_arguments)]
+pub fn execute_token_sale(
+    program_id: &Pubkey,
+    user_pubkey: &Pubkey,
+    token_sale_pubkey: &Pubkey,
+    sale_token_pubkey: &Pubkey,
+    user_token_pubkey: &Pubkey,
+    user_usd_pubkey: &Pubkey,
+    pool_token_pubkey: &Pubkey,
+    whitelist_map_pubkey: &Pubkey,
+    whitelist_account_pubkey: &Pubkey,
+    whitelist_program_pubkey: &Pubkey,
+    usd_amount: u64,
+    max_price: u64,
+    merkle_proof: Option<MerkleProof>,
+) -> Instruction {
+    let data = TokenSaleInstruction::ExecuteTokenSale { usd_amount, max_price, merkle_proof }.pack();
+    let (authority_pubkey, _) = Processor::find_authority_address(program_id, token_sale_pubkey);
+    let (purchase_record_pubkey, _) =
+        Processor::find_purchase_record_address(program_id, token_sale_pubkey, user_pubkey);
+    let (payment_config_pubkey, _) = Processor::find_payment_config_address(program_id, token_sale_pubkey);
+
+    let accounts = vec![
+        AccountMeta::new(*user_pubkey, true),
+        AccountMeta::new(*token_sale_pubkey, false),
+        AccountMeta::new(*sale_token_pubkey, false),
+        AccountMeta::new(*user_token_pubkey, false),
+        AccountMeta::new(*user_usd_pubkey, false),
+        AccountMeta::new(*pool_token_pubkey, false),
+        AccountMeta::new_readonly(authority_pubkey, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(*whitelist_map_pubkey, false),
+        AccountMeta::new_readonly(*whitelist_account_pubkey, false),
+        AccountMeta::new_readonly(*whitelist_program_pubkey, false),
+        AccountMeta::new(purchase_record_pubkey, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(payment_config_pubkey, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates an [ExecuteTokenSale](enum.TokenSaleInstruction.html) instruction paying
+/// `lamports` of native SOL from the user wallet, wrapped into the pool token account
+/// set for the native mint. Sales with an oracle need its price account as `oracle_pubkey`.
+#[allow(clippy::too_many_arguments)]
+pub fn execute_token_sale_with_sol(
+    program_id: &Pubkey,
+    user_pubkey: &Pubkey,
+    token_sale_pubkey: &Pubkey,
+    sale_token_pubkey: &Pubkey,
+    user_token_pubkey: &Pubkey,
+    pool_token_pubkey: &Pubkey,
+    whitelist_map_pubkey: &Pubkey,
+    whitelist_account_pubkey: &Pubkey,
+    whitelist_program_pubkey: &Pubkey,
+    lamports: u64,
+    max_price: u64,
+    merkle_proof: Option<MerkleProof>,
+    oracle_pubkey: Option<&Pubkey>,
+) -> Instruction {
+    let mut instruction = execute_token_sale(
+        program_id,
+        user_pubkey,
+        token_sale_pubkey,
+        sale_token_pubkey,
+        user_token_pubkey,
+        user_pubkey,
+        pool_token_pubkey,
+        whitelist_map_pubkey,
+        whitelist_account_pubkey,
+        whitelist_program_pubkey,
+        lamports,
+        max_price,
+        merkle_proof,
+    );
+    if let Some(oracle_pubkey) = oracle_pubkey {
+        instruction.accounts.push(AccountMeta::new_readonly(*oracle_pubkey, false));
+    }
+    instruction
+}
+
+/// Creates an [ExecuteTokenSale](enum.TokenSaleInstruction.html) instruction in a sale
+/// with a voucher signer, reading the allocation from the voucher verified by the
+/// Ed25519 program instruction placed right before it
+#[allow(clippy::too_many_arguments)]
+pub fn execute_token_sale_with_voucher(
+    program_id: &Pubkey,
+    user_pubkey: &Pubkey,
+    token_sale_pubkey: &Pubkey,
+    sale_token_pubkey: &Pubkey,
+    user_token_pubkey: &Pubkey,
+    user_usd_pubkey: &Pubkey,
+    pool_token_pubkey: &Pubkey,
+    whitelist_map_pubkey: &Pubkey,
+    whitelist_program_pubkey: &Pubkey,
+    usd_amount: u64,
+    max_price: u64,
+) -> Instruction {
+    execute_token_sale(
+        program_id,
+        user_pubkey,
+        token_sale_pubkey,
+        sale_token_pubkey,
+        user_token_pubkey,
+        user_usd_pubkey,
+        pool_token_pubkey,
+        whitelist_map_pubkey,
+        &sysvar::instructions::id(),
+        whitelist_program_pubkey,
+        usd_amount,
+        max_price,
+        None,
+    )
+}
+
+/// Creates a [PauseTokenSale](enum.TokenSaleInstruction.html) instruction
+pub fn pause_token_sale(
+    program_id: &Pubkey,
+    guardian_pubkey: &Pubkey,
+    token_sale_pubkey: &Pubkey,
+) -> Instruction {
+    admin_instruction(program_id, guardian_pubkey, token_sale_pubkey, TokenSaleInstruction::PauseTokenSale {})
+}
+
+/// Creates a [ResumeTokenSale](enum.TokenSaleInstruction.html) instruction
+pub fn resume_token_sale(
+    program_id: &Pubkey,
+    operator_pubkey: &Pubkey,
+    token_sale_pubkey: &Pubkey,
+) -> Instruction {
+    admin_instruction(program_id, operator_pubkey, token_sale_pubkey, TokenSaleInstruction::ResumeTokenSale {})
+}
+
+/// Creates an [EndTokenSale](enum.TokenSaleInstruction.html) instruction
+pub fn end_token_sale(
+    program_id: &Pubkey,
+    operator_pubkey: &Pubkey,
+    token_sale_pubkey: &Pubkey,
+) -> Instruction {
+    admin_instruction(program_id, operator_pubkey, token_sale_pubkey, TokenSaleInstruction::EndTokenSale {})
+}
+
+/// Creates an instruction signed by a sale role that only touches the token sale account
+fn admin_instruction(
+    program_id: &Pubkey,
+    signer_pubkey: &Pubkey,
+    token_sale_pubkey: &Pubkey,
+    instruction: TokenSaleInstruction,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*signer_pubkey, true),
+        AccountMeta::new(*token_sale_pubkey, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction.pack(),
+    }
+}
+
+/// Creates a [WithdrawUnsoldTokens](enum.TokenSaleInstruction.html) instruction
+pub fn withdraw_unsold_tokens(
+    program_id: &Pubkey,
+    init_pubkey: &Pubkey,
+    token_sale_pubkey: &Pubkey,
+    sale_token_pubkey: &Pubkey,
+    destination_pubkey: &Pubkey,
+) -> Instruction {
+    let data = TokenSaleInstruction::WithdrawUnsoldTokens {}.pack();
+    let (authority_pubkey, _) = Processor::find_authority_address(program_id, token_sale_pubkey);
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*init_pubkey, true),
+        AccountMeta::new(*token_sale_pubkey, false),
+        AccountMeta::new(*sale_token_pubkey, false),
+        AccountMeta::new(*destination_pubkey, false),
+        AccountMeta::new_readonly(authority_pubkey, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a [ClaimVestedTokens](enum.TokenSaleInstruction.html) instruction
+pub fn claim_vested_tokens(
+    program_id: &Pubkey,
+    user_pubkey: &Pubkey,
+    token_sale_pubkey: &Pubkey,
+    sale_token_pubkey: &Pubkey,
+    user_token_pubkey: &Pubkey,
+) -> Instruction {
+    let data = TokenSaleInstruction::ClaimVestedTokens {}.pack();
+    let (authority_pubkey, _) = Processor::find_authority_address(program_id, token_sale_pubkey);
+    let (purchase_record_pubkey, _) =
+        Processor::find_purchase_record_address(program_id, token_sale_pubkey, user_pubkey);
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*user_pubkey, true),
+        AccountMeta::new(*token_sale_pubkey, false),
+        AccountMeta::new(purchase_record_pubkey, false),
+        AccountMeta::new(*sale_token_pubkey, false),
+        AccountMeta::new(*user_token_pubkey, false),
+        AccountMeta::new_readonly(authority_pubkey, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a [RefundPurchase](enum.TokenSaleInstruction.html) instruction
+pub fn refund_purchase(
+    program_id: &Pubkey,
+    user_pubkey: &Pubkey,
+    token_sale_pubkey: &Pubkey,
+    pool_token_pubkey: &Pubkey,
+    user_usd_pubkey: &Pubkey,
+    sale_token_pubkey: &Pubkey,
+    user_token_pubkey: &Pubkey,
+) -> Instruction {
+    let data = TokenSaleInstruction::RefundPurchase {}.pack();
+    let (authority_pubkey, _) = Processor::find_authority_address(program_id, token_sale_pubkey);
+    let (purchase_record_pubkey, _) =
+        Processor::find_purchase_record_address(program_id, token_sale_pubkey, user_pubkey);
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*user_pubkey, true),
+        AccountMeta::new(*token_sale_pubkey, false),
+        AccountMeta::new(purchase_record_pubkey, false),
+        AccountMeta::new(*pool_token_pubkey, false),
+        AccountMeta::new(*user_usd_pubkey, false),
+        AccountMeta::new(*sale_token_pubkey, false),
+        AccountMeta::new(*user_token_pubkey, false),
+        AccountMeta::new_readonly(authority_pubkey, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a [WithdrawSaleProceeds](enum.TokenSaleInstruction.html) instruction
+pub fn withdraw_sale_proceeds(
+    program_id: &Pubkey,
+    init_pubkey: &Pubkey,
+    token_sale_pubkey: &Pubkey,
+    pool_token_pubkey: &Pubkey,
+    destination_pubkey: &Pubkey,
+) -> Instruction {
+    let data = TokenSaleInstruction::WithdrawSaleProceeds {}.pack();
+    let (authority_pubkey, _) = Processor::find_authority_address(program_id, token_sale_pubkey);
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*init_pubkey, true),
+        AccountMeta::new(*token_sale_pubkey, false),
+        AccountMeta::new(*pool_token_pubkey, false),
+        AccountMeta::new(*destination_pubkey, false),
+        AccountMeta::new_readonly(authority_pubkey, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a [CommitToSale](enum.TokenSaleInstruction.html) instruction
+#[allow(clippy::too_many_arguments)]
+pub fn commit_to_sale(
+    program_id: &Pubkey,
+    user_pubkey: &Pubkey,
+    token_sale_pubkey: &Pubkey,
+    user_usd_pubkey: &Pubkey,
+    pool_token_pubkey: &Pubkey,
+    whitelist_map_pubkey: &Pubkey,
+    whitelist_account_pubkey: &Pubkey,
+    whitelist_program_pubkey: &Pubkey,
+    usd_amount: u64,
+    merkle_proof: Option<MerkleProof>,
+) -> Instruction {
+    let data = TokenSaleInstruction::CommitToSale { usd_amount, merkle_proof }.pack();
+    let (purchase_record_pubkey, _) =
+        Processor::find_purchase_record_address(program_id, token_sale_pubkey, user_pubkey);
+
+    let accounts = vec![
+        AccountMeta::new(*user_pubkey, true),
+        AccountMeta::new(*token_sale_pubkey, false),
+        AccountMeta::new(*user_usd_pubkey, false),
+        AccountMeta::new(*pool_token_pubkey, false),
+        AccountMeta::new(purchase_record_pubkey, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(*whitelist_map_pubkey, false),
+        AccountMeta::new_readonly(*whitelist_account_pubkey, false),
+        AccountMeta::new_readonly(*whitelist_program_pubkey, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a [SettleCommitment](enum.TokenSaleInstruction.html) instruction
+pub fn settle_commitment(
+    program_id: &Pubkey,
+    user_pubkey: &Pubkey,
+    token_sale_pubkey: &Pubkey,
+    pool_token_pubkey: &Pubkey,
+    user_usd_pubkey: &Pubkey,
+    sale_token_pubkey: &Pubkey,
+    user_token_pubkey: &Pubkey,
+) -> Instruction {
+    let data = TokenSaleInstruction::SettleCommitment {}.pack();
+    let (authority_pubkey, _) = Processor::find_authority_address(program_id, token_sale_pubkey);
+    let (purchase_record_pubkey, _) =
+        Processor::find_purchase_record_address(program_id, token_sale_pubkey, user_pubkey);
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*user_pubkey, true),
+        AccountMeta::new(*token_sale_pubkey, false),
+        AccountMeta::new(purchase_record_pubkey, false),
+        AccountMeta::new(*pool_token_pubkey, false),
+        AccountMeta::new(*user_usd_pubkey, false),
+        AccountMeta::new(*sale_token_pubkey, false),
+        AccountMeta::new(*user_token_pubkey, false),
+        AccountMeta::new_readonly(authority_pubkey, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a [MigrateTokenSale](enum.TokenSaleInstruction.html) instruction
+pub fn migrate_token_sale(
+    program_id: &Pubkey,
+    init_pubkey: &Pubkey,
+    token_sale_pubkey: &Pubkey,
+    sale_token_pubkey: &Pubkey,
+    pool_token_pubkey: &Pubkey,
+    sale_mint_pubkey: &Pubkey,
+    usd_mint_pubkey: &Pubkey,
+) -> Instruction {
+    let data = TokenSaleInstruction::MigrateTokenSale {}.pack();
+    let (authority_v1_pubkey, _) = Processor::find_authority_v1_address(program_id);
+
+    let accounts = vec![
+        AccountMeta::new(*init_pubkey, true),
+        AccountMeta::new(*token_sale_pubkey, false),
+        AccountMeta::new(*sale_token_pubkey, false),
+        AccountMeta::new_readonly(*pool_token_pubkey, false),
+        AccountMeta::new_readonly(*sale_mint_pubkey, false),
+        AccountMeta::new_readonly(*usd_mint_pubkey, false),
+        AccountMeta::new_readonly(authority_v1_pubkey, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates an [UpdateSaleParameters](enum.TokenSaleInstruction.html) instruction
+pub fn update_sale_parameters(
+    program_id: &Pubkey,
+    operator_pubkey: &Pubkey,
+    token_sale_pubkey: &Pubkey,
+    usd_min_amount: u64,
+    usd_max_amount: u64,
+    token_sale_price: u64,
+    token_sale_time: u64,
+) -> Instruction {
+    admin_instruction(
+        program_id,
+        operator_pubkey,
+        token_sale_pubkey,
+        TokenSaleInstruction::UpdateSaleParameters {
+            usd_min_amount,
+            usd_max_amount,
+            token_sale_price,
+            token_sale_time,
+        },
+    )
+}
+
+/// Creates a [ProposeAdmin](enum.TokenSaleInstruction.html) instruction
+pub fn propose_admin(
+    program_id: &Pubkey,
+    init_pubkey: &Pubkey,
+    token_sale_pubkey: &Pubkey,
+    new_admin_pubkey: &Pubkey,
+) -> Instruction {
+    admin_instruction(
+        program_id,
+        init_pubkey,
+        token_sale_pubkey,
+        TokenSaleInstruction::ProposeAdmin { new_admin_pubkey: *new_admin_pubkey },
+    )
+}
+
+/// Creates an [AcceptAdmin](enum.TokenSaleInstruction.html) instruction
+pub fn accept_admin(
+    program_id: &Pubkey,
+    new_admin_pubkey: &Pubkey,
+    token_sale_pubkey: &Pubkey,
+) -> Instruction {
+    admin_instruction(program_id, new_admin_pubkey, token_sale_pubkey, TokenSaleInstruction::AcceptAdmin {})
+}
+
+/// Creates a [SetSaleRole](enum.TokenSaleInstruction.html) instruction
+pub fn set_sale_role(
+    program_id: &Pubkey,
+    init_pubkey: &Pubkey,
+    token_sale_pubkey: &Pubkey,
+    role: SaleRole,
+    role_pubkey: &Pubkey,
+) -> Instruction {
+    admin_instruction(
+        program_id,
+        init_pubkey,
+        token_sale_pubkey,
+        TokenSaleInstruction::SetSaleRole { role, role_pubkey: *role_pubkey },
+    )
+}
+
+/// Creates a [CancelTokenSale](enum.TokenSaleInstruction.html) instruction
+pub fn cancel_token_sale(
+    program_id: &Pubkey,
+    init_pubkey: &Pubkey,
+    token_sale_pubkey: &Pubkey,
+    sale_token_pubkey: &Pubkey,
+    destination_pubkey: &Pubkey,
+) -> Instruction {
+    let data = TokenSaleInstruction::CancelTokenSale {}.pack();
+    let (authority_pubkey, _) = Processor::find_authority_address(program_id, token_sale_pubkey);
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*init_pubkey, true),
+        AccountMeta::new(*token_sale_pubkey, false),
+        AccountMeta::new(*sale_token_pubkey, false),
+        AccountMeta::new(*destination_pubkey, false),
+        AccountMeta::new_readonly(authority_pubkey, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a [CloseTokenSale](enum.TokenSaleInstruction.html) instruction
+pub fn close_token_sale(
+    program_id: &Pubkey,
+    init_pubkey: &Pubkey,
+    token_sale_pubkey: &Pubkey,
+    sale_token_pubkey: &Pubkey,
+    pool_token_pubkey: &Pubkey,
+) -> Instruction {
+    let data = TokenSaleInstruction::CloseTokenSale {}.pack();
+    let (authority_pubkey, _) = Processor::find_authority_address(program_id, token_sale_pubkey);
+    let (payment_config_pubkey, _) = Processor::find_payment_config_address(program_id, token_sale_pubkey);
+
+    let accounts = vec![
+        AccountMeta::new(*init_pubkey, true),
+        AccountMeta::new(*token_sale_pubkey, false),
+        AccountMeta::new(*sale_token_pubkey, false),
+        AccountMeta::new(*pool_token_pubkey, false),
+        AccountMeta::new_readonly(authority_pubkey, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(payment_config_pubkey, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a [SetPaymentMint](enum.TokenSaleInstruction.html) instruction
+pub fn set_payment_mint(
+    program_id: &Pubkey,
+    init_pubkey: &Pubkey,
+    token_sale_pubkey: &Pubkey,
+    payment_mint_pubkey: &Pubkey,
+    pool_token_pubkey: &Pubkey,
+    price: u64,
+) -> Instruction {
+    let data = TokenSaleInstruction::SetPaymentMint { price }.pack();
+    let (payment_config_pubkey, _) = Processor::find_payment_config_address(program_id, token_sale_pubkey);
+
+    let accounts = vec![
+        AccountMeta::new(*init_pubkey, true),
+        AccountMeta::new(*token_sale_pubkey, false),
+        AccountMeta::new(payment_config_pubkey, false),
+        AccountMeta::new_readonly(*payment_mint_pubkey, false),
+        AccountMeta::new_readonly(*pool_token_pubkey, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a [SetOracle](enum.TokenSaleInstruction.html) instruction
+pub fn set_oracle(
+    program_id: &Pubkey,
+    init_pubkey: &Pubkey,
+    token_sale_pubkey: &Pubkey,
+    oracle_pubkey: &Pubkey,
+    max_age: u64,
+    max_confidence_bps: u16,
+) -> Instruction {
+    let data = TokenSaleInstruction::SetOracle { max_age, max_confidence_bps }.pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*init_pubkey, true),
+        AccountMeta::new(*token_sale_pubkey, false),
+        AccountMeta::new_readonly(*oracle_pubkey, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a [SetVoucherSigner](enum.TokenSaleInstruction.html) instruction
+pub fn set_voucher_signer(
+    program_id: &Pubkey,
+    init_pubkey: &Pubkey,
+    token_sale_pubkey: &Pubkey,
+    voucher_signer_pubkey: &Pubkey,
+) -> Instruction {
+    let data = TokenSaleInstruction::SetVoucherSigner { voucher_signer_pubkey: *voucher_signer_pubkey }.pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*init_pubkey, true),
+        AccountMeta::new(*token_sale_pubkey, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a [SetMerkleRoot](enum.TokenSaleInstruction.html) instruction
+pub fn set_merkle_root(
+    program_id: &Pubkey,
+    init_pubkey: &Pubkey,
+    token_sale_pubkey: &Pubkey,
+    merkle_root: [u8; 32],
+) -> Instruction {
+    let data = TokenSaleInstruction::SetMerkleRoot { merkle_root }.pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*init_pubkey, true),
+        AccountMeta::new(*token_sale_pubkey, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -223,12 +1577,34 @@ mod tests {
         let max_amount: u64 = 500;
         let price: u64 = 10;
         let timestamp: u64 = 123456789;
+        let end_timestamp: u64 = 123460389;
+        let tge_percent: u8 = 20;
+        let vesting_start: u64 = 123470000;
+        let vesting_cliff: u64 = 125000000;
+        let vesting_end: u64 = 130000000;
+        let soft_cap: u64 = 300;
+        let auction_start: u64 = 50;
+        let auction_floor: u64 = 20;
+        let auction_decay: u64 = 5;
+        let auction_interval: u64 = 60;
         let check = TokenSaleInstruction::InitTokenSale {
             token_sale_amount: sale_amount,
             usd_min_amount: min_amount,
             usd_max_amount: max_amount,
             token_sale_price: price,
             token_sale_time: timestamp,
+            token_sale_end_time: end_timestamp,
+            vesting_tge_percent: tge_percent,
+            vesting_start_time: vesting_start,
+            vesting_cliff_time: vesting_cliff,
+            vesting_end_time: vesting_end,
+            soft_cap_amount: soft_cap,
+            sale_mode: SaleMode::ProRata,
+            pricing_mode: PricingMode::DutchAuction,
+            auction_start_price: auction_start,
+            auction_floor_price: auction_floor,
+            auction_decay_amount: auction_decay,
+            auction_decay_interval: auction_interval,
         };
         let packed = check.pack();
         let mut expect = vec![0];
@@ -237,6 +1613,18 @@ mod tests {
         expect.extend_from_slice(&max_amount.to_le_bytes());
         expect.extend_from_slice(&price.to_le_bytes());
         expect.extend_from_slice(&timestamp.to_le_bytes());
+        expect.extend_from_slice(&end_timestamp.to_le_bytes());
+        expect.push(tge_percent);
+        expect.extend_from_slice(&vesting_start.to_le_bytes());
+        expect.extend_from_slice(&vesting_cliff.to_le_bytes());
+        expect.extend_from_slice(&vesting_end.to_le_bytes());
+        expect.extend_from_slice(&soft_cap.to_le_bytes());
+        expect.push(1);
+        expect.push(1);
+        expect.extend_from_slice(&auction_start.to_le_bytes());
+        expect.extend_from_slice(&auction_floor.to_le_bytes());
+        expect.extend_from_slice(&auction_decay.to_le_bytes());
+        expect.extend_from_slice(&auction_interval.to_le_bytes());
         assert_eq!(packed, expect);
         let unpacked = TokenSaleInstruction::unpack(&expect).unwrap();
         assert_eq!(unpacked, check);
@@ -257,12 +1645,253 @@ mod tests {
     #[test]
     fn test_pack_execute_token_sale() {
         let amount: u64 = 100;
-        let check = TokenSaleInstruction::ExecuteTokenSale { usd_amount: amount };
+        let max_price: u64 = 45;
+        let check = TokenSaleInstruction::ExecuteTokenSale { usd_amount: amount, max_price, merkle_proof: None };
         let packed = check.pack();
         let mut expect = vec![2];
         expect.extend_from_slice(&amount.to_le_bytes());
+        expect.extend_from_slice(&max_price.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = TokenSaleInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let merkle_proof = MerkleProof { allocation_amount: 500, proof: vec![[1; 32], [2; 32]] };
+        let check = TokenSaleInstruction::ExecuteTokenSale {
+            usd_amount: amount,
+            max_price,
+            merkle_proof: Some(merkle_proof),
+        };
+        let packed = check.pack();
+        expect.extend_from_slice(&500u64.to_le_bytes());
+        expect.push(2);
+        expect.extend_from_slice(&[1; 32]);
+        expect.extend_from_slice(&[2; 32]);
+        assert_eq!(packed, expect);
+        let unpacked = TokenSaleInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+        assert!(TokenSaleInstruction::unpack(&expect[..expect.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_unpack_truncated_instruction() {
+        // execute token sale before max price was added
+        let mut input = vec![2];
+        input.extend_from_slice(&100u64.to_le_bytes());
+        assert_eq!(
+            TokenSaleInstruction::unpack(&input),
+            Err(InvalidInstruction.into()),
+        );
+
+        let check = TokenSaleInstruction::SetOracle { max_age: 60, max_confidence_bps: 100 };
+        let packed = check.pack();
+        assert_eq!(
+            TokenSaleInstruction::unpack(&packed[..packed.len() - 1]),
+            Err(InvalidInstruction.into()),
+        );
+    }
+
+    #[test]
+    fn test_pack_commit_to_sale() {
+        let amount: u64 = 100;
+        let check = TokenSaleInstruction::CommitToSale { usd_amount: amount, merkle_proof: None };
+        let packed = check.pack();
+        let mut expect = vec![10];
+        expect.extend_from_slice(&amount.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = TokenSaleInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let merkle_proof = MerkleProof { allocation_amount: 500, proof: vec![] };
+        let check = TokenSaleInstruction::CommitToSale { usd_amount: amount, merkle_proof: Some(merkle_proof) };
+        let packed = check.pack();
+        expect.extend_from_slice(&500u64.to_le_bytes());
+        expect.push(0);
         assert_eq!(packed, expect);
         let unpacked = TokenSaleInstruction::unpack(&expect).unwrap();
         assert_eq!(unpacked, check);
     }
+
+    #[test]
+    fn test_pack_update_sale_parameters() {
+        let min_amount: u64 = 100;
+        let max_amount: u64 = 500;
+        let price: u64 = 10;
+        let timestamp: u64 = 123456789;
+        let check = TokenSaleInstruction::UpdateSaleParameters {
+            usd_min_amount: min_amount,
+            usd_max_amount: max_amount,
+            token_sale_price: price,
+            token_sale_time: timestamp,
+        };
+        let packed = check.pack();
+        let mut expect = vec![13];
+        expect.extend_from_slice(&min_amount.to_le_bytes());
+        expect.extend_from_slice(&max_amount.to_le_bytes());
+        expect.extend_from_slice(&price.to_le_bytes());
+        expect.extend_from_slice(&timestamp.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = TokenSaleInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_propose_admin() {
+        let new_admin = Pubkey::new_unique();
+        let check = TokenSaleInstruction::ProposeAdmin { new_admin_pubkey: new_admin };
+        let packed = check.pack();
+        let mut expect = vec![14];
+        expect.extend_from_slice(new_admin.as_ref());
+        assert_eq!(packed, expect);
+        let unpacked = TokenSaleInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_set_sale_role() {
+        let guardian = Pubkey::new_unique();
+        let check = TokenSaleInstruction::SetSaleRole { role: SaleRole::Guardian, role_pubkey: guardian };
+        let packed = check.pack();
+        let mut expect = vec![16, 1];
+        expect.extend_from_slice(guardian.as_ref());
+        assert_eq!(packed, expect);
+        let unpacked = TokenSaleInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_set_payment_mint() {
+        let price: u64 = 150_000;
+        let check = TokenSaleInstruction::SetPaymentMint { price };
+        let packed = check.pack();
+        let mut expect = vec![19];
+        expect.extend_from_slice(&price.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = TokenSaleInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_set_oracle() {
+        let max_age: u64 = 60;
+        let max_confidence_bps: u16 = 200;
+        let check = TokenSaleInstruction::SetOracle { max_age, max_confidence_bps };
+        let packed = check.pack();
+        let mut expect = vec![20];
+        expect.extend_from_slice(&max_age.to_le_bytes());
+        expect.extend_from_slice(&max_confidence_bps.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = TokenSaleInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_set_voucher_signer() {
+        let voucher_signer_pubkey = Pubkey::new_unique();
+        let check = TokenSaleInstruction::SetVoucherSigner { voucher_signer_pubkey };
+        let packed = check.pack();
+        let mut expect = vec![21];
+        expect.extend_from_slice(voucher_signer_pubkey.as_ref());
+        assert_eq!(packed, expect);
+        let unpacked = TokenSaleInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_set_merkle_root() {
+        let merkle_root = [7; 32];
+        let check = TokenSaleInstruction::SetMerkleRoot { merkle_root };
+        let packed = check.pack();
+        let mut expect = vec![22];
+        expect.extend_from_slice(&merkle_root);
+        assert_eq!(packed, expect);
+        let unpacked = TokenSaleInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_execute_token_sale_accounts() {
+        let program_id = Pubkey::new_unique();
+        let user = Pubkey::new_unique();
+        let token_sale = Pubkey::new_unique();
+        let whitelist_account = Pubkey::new_unique();
+        let ix = execute_token_sale(
+            &program_id,
+            &user,
+            &token_sale,
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &whitelist_account,
+            &Pubkey::new_unique(),
+            100,
+            45,
+            None,
+        );
+        assert_eq!(ix.program_id, program_id);
+        assert_eq!(ix.accounts.len(), 14);
+        assert!(ix.accounts[0].is_signer && ix.accounts[0].is_writable);
+        assert_eq!(ix.accounts[6].pubkey, Processor::find_authority_address(&program_id, &token_sale).0);
+        assert_eq!(ix.accounts[9].pubkey, whitelist_account);
+        assert!(!ix.accounts[9].is_writable);
+        assert_eq!(
+            ix.accounts[11].pubkey,
+            Processor::find_purchase_record_address(&program_id, &token_sale, &user).0,
+        );
+        assert_eq!(ix.accounts[13].pubkey, Processor::find_payment_config_address(&program_id, &token_sale).0);
+        assert_eq!(
+            TokenSaleInstruction::unpack(&ix.data).unwrap(),
+            TokenSaleInstruction::ExecuteTokenSale { usd_amount: 100, max_price: 45, merkle_proof: None },
+        );
+    }
+
+    #[test]
+    fn test_execute_token_sale_with_voucher_accounts() {
+        let ix = execute_token_sale_with_voucher(
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            100,
+            45,
+        );
+        assert_eq!(ix.accounts.len(), 14);
+        assert_eq!(ix.accounts[9].pubkey, sysvar::instructions::id());
+        assert!(!ix.accounts[9].is_writable);
+    }
+
+    #[test]
+    fn test_execute_token_sale_with_sol_accounts() {
+        let program_id = Pubkey::new_unique();
+        let user = Pubkey::new_unique();
+        let pool_token = Pubkey::new_unique();
+        let oracle = Pubkey::new_unique();
+        let ix = execute_token_sale_with_sol(
+            &program_id,
+            &user,
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &pool_token,
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            100,
+            45,
+            None,
+            Some(&oracle),
+        );
+        assert_eq!(ix.accounts.len(), 15);
+        assert_eq!(ix.accounts[4].pubkey, user);
+        assert!(ix.accounts[4].is_writable);
+        assert_eq!(ix.accounts[5].pubkey, pool_token);
+        assert_eq!(ix.accounts[14].pubkey, oracle);
+        assert!(!ix.accounts[14].is_writable);
+    }
 }
@@ -1,7 +1,11 @@
 pub mod error;
 pub mod instruction;
+pub mod merkle;
+pub mod oracle;
 pub mod processor;
 pub mod state;
+pub mod validation;
+pub mod voucher;
 
 #[cfg(not(feature = "no-entrypoint"))]
 pub mod entrypoint;
@@ -0,0 +1,222 @@
+//! Merkle whitelist of (user, usd allocation) leaves. Sales store the root of the tree and
+//! buyers prove their allocation with the sibling hashes from their leaf up to the root.
+//! Pairs are hashed in sorted order, so proofs carry no left or right flags.
+
+use std::convert::TryInto;
+
+use solana_program::{hash::hashv, program_error::ProgramError, pubkey::Pubkey};
+
+#[cfg(not(target_os = "solana"))]
+use std::{collections::HashSet, str::FromStr};
+
+/// Longest proof accepted, enough for a tree of 2^24 leaves
+pub const MAX_MERKLE_PROOF_LEN: usize = 24;
+
+/// Usd allocation of a user with the proof of its leaf in the merkle whitelist
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MerkleProof {
+    pub allocation_amount: u64,
+    pub proof: Vec<[u8; 32]>,
+}
+
+impl MerkleProof {
+    /// Unpacks the proof trailing the instruction data, if any
+    pub fn unpack(input: &[u8]) -> Result<Option<Self>, ProgramError> {
+        if input.is_empty() {
+            return Ok(None);
+        }
+        if input.len() < 9 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let (allocation_amount, rest) = input.split_at(8);
+        let allocation_amount = allocation_amount
+            .try_into()
+            .ok()
+            .map(u64::from_le_bytes)
+            .ok_or(ProgramError::InvalidInstructionData)?;
+        let (&proof_len, rest) = rest.split_first().ok_or(ProgramError::InvalidInstructionData)?;
+        if proof_len as usize > MAX_MERKLE_PROOF_LEN || rest.len() != proof_len as usize * 32 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let proof = rest
+            .chunks_exact(32)
+            .map(|hash| hash.try_into().unwrap())
+            .collect();
+        Ok(Some(MerkleProof { allocation_amount, proof }))
+    }
+
+    pub fn pack_into(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.allocation_amount.to_le_bytes());
+        buf.push(self.proof.len() as u8);
+        for hash in &self.proof {
+            buf.extend_from_slice(hash);
+        }
+    }
+
+    /// Checks that the leaf of `user_pubkey` and this allocation hashes up to `merkle_root`
+    pub fn verify(&self, merkle_root: &[u8; 32], user_pubkey: &Pubkey) -> bool {
+        let computed_root = self.proof
+            .iter()
+            .fold(leaf_hash(user_pubkey, self.allocation_amount), |hash, sibling| node_hash(&hash, sibling));
+        computed_root == *merkle_root
+    }
+}
+
+/// Hash of the leaf of `user_pubkey` with `allocation_amount`
+pub fn leaf_hash(user_pubkey: &Pubkey, allocation_amount: u64) -> [u8; 32] {
+    hashv(&[&[0], user_pubkey.as_ref(), &allocation_amount.to_le_bytes()]).to_bytes()
+}
+
+/// Hash of the parent of two nodes, prefixed so a node never hashes like a leaf
+fn node_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let (left, right) = if left <= right { (left, right) } else { (right, left) };
+    hashv(&[&[1], left, right]).to_bytes()
+}
+
+/// Merkle whitelist built off-chain, giving the root to store in the sale and the proof of every user
+#[cfg(not(target_os = "solana"))]
+pub struct MerkleTree {
+    leaves: Vec<(Pubkey, u64)>,
+    layers: Vec<Vec<[u8; 32]>>,
+}
+
+#[cfg(not(target_os = "solana"))]
+impl MerkleTree {
+    /// Builds the tree of (user, usd allocation) leaves. The last node of a layer with an
+    /// odd number of nodes moves up unchanged.
+    pub fn new(leaves: Vec<(Pubkey, u64)>) -> Result<Self, String> {
+        if leaves.is_empty() {
+            return Err("merkle whitelist has no users".to_string());
+        }
+        let mut users = HashSet::new();
+        if let Some((user, _)) = leaves.iter().find(|(user, _)| !users.insert(*user)) {
+            return Err(format!("user {} is listed more than once", user));
+        }
+        let mut layers = vec![leaves
+            .iter()
+            .map(|(user, allocation_amount)| leaf_hash(user, *allocation_amount))
+            .collect::<Vec<_>>()];
+        while layers[layers.len() - 1].len() > 1 {
+            let layer = layers[layers.len() - 1]
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => node_hash(left, right),
+                    [node] => *node,
+                    _ => unreachable!(),
+                })
+                .collect();
+            layers.push(layer);
+        }
+        if layers.len() - 1 > MAX_MERKLE_PROOF_LEN {
+            return Err(format!("merkle whitelist has more than 2^{} users", MAX_MERKLE_PROOF_LEN));
+        }
+        Ok(MerkleTree { leaves, layers })
+    }
+
+    /// Builds the tree from CSV text with one `user,allocation` line per user, the allocation
+    /// in base units of the usd mint. Blank lines and a header line are skipped.
+    pub fn from_csv(csv: &str) -> Result<Self, String> {
+        let mut leaves = Vec::new();
+        for (index, line) in csv.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (user, allocation_amount) = match line.split(',').map(str::trim).collect::<Vec<_>>()[..] {
+                [user, allocation_amount] => (user, allocation_amount),
+                _ => return Err(format!("line {}: expected user,allocation", index + 1)),
+            };
+            let allocation_amount = match allocation_amount.parse::<u64>() {
+                Ok(allocation_amount) => allocation_amount,
+                Err(_) if leaves.is_empty() && Pubkey::from_str(user).is_err() => continue,
+                Err(_) => return Err(format!("line {}: invalid allocation {}", index + 1, allocation_amount)),
+            };
+            let user = Pubkey::from_str(user)
+                .map_err(|_| format!("line {}: invalid user {}", index + 1, user))?;
+            leaves.push((user, allocation_amount));
+        }
+        Self::new(leaves)
+    }
+
+    pub fn root(&self) -> [u8; 32] {
+        self.layers[self.layers.len() - 1][0]
+    }
+
+    pub fn leaves(&self) -> &[(Pubkey, u64)] {
+        &self.leaves
+    }
+
+    /// Proof of the allocation of `user_pubkey`, if it is in the whitelist
+    pub fn proof(&self, user_pubkey: &Pubkey) -> Option<MerkleProof> {
+        let mut index = self.leaves.iter().position(|(user, _)| user == user_pubkey)?;
+        let allocation_amount = self.leaves[index].1;
+        let mut proof = Vec::new();
+        for layer in &self.layers[..self.layers.len() - 1] {
+            if let Some(sibling) = layer.get(index ^ 1) {
+                proof.push(*sibling);
+            }
+            index /= 2;
+        }
+        Some(MerkleProof { allocation_amount, proof })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merkle_tree() {
+        let users: Vec<Pubkey> = (0..5).map(|_| Pubkey::new_unique()).collect();
+        let csv = users
+            .iter()
+            .enumerate()
+            .map(|(index, user)| format!("{},{}", user, (index as u64 + 1) * 1_000_000))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let tree = MerkleTree::from_csv(&format!("user,allocation\n{}\n", csv)).unwrap();
+        assert_eq!(tree.leaves().len(), 5);
+
+        for (index, user) in users.iter().enumerate() {
+            let merkle_proof = tree.proof(user).unwrap();
+            assert_eq!(merkle_proof.allocation_amount, (index as u64 + 1) * 1_000_000);
+            assert!(merkle_proof.verify(&tree.root(), user));
+            // the allocation is part of the leaf
+            let raised_proof = MerkleProof {
+                allocation_amount: merkle_proof.allocation_amount + 1,
+                ..merkle_proof.clone()
+            };
+            assert!(!raised_proof.verify(&tree.root(), user));
+
+            let mut packed = vec![];
+            merkle_proof.pack_into(&mut packed);
+            assert_eq!(MerkleProof::unpack(&packed), Ok(Some(merkle_proof)));
+        }
+        let other_user = Pubkey::new_unique();
+        assert_eq!(tree.proof(&other_user), None);
+        assert!(!tree.proof(&users[0]).unwrap().verify(&tree.root(), &other_user));
+    }
+
+    #[test]
+    fn test_merkle_tree_from_invalid_csv() {
+        let user = Pubkey::new_unique();
+        assert!(MerkleTree::from_csv("").is_err());
+        assert!(MerkleTree::from_csv(&format!("{},100\n{},200", user, user)).is_err());
+        assert!(MerkleTree::from_csv(&format!("{},-1", user)).is_err());
+        assert!(MerkleTree::from_csv("not a pubkey,100").is_err());
+        assert!(MerkleTree::from_csv(&format!("{},100,1", user)).is_err());
+
+        let tree = MerkleTree::from_csv(&format!("{},100", user)).unwrap();
+        assert_eq!(tree.root(), leaf_hash(&user, 100));
+        assert_eq!(tree.proof(&user).unwrap().proof, Vec::<[u8; 32]>::new());
+    }
+
+    #[test]
+    fn test_unpack_merkle_proof() {
+        assert_eq!(MerkleProof::unpack(&[]), Ok(None));
+        assert_eq!(MerkleProof::unpack(&[1; 8]), Err(ProgramError::InvalidInstructionData));
+        let mut packed = vec![];
+        MerkleProof { allocation_amount: 1, proof: vec![[2; 32]] }.pack_into(&mut packed);
+        assert_eq!(MerkleProof::unpack(&packed[..packed.len() - 1]), Err(ProgramError::InvalidInstructionData));
+    }
+}
@@ -0,0 +1,151 @@
+//! Pyth price account layout read by oracle priced sales. Only the header and the
+//! aggregate price are read; the price components of each publisher are skipped.
+
+use solana_program::{
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey,
+    pubkey::Pubkey,
+};
+
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+
+/// Program owning the Pyth price accounts, price accounts owned by any other
+/// program are rejected
+pub const PYTH_PROGRAM_ID: Pubkey = pubkey!("FsJ3A3u2vn5cTVofAjvy6y5kwABJAqYWpe4975bi2epH");
+
+/// Magic number at the start of every Pyth account
+pub const PYTH_MAGIC: u32 = 0xa1b2_c3d4;
+
+/// Layout version of Pyth accounts
+pub const PYTH_VERSION: u32 = 2;
+
+/// Account type of Pyth price accounts
+pub const PYTH_PRICE_ACCOUNT_TYPE: u32 = 3;
+
+/// Status of an aggregate price published while the product is trading
+pub const PYTH_PRICE_STATUS_TRADING: u32 = 1;
+
+/// Aggregate price of a Pyth price account
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PriceAccount {
+    /// Power of ten the price and confidence are scaled by
+    pub expo: i32,
+    /// Unix timestamp the aggregate price was published at
+    pub timestamp: i64,
+    pub price: i64,
+    /// Confidence interval around the price, in the same units
+    pub conf: u64,
+    pub status: u32,
+}
+
+impl PriceAccount {
+    pub fn is_trading(&self) -> bool {
+        self.status == PYTH_PRICE_STATUS_TRADING
+    }
+}
+
+impl Sealed for PriceAccount {}
+
+impl IsInitialized for PriceAccount {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+}
+
+impl Pack for PriceAccount {
+    const LEN: usize = 3312;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, PriceAccount::LEN];
+        let (
+            magic,
+            version,
+            account_type,
+            _size_type_and_count,
+            expo,
+            _count_and_slots,
+            _ema,
+            timestamp,
+            _publishers_and_products,
+            _previous_price,
+            price,
+            conf,
+            status,
+            _corporate_action_and_slot,
+            _components,
+        ) = array_refs![src, 4, 4, 4, 8, 4, 24, 48, 8, 72, 32, 8, 8, 4, 12, 3072];
+
+        if u32::from_le_bytes(*magic) != PYTH_MAGIC ||
+            u32::from_le_bytes(*version) != PYTH_VERSION ||
+            u32::from_le_bytes(*account_type) != PYTH_PRICE_ACCOUNT_TYPE {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(PriceAccount {
+            expo: i32::from_le_bytes(*expo),
+            timestamp: i64::from_le_bytes(*timestamp),
+            price: i64::from_le_bytes(*price),
+            conf: u64::from_le_bytes(*conf),
+            status: u32::from_le_bytes(*status),
+        })
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, PriceAccount::LEN];
+        let (
+            magic_dst,
+            version_dst,
+            account_type_dst,
+            _size_type_and_count_dst,
+            expo_dst,
+            _count_and_slots_dst,
+            _ema_dst,
+            timestamp_dst,
+            _publishers_and_products_dst,
+            _previous_price_dst,
+            price_dst,
+            conf_dst,
+            status_dst,
+            _corporate_action_and_slot_dst,
+            _components_dst,
+        ) = mut_array_refs![dst, 4, 4, 4, 8, 4, 24, 48, 8, 72, 32, 8, 8, 4, 12, 3072];
+
+        *magic_dst = PYTH_MAGIC.to_le_bytes();
+        *version_dst = PYTH_VERSION.to_le_bytes();
+        *account_type_dst = PYTH_PRICE_ACCOUNT_TYPE.to_le_bytes();
+        *expo_dst = self.expo.to_le_bytes();
+        *timestamp_dst = self.timestamp.to_le_bytes();
+        *price_dst = self.price.to_le_bytes();
+        *conf_dst = self.conf.to_le_bytes();
+        *status_dst = self.status.to_le_bytes();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_price_account() {
+        let price_account = PriceAccount {
+            expo: -8,
+            timestamp: 1_650_000_000,
+            price: 12_345_000_000,
+            conf: 5_000_000,
+            status: PYTH_PRICE_STATUS_TRADING,
+        };
+        let mut data = vec![0; PriceAccount::LEN];
+        PriceAccount::pack(price_account, &mut data).unwrap();
+        // offsets of the Pyth price account layout
+        assert_eq!(data[0..4], PYTH_MAGIC.to_le_bytes());
+        assert_eq!(data[20..24], (-8i32).to_le_bytes());
+        assert_eq!(data[96..104], 1_650_000_000i64.to_le_bytes());
+        assert_eq!(data[208..216], 12_345_000_000i64.to_le_bytes());
+        assert_eq!(data[216..224], 5_000_000u64.to_le_bytes());
+        assert_eq!(data[224..228], PYTH_PRICE_STATUS_TRADING.to_le_bytes());
+        assert_eq!(PriceAccount::unpack(&data).unwrap(), price_account);
+
+        data[8] = 2;
+        assert_eq!(PriceAccount::unpack(&data), Err(ProgramError::InvalidAccountData));
+    }
+}
@@ -2,21 +2,287 @@ use num_traits::FromPrimitive;
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
     entrypoint::ProgramResult,
-    msg,
     instruction::{AccountMeta, Instruction},
+    msg,
     program::{invoke, invoke_signed},
     decode_error::DecodeError,
     program_error::{PrintProgramError, ProgramError},
     program_pack::{IsInitialized, Pack},
     pubkey::Pubkey,
+    system_instruction,
     sysvar::{rent::Rent, clock::Clock, Sysvar},
 };
-use spl_token::state::Account as TokenAccount;
 use solr_token_whitelist::state::TokenWhitelist as TokenWhitelist;
-use crate::{error::TokenSaleError, instruction::TokenSaleInstruction, state::TokenSale};
+use crate::{
+    error::TokenSaleError,
+    instruction::TokenSaleInstruction,
+    merkle::MerkleProof,
+    state::{
+        TokenSale, TokenSaleV1, PurchaseRecord, PaymentConfig, PaymentMint, SaleMode, PricingMode, SaleStatus,
+        SaleAction, SaleRole, MAX_PAYMENT_MINTS, PAYMENT_CONFIG_SEED, PURCHASE_RECORD_SEED, TOKEN_SALE_V1_AUTHORITY_SEED,
+    },
+    validation,
+};
 
 pub struct Processor;
 impl Processor {
+    /// Calculates the authority id by generating a program address.
+    pub fn authority_id(
+        program_id: &Pubkey,
+        my_info: &Pubkey,
+        bump_seed: u8,
+    ) -> Result<Pubkey, TokenSaleError> {
+        Pubkey::create_program_address(&[&my_info.to_bytes()[..32], &[bump_seed]], program_id)
+            .or(Err(TokenSaleError::InvalidProgramAddress))
+    }
+
+    /// Finds the authority address of a token sale.
+    pub fn find_authority_address(
+        program_id: &Pubkey,
+        token_sale: &Pubkey,
+    ) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[&token_sale.to_bytes()[..32]], program_id)
+    }
+
+    /// Finds the authority address shared by all version 1 token sales.
+    pub fn find_authority_v1_address(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[TOKEN_SALE_V1_AUTHORITY_SEED], program_id)
+    }
+
+    /// Finds the purchase record address of a user in a token sale.
+    pub fn find_purchase_record_address(
+        program_id: &Pubkey,
+        token_sale: &Pubkey,
+        user: &Pubkey,
+    ) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[PURCHASE_RECORD_SEED, token_sale.as_ref(), user.as_ref()],
+            program_id,
+        )
+    }
+
+    /// Finds the payment config address of a token sale.
+    pub fn find_payment_config_address(
+        program_id: &Pubkey,
+        token_sale: &Pubkey,
+    ) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[PAYMENT_CONFIG_SEED, token_sale.as_ref()], program_id)
+    }
+
+    /// Creates a token program SyncNative instruction, which spl-token 3.1 has no builder for
+    fn sync_native_instruction(token_program_id: &Pubkey, account: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: *token_program_id,
+            accounts: vec![AccountMeta::new(*account, false)],
+            data: vec![17],
+        }
+    }
+
+    /// Logs and returns the error raised when checked arithmetic overflows
+    fn math_overflow() -> ProgramError {
+        msg!("SOLR_ERROR_27: arithmetic overflow");
+        TokenSaleError::MathOverflow.into()
+    }
+
+    /// Moves a token sale along the lifecycle transition table, rejecting illegal transitions
+    fn transition_status(token_sale_state: &mut TokenSale, action: SaleAction) -> ProgramResult {
+        let clock = Clock::get()?;
+        let status = token_sale_state.current_status(clock.unix_timestamp as u64);
+        match status.transition(action) {
+            Ok(next_status) => {
+                token_sale_state.status = next_status;
+                Ok(())
+            }
+            Err(error) => {
+                msg!("SOLR_ERROR_{}: {:?} is not allowed while the token sale is {:?}", error as u32, action, status);
+                Err(error.into())
+            }
+        }
+    }
+
+    /// Returns the usd allocation of the user, read from the voucher verified by the previous
+    /// instruction in sales with a voucher signer, from the merkle proof of the instruction in
+    /// sales with a merkle root and from the whitelist program otherwise.
+    /// The instructions sysvar is passed in place of the whitelist account for a voucher.
+    fn user_allocation(
+        token_sale_state: &TokenSale,
+        token_sale_account: &AccountInfo,
+        user_account: &AccountInfo,
+        token_whitelist_map: &AccountInfo,
+        token_whitelist_account: &AccountInfo,
+        token_whitelist_program: &AccountInfo,
+        merkle_proof: Option<&MerkleProof>,
+    ) -> Result<u64, ProgramError> {
+        if token_sale_state.has_voucher_signer() {
+            let clock = Clock::get()?;
+            let voucher = validation::unpack_voucher(
+                token_sale_state,
+                token_sale_account,
+                user_account,
+                token_whitelist_account,
+                clock.unix_timestamp as u64,
+            )?;
+            return Ok(voucher.allocation_amount);
+        }
+        if token_sale_state.has_merkle_root() {
+            return validation::check_merkle_proof(token_sale_state, user_account, merkle_proof);
+        }
+        Self::whitelist_allocation(
+            token_sale_state,
+            user_account,
+            token_whitelist_map,
+            token_whitelist_account,
+            token_whitelist_program,
+        )
+    }
+
+    /// Validates the whitelist accounts of a sale and returns the usd allocation of the user
+    fn whitelist_allocation(
+        token_sale_state: &TokenSale,
+        user_account: &AccountInfo,
+        token_whitelist_map: &AccountInfo,
+        token_whitelist_account: &AccountInfo,
+        token_whitelist_program: &AccountInfo,
+    ) -> Result<u64, ProgramError> {
+        if token_sale_state.whitelist_map_pubkey != *token_whitelist_map.key {
+            msg!("SOLR_ERROR_46: invalid token whitelist account map");
+            msg!(&token_sale_state.whitelist_map_pubkey.to_string());
+            msg!(&token_whitelist_map.key.to_string());
+            return Err(TokenSaleError::InvalidWhitelist.into());
+        }
+        if token_sale_state.whitelist_program_pubkey != *token_whitelist_program.key {
+            msg!("SOLR_ERROR_46: invalid token whitelist program");
+            msg!(&token_sale_state.whitelist_program_pubkey.to_string());
+            msg!(&token_whitelist_program.key.to_string());
+            return Err(TokenSaleError::InvalidWhitelist.into());
+        }
+        validation::check_account_owner(token_whitelist_map, token_whitelist_program.key)?;
+        validation::check_account_owner(token_whitelist_account, token_whitelist_program.key)?;
+
+        let mut token_whitelist_map_state = TokenWhitelist::unpack_from_slice(&token_whitelist_map.data.borrow())?;
+        let mut token_whitelist_account_state = TokenWhitelist::unpack_from_slice(&token_whitelist_account.data.borrow())?;
+        if !token_whitelist_map_state.contains_key(&token_whitelist_account.key.to_string()) {
+            msg!("SOLR_ERROR_46: invalid token whitelist account");
+            msg!("{}", token_whitelist_account.key);
+            return Err(TokenSaleError::InvalidWhitelist.into());
+        }
+        if !token_whitelist_account_state.contains_key(&user_account.key.to_string()) {
+            msg!("SOLR_ERROR_2: user is not whitelisted");
+            msg!("{}", user_account.key);
+            return Err(TokenSaleError::UserNotWhitelisted.into());
+        }
+        let mut allocation_amount: u64 = 0;
+        if let Some(value) = token_whitelist_account_state.get(&user_account.key.to_string()) {
+            allocation_amount = *value;
+        }
+        Ok(allocation_amount)
+    }
+
+    /// Loads the purchase record of a user in a token sale, creating it on first use
+    fn load_purchase_record<'a>(
+        program_id: &Pubkey,
+        token_sale_account: &AccountInfo<'a>,
+        user_account: &AccountInfo<'a>,
+        user_purchase_record: &AccountInfo<'a>,
+        system_program_info: &AccountInfo<'a>,
+    ) -> Result<PurchaseRecord, ProgramError> {
+        let (user_purchase_record_address, purchase_bump_seed) =
+            Self::find_purchase_record_address(program_id, token_sale_account.key, user_account.key);
+        if user_purchase_record_address != *user_purchase_record.key {
+            msg!("SOLR_ERROR_14: invalid user purchase record program derived address");
+            msg!(&user_purchase_record.key.to_string());
+            return Err(TokenSaleError::InvalidProgramAddress.into());
+        }
+        if user_purchase_record.data_is_empty() {
+            Self::create_pda_account(
+                user_account,
+                user_purchase_record,
+                system_program_info,
+                PurchaseRecord::LEN,
+                program_id,
+                &[
+                    PURCHASE_RECORD_SEED,
+                    token_sale_account.key.as_ref(),
+                    user_account.key.as_ref(),
+                    &[purchase_bump_seed],
+                ],
+            )?;
+        }
+        validation::check_account_owner(user_purchase_record, program_id)?;
+
+        let mut user_purchase_record_state = PurchaseRecord::unpack_unchecked(&user_purchase_record.data.borrow())?;
+        if !user_purchase_record_state.is_initialized() {
+            user_purchase_record_state.is_initialized = true;
+            user_purchase_record_state.token_sale_pubkey = *token_sale_account.key;
+            user_purchase_record_state.user_pubkey = *user_account.key;
+            user_purchase_record_state.usd_amount = 0;
+            user_purchase_record_state.token_amount = 0;
+            user_purchase_record_state.claimed_amount = 0;
+            user_purchase_record_state.is_refunded = false;
+            user_purchase_record_state.is_settled = false;
+            user_purchase_record_state.bump_seed = purchase_bump_seed;
+        }
+        Ok(user_purchase_record_state)
+    }
+
+    /// Creates a program owned account at a program derived address. Lamports
+    /// already sent to the address are topped up instead of failing the creation.
+    fn create_pda_account<'a>(
+        payer: &AccountInfo<'a>,
+        new_account: &AccountInfo<'a>,
+        system_program_info: &AccountInfo<'a>,
+        space: usize,
+        program_id: &Pubkey,
+        signer_seeds: &[&[u8]],
+    ) -> ProgramResult {
+        validation::check_system_program(system_program_info)?;
+        let required_lamports = Rent::get()?
+            .minimum_balance(space)
+            .saturating_sub(new_account.lamports());
+
+        if new_account.lamports() == 0 {
+            return invoke_signed(
+                &system_instruction::create_account(
+                    payer.key,
+                    new_account.key,
+                    required_lamports,
+                    space as u64,
+                    program_id,
+                ),
+                &[payer.clone(), new_account.clone(), system_program_info.clone()],
+                &[signer_seeds],
+            );
+        }
+
+        if required_lamports > 0 {
+            invoke(
+                &system_instruction::transfer(payer.key, new_account.key, required_lamports),
+                &[payer.clone(), new_account.clone(), system_program_info.clone()],
+            )?;
+        }
+        invoke_signed(
+            &system_instruction::allocate(new_account.key, space as u64),
+            &[new_account.clone(), system_program_info.clone()],
+            &[signer_seeds],
+        )?;
+        invoke_signed(
+            &system_instruction::assign(new_account.key, program_id),
+            &[new_account.clone(), system_program_info.clone()],
+            &[signer_seeds],
+        )
+    }
+
+    /// Closes an account owned by the program, moving its lamports to `destination` and wiping its data
+    fn close_program_account(account: &AccountInfo, destination: &AccountInfo) -> ProgramResult {
+        let lamports = account.lamports();
+        **destination.lamports.borrow_mut() = destination.lamports()
+            .checked_add(lamports)
+            .ok_or_else(Self::math_overflow)?;
+        **account.lamports.borrow_mut() = 0;
+        account.data.borrow_mut().fill(0);
+        Ok(())
+    }
+
     pub fn process(
         program_id: &Pubkey,
         accounts: &[AccountInfo],
@@ -30,7 +296,19 @@ impl Processor {
                 usd_min_amount,
                 usd_max_amount,
                 token_sale_price,
-                token_sale_time
+                token_sale_time,
+                token_sale_end_time,
+                vesting_tge_percent,
+                vesting_start_time,
+                vesting_cliff_time,
+                vesting_end_time,
+                soft_cap_amount,
+                sale_mode,
+                pricing_mode,
+                auction_start_price,
+                auction_floor_price,
+                auction_decay_amount,
+                auction_decay_interval,
             } => {
                 msg!("Instruction: InitTokenSale");
                 Self::process_init_sale(
@@ -40,6 +318,18 @@ impl Processor {
                     usd_max_amount,
                     token_sale_price,
                     token_sale_time,
+                    token_sale_end_time,
+                    vesting_tge_percent,
+                    vesting_start_time,
+                    vesting_cliff_time,
+                    vesting_end_time,
+                    soft_cap_amount,
+                    sale_mode,
+                    pricing_mode,
+                    auction_start_price,
+                    auction_floor_price,
+                    auction_decay_amount,
+                    auction_decay_interval,
                     program_id
                 )
             }
@@ -51,11 +341,13 @@ impl Processor {
                     program_id
                 )
             }
-            TokenSaleInstruction::ExecuteTokenSale { usd_amount } => {
+            TokenSaleInstruction::ExecuteTokenSale { usd_amount, max_price, merkle_proof } => {
                 msg!("Instruction: ExecuteTokenSale");
                 Self::process_execute_sale(
                     accounts,
                     usd_amount,
+                    max_price,
+                    merkle_proof,
                     program_id
                 )
             }
@@ -80,10 +372,149 @@ impl Processor {
                     program_id
                 )
             }
+            TokenSaleInstruction::WithdrawUnsoldTokens {} => {
+                msg!("Instruction: WithdrawUnsoldTokens");
+                Self::process_withdraw_unsold(
+                    accounts,
+                    program_id
+                )
+            }
+            TokenSaleInstruction::ClaimVestedTokens {} => {
+                msg!("Instruction: ClaimVestedTokens");
+                Self::process_claim_vested(
+                    accounts,
+                    program_id
+                )
+            }
+            TokenSaleInstruction::RefundPurchase {} => {
+                msg!("Instruction: RefundPurchase");
+                Self::process_refund_purchase(
+                    accounts,
+                    program_id
+                )
+            }
+            TokenSaleInstruction::WithdrawSaleProceeds {} => {
+                msg!("Instruction: WithdrawSaleProceeds");
+                Self::process_withdraw_proceeds(
+                    accounts,
+                    program_id
+                )
+            }
+            TokenSaleInstruction::CommitToSale { usd_amount, merkle_proof } => {
+                msg!("Instruction: CommitToSale");
+                Self::process_commit_to_sale(
+                    accounts,
+                    usd_amount,
+                    merkle_proof,
+                    program_id
+                )
+            }
+            TokenSaleInstruction::SettleCommitment {} => {
+                msg!("Instruction: SettleCommitment");
+                Self::process_settle_commitment(
+                    accounts,
+                    program_id
+                )
+            }
+            TokenSaleInstruction::MigrateTokenSale {} => {
+                msg!("Instruction: MigrateTokenSale");
+                Self::process_migrate_sale(
+                    accounts,
+                    program_id
+                )
+            }
+            TokenSaleInstruction::UpdateSaleParameters {
+                usd_min_amount,
+                usd_max_amount,
+                token_sale_price,
+                token_sale_time,
+            } => {
+                msg!("Instruction: UpdateSaleParameters");
+                Self::process_update_sale_parameters(
+                    accounts,
+                    usd_min_amount,
+                    usd_max_amount,
+                    token_sale_price,
+                    token_sale_time,
+                    program_id
+                )
+            }
+            TokenSaleInstruction::ProposeAdmin { new_admin_pubkey } => {
+                msg!("Instruction: ProposeAdmin");
+                Self::process_propose_admin(
+                    accounts,
+                    new_admin_pubkey,
+                    program_id
+                )
+            }
+            TokenSaleInstruction::AcceptAdmin {} => {
+                msg!("Instruction: AcceptAdmin");
+                Self::process_accept_admin(
+                    accounts,
+                    program_id
+                )
+            }
+            TokenSaleInstruction::SetSaleRole { role, role_pubkey } => {
+                msg!("Instruction: SetSaleRole");
+                Self::process_set_sale_role(
+                    accounts,
+                    role,
+                    role_pubkey,
+                    program_id
+                )
+            }
+            TokenSaleInstruction::CancelTokenSale {} => {
+                msg!("Instruction: CancelTokenSale");
+                Self::process_cancel_sale(
+                    accounts,
+                    program_id
+                )
+            }
+            TokenSaleInstruction::CloseTokenSale {} => {
+                msg!("Instruction: CloseTokenSale");
+                Self::process_close_sale(
+                    accounts,
+                    program_id
+                )
+            }
+            TokenSaleInstruction::SetPaymentMint { price } => {
+                msg!("Instruction: SetPaymentMint");
+                Self::process_set_payment_mint(
+                    accounts,
+                    price,
+                    program_id
+                )
+            }
+            TokenSaleInstruction::SetOracle { max_age, max_confidence_bps } => {
+                msg!("Instruction: SetOracle");
+                Self::process_set_oracle(
+                    accounts,
+                    max_age,
+                    max_confidence_bps,
+                    program_id
+                )
+            }
+            TokenSaleInstruction::SetVoucherSigner { voucher_signer_pubkey } => {
+                msg!("Instruction: SetVoucherSigner");
+                Self::process_set_voucher_signer(
+                    accounts,
+                    voucher_signer_pubkey,
+                    program_id
+                )
+            }
+            TokenSaleInstruction::SetMerkleRoot { merkle_root } => {
+                msg!("Instruction: SetMerkleRoot");
+                Self::process_set_merkle_root(
+                    accounts,
+                    merkle_root,
+                    program_id
+                )
+            }
         }
     }
 
     /// Processes [InitTokenSale](enum.TokenSaleInstruction.html) instruction
+    #[allow(clippy::too_many_arguments)]
     fn process_init_sale(
         accounts: &[AccountInfo],
         token_sale_amount: u64,
@@ -91,6 +522,18 @@ impl Processor {
         usd_max_amount: u64,
         token_sale_price: u64,
         token_sale_time: u64,
+        token_sale_end_time: u64,
+        vesting_tge_percent: u8,
+        vesting_start_time: u64,
+        vesting_cliff_time: u64,
+        vesting_end_time: u64,
+        soft_cap_amount: u64,
+        sale_mode: SaleMode,
+        pricing_mode: PricingMode,
+        auction_start_price: u64,
+        auction_floor_price: u64,
+        auction_decay_amount: u64,
+        auction_decay_interval: u64,
         program_id: &Pubkey,
     ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
@@ -107,17 +550,12 @@ impl Processor {
         let token_whitelist_map = next_account_info(account_info_iter)?;
 
         let token_program = next_account_info(account_info_iter)?;
-        if !spl_token::check_id(token_program.key) {
-            msg!("invalid token program");
-            msg!(&token_program.key.to_string());
-            return Err(ProgramError::InvalidAccountData);
-        }
+        validation::check_token_program(token_program)?;
 
         let token_whitelist_program = next_account_info(account_info_iter)?;
-        if token_whitelist_map.owner != token_whitelist_program.key {
-            msg!("token whitelist map is not owned by token whitelist program");
-            return Err(ProgramError::InvalidAccountData);
-        }
+        validation::check_account_owner(token_whitelist_map, token_whitelist_program.key)?;
+        validation::check_account_owner(token_sale_account, program_id)?;
+        validation::check_unique_accounts(&[pool_usdt_account, token_sale_solr_account])?;
 
         let sysvar_rent_pubkey = &Rent::from_account_info(next_account_info(account_info_iter)?)?;
         if !sysvar_rent_pubkey.is_exempt(token_sale_account.lamports(), token_sale_account.data_len()) {
@@ -125,14 +563,77 @@ impl Processor {
             return Err(TokenSaleError::NotRentExempt.into());
         }
 
+        let sale_mint_account = next_account_info(account_info_iter)?;
+        let usd_mint_account = next_account_info(account_info_iter)?;
+        let sale_mint_state = validation::unpack_mint(sale_mint_account)?;
+        let usd_mint_state = validation::unpack_mint(usd_mint_account)?;
+        let token_sale_solr_account_info =
+            validation::unpack_token_account_with_mint(token_sale_solr_account, sale_mint_account.key)?;
+        validation::unpack_token_account_with_mint(pool_usdt_account, usd_mint_account.key)?;
+
         let mut token_sale_state = TokenSale::unpack_unchecked(&token_sale_account.data.borrow())?;
         if token_sale_state.is_initialized() {
             msg!("token sale already initialized");
             return Err(ProgramError::AccountAlreadyInitialized);
         }
+        if token_sale_amount == 0 {
+            msg!("SOLR_ERROR_48: token sale amount must be greater than zero");
+            return Err(TokenSaleError::InvalidTokenSaleAmount.into());
+        }
+        // every token in the sale token account is for sale, FundTokenSale deposits exactly
+        // the token sale amount into an empty account
+        if token_sale_solr_account_info.amount != 0 {
+            msg!("SOLR_ERROR_49: sale token account is not empty");
+            msg!("{}", token_sale_solr_account_info.amount);
+            msg!("{}", token_sale_amount);
+            return Err(TokenSaleError::InvalidSaleTokenBalance.into());
+        }
+        if usd_max_amount == 0 || usd_min_amount > usd_max_amount {
+            msg!("SOLR_ERROR_32: invalid purchase limits");
+            msg!("{}", usd_min_amount);
+            msg!("{}", usd_max_amount);
+            return Err(TokenSaleError::InvalidPurchaseLimits.into());
+        }
+        if token_sale_price == 0 && pricing_mode == PricingMode::Fixed {
+            msg!("SOLR_ERROR_33: token sale price must be greater than zero");
+            return Err(TokenSaleError::InvalidTokenSalePrice.into());
+        }
+        let clock = Clock::get()?;
+        if token_sale_time < clock.unix_timestamp as u64 || token_sale_time >= token_sale_end_time {
+            msg!("SOLR_ERROR_34: token sale time must not be in the past and must be before the end time");
+            msg!("{}", token_sale_time);
+            msg!("{}", token_sale_end_time);
+            return Err(TokenSaleError::InvalidTokenSaleTime.into());
+        }
+        if vesting_tge_percent > 100 ||
+            (vesting_tge_percent < 100 &&
+                (vesting_start_time > vesting_cliff_time || vesting_cliff_time > vesting_end_time)) {
+            msg!("SOLR_ERROR_18: invalid vesting schedule");
+            msg!("{}", vesting_tge_percent);
+            msg!("{}", vesting_start_time);
+            msg!("{}", vesting_cliff_time);
+            msg!("{}", vesting_end_time);
+            return Err(TokenSaleError::InvalidVestingSchedule.into());
+        }
+        if pricing_mode == PricingMode::DutchAuction {
+            if sale_mode == SaleMode::ProRata {
+                msg!("SOLR_ERROR_23: pro-rata sales are sold at a fixed price");
+                return Err(TokenSaleError::InvalidSaleMode.into());
+            }
+            if auction_floor_price == 0 ||
+                auction_start_price < auction_floor_price ||
+                auction_decay_interval == 0 {
+                msg!("SOLR_ERROR_25: invalid auction schedule");
+                msg!("{}", auction_start_price);
+                msg!("{}", auction_floor_price);
+                msg!("{}", auction_decay_interval);
+                return Err(TokenSaleError::InvalidAuctionSchedule.into());
+            }
+        }
 
         // Transfer token sale solr account ownership to the token sale program derived address
-        let (token_sale_program_address, _nonce) = Pubkey::find_program_address(&[b"solrsale"], program_id);
+        let (token_sale_program_address, bump_seed) =
+            Self::find_authority_address(program_id, token_sale_account.key);
         msg!("Transfer token sale solr account ownership to the token sale program derived address");
         let transfer_ownership_ix = spl_token::instruction::set_authority(
             token_program.key,
@@ -140,7 +641,7 @@ impl Processor {
             Some(&token_sale_program_address),
             spl_token::instruction::AuthorityType::AccountOwner,
             pool_account.key,
-            &[&pool_account.key],
+            &[pool_account.key],
         )?;
         invoke(
             &transfer_ownership_ix,
@@ -151,6 +652,27 @@ impl Processor {
             ],
         )?;
 
+        if soft_cap_amount > 0 || sale_mode == SaleMode::ProRata {
+            // Sale proceeds are held in escrow while they may be refunded
+            msg!("Transfer pool usdt account ownership to the token sale program derived address");
+            let transfer_pool_ownership_ix = spl_token::instruction::set_authority(
+                token_program.key,
+                pool_usdt_account.key,
+                Some(&token_sale_program_address),
+                spl_token::instruction::AuthorityType::AccountOwner,
+                pool_account.key,
+                &[pool_account.key],
+            )?;
+            invoke(
+                &transfer_pool_ownership_ix,
+                &[
+                    pool_usdt_account.clone(),
+                    pool_account.clone(),
+                    token_program.clone(),
+                ],
+            )?;
+        }
+
         token_sale_state.is_initialized = true;
         token_sale_state.init_pubkey = *pool_account.key;
         token_sale_state.sale_token_account_pubkey = *token_sale_solr_account.key;
@@ -162,20 +684,46 @@ impl Processor {
         token_sale_state.usd_max_amount = usd_max_amount;
         token_sale_state.token_sale_price = token_sale_price;
         token_sale_state.token_sale_time = token_sale_time;
-        token_sale_state.token_sale_paused = false;
-        token_sale_state.token_sale_ended = false;
+        token_sale_state.token_sale_end_time = token_sale_end_time;
+        token_sale_state.status = SaleStatus::Initialized;
+        token_sale_state.bump_seed = bump_seed;
+        token_sale_state.vesting_tge_percent = vesting_tge_percent;
+        token_sale_state.vesting_start_time = vesting_start_time;
+        token_sale_state.vesting_cliff_time = vesting_cliff_time;
+        token_sale_state.vesting_end_time = vesting_end_time;
+        token_sale_state.vesting_locked_amount = 0;
+        token_sale_state.soft_cap_amount = soft_cap_amount;
+        token_sale_state.usd_raised_amount = 0;
+        token_sale_state.sale_mode = sale_mode;
+        token_sale_state.usd_withdrawn_amount = 0;
+        token_sale_state.token_settled_amount = 0;
+        token_sale_state.usd_filled_amount = 0;
+        token_sale_state.pricing_mode = pricing_mode;
+        token_sale_state.auction_start_price = auction_start_price;
+        token_sale_state.auction_floor_price = auction_floor_price;
+        token_sale_state.auction_decay_amount = auction_decay_amount;
+        token_sale_state.auction_decay_interval = auction_decay_interval;
+        token_sale_state.sale_mint_decimals = sale_mint_state.decimals;
+        token_sale_state.usd_mint_decimals = usd_mint_state.decimals;
+        token_sale_state.pending_admin_pubkey = Pubkey::default();
+        token_sale_state.guardian_pubkey = *pool_account.key;
+        token_sale_state.operator_pubkey = *pool_account.key;
+        token_sale_state.oracle_pubkey = Pubkey::default();
+        token_sale_state.oracle_max_age = 0;
+        token_sale_state.oracle_max_confidence_bps = 0;
+        token_sale_state.voucher_signer_pubkey = Pubkey::default();
+        token_sale_state.merkle_root = [0; 32];
         
         TokenSale::pack(token_sale_state, &mut token_sale_account.data.borrow_mut())?;
 
         Ok(())
     }
 
-    /// This instruction is redundant. Use spl token transfer to fund the sale
     /// Processes [FundTokenSale](enum.TokenSaleInstruction.html) instruction
     fn process_fund_sale(
         accounts: &[AccountInfo],
         token_sale_amount: u64,
-        _program_id: &Pubkey,
+        program_id: &Pubkey,
     ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
 
@@ -190,31 +738,19 @@ impl Processor {
         let token_sale_solr_account = next_account_info(account_info_iter)?;
 
         let token_program = next_account_info(account_info_iter)?;
-        if !spl_token::check_id(token_program.key) {
-            msg!("invalid token program");
-            msg!(&token_program.key.to_string());
-            return Err(ProgramError::InvalidAccountData);
-        }
+        validation::check_token_program(token_program)?;
 
         // check if token sale can be funded
-        let token_sale_state = TokenSale::unpack(&token_sale_account.data.borrow())?;
-        let token_sale_solr_account_info = TokenAccount::unpack(&token_sale_solr_account.data.borrow())?;
-        if !token_sale_state.is_initialized() {
-            msg!("SOLR_ERROR_3: token sale needs to be initialized before funding");
-            return Err(TokenSaleError::TokenSaleNotInit.into());
-        }
+        let mut token_sale_state = validation::unpack_token_sale(program_id, token_sale_account)?;
+        validation::check_sale_token_account(&token_sale_state, token_sale_solr_account)?;
+        validation::check_unique_accounts(&[pool_solr_account, token_sale_solr_account])?;
         if token_sale_amount != token_sale_state.token_sale_amount {
             msg!("SOLR_ERROR_6: funding amount has to match token sale amount");
             msg!(&token_sale_amount.to_string());
             msg!(&token_sale_state.token_sale_amount.to_string());
             return Err(TokenSaleError::TokenSaleAmountExceeds.into());
         }
-        if token_sale_solr_account_info.amount == token_sale_state.token_sale_amount {
-            msg!("SOLR_ERROR_5: token sale already funded");
-            msg!(&token_sale_solr_account_info.amount.to_string());
-            msg!(&token_sale_state.token_sale_amount.to_string());
-            return Err(TokenSaleError::TokenSaleFunded.into());
-        }
+        Self::transition_status(&mut token_sale_state, SaleAction::Fund)?;
 
         // Fund the token sale account with SOLR
         msg!("Fund the token sale account with SOLR");
@@ -223,7 +759,7 @@ impl Processor {
             pool_solr_account.key,
             token_sale_solr_account.key,
             pool_account.key,
-            &[&pool_account.key],
+            &[pool_account.key],
             token_sale_amount,
         )?;
         invoke(
@@ -236,6 +772,8 @@ impl Processor {
             ],
         )?;
 
+        TokenSale::pack(token_sale_state, &mut token_sale_account.data.borrow_mut())?;
+
         Ok(())
     }
 
@@ -243,6 +781,8 @@ impl Processor {
     fn process_execute_sale(
         accounts: &[AccountInfo],
         usd_amount: u64,
+        max_price: u64,
+        merkle_proof: Option<MerkleProof>,
         program_id: &Pubkey,
     ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
@@ -266,166 +806,240 @@ impl Processor {
         let token_whitelist_map = next_account_info(account_info_iter)?;
         let token_whitelist_account = next_account_info(account_info_iter)?;
         let token_whitelist_program = next_account_info(account_info_iter)?;
+
+        let user_purchase_record = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
         
-        let token_sale_state = TokenSale::unpack(&token_sale_account.data.borrow())?;
-        let token_sale_solr_account_info = TokenAccount::unpack(&token_sale_solr_account.data.borrow())?;
-        let mut token_whitelist_map_state = TokenWhitelist::unpack_from_slice(&token_whitelist_map.data.borrow())?;
-        let mut token_whitelist_account_state = TokenWhitelist::unpack_from_slice(&token_whitelist_account.data.borrow())?;
+        let mut token_sale_state = validation::unpack_token_sale(program_id, token_sale_account)?;
 
         // check if token sale is allowed
-        if !spl_token::check_id(token_program.key) {
-            msg!("invalid token program");
-            msg!(&token_program.key.to_string());
-            return Err(ProgramError::InvalidAccountData);
-        }
-        if token_sale_state.whitelist_map_pubkey != *token_whitelist_map.key {
-            msg!("invalid token whitelist account map");
-            msg!(&token_sale_state.whitelist_map_pubkey.to_string());
-            msg!(&token_whitelist_map.key.to_string());
-            return Err(ProgramError::InvalidAccountData);
-        }
-        if token_sale_state.whitelist_program_pubkey != *token_whitelist_program.key {
-            msg!("invalid token whitelist program");
-            msg!(&token_sale_state.whitelist_program_pubkey.to_string());
-            msg!(&token_whitelist_program.key.to_string());
-            return Err(ProgramError::InvalidAccountData);
-        }
-        if !token_whitelist_map_state.contains_key(&token_whitelist_account.key.to_string()) {
-            msg!("invalid token whitelist account");
-            msg!("{}", token_whitelist_account.key);
-            return Err(ProgramError::InvalidAccountData);
-        }
-        if !token_whitelist_account_state.contains_key(&user_account.key.to_string()) {
-            msg!("SOLR_ERROR_2: user is not whitelisted");
-            msg!("{}", user_account.key);
-            return Err(TokenSaleError::UserNotWhitelisted.into());
-        }
-        let mut allocation_amount: u64 = 0;
-        if let Some(value) = token_whitelist_account_state.get(&user_account.key.to_string()) {
-            allocation_amount = *value;
+        validation::check_token_program(token_program)?;
+        validation::check_sale_token_account(&token_sale_state, token_sale_solr_account)?;
+        validation::check_sale_authority(program_id, token_sale_account, &token_sale_state, sale_pda)?;
+        validation::check_unique_accounts(&[
+            token_sale_solr_account,
+            user_solr_account,
+            user_usdt_account,
+            pool_usdt_account,
+            user_purchase_record,
+        ])?;
+        let token_sale_solr_account_info = validation::unpack_token_account(token_sale_solr_account)?;
+        validation::unpack_token_account_with_mint(user_solr_account, &token_sale_solr_account_info.mint)?;
+        let payment_mint = if *pool_usdt_account.key == token_sale_state.pool_token_account_pubkey {
+            let pool_usdt_account_info = validation::unpack_token_account(pool_usdt_account)?;
+            validation::unpack_token_account_with_mint(user_usdt_account, &pool_usdt_account_info.mint)?;
+            None
+        } else {
+            // payments in other mints go to the pool token account of the mint in the payment config
+            let payment_config_account = next_account_info(account_info_iter)?;
+            if payment_config_account.data_is_empty() {
+                validation::check_pool_token_account(&token_sale_state, pool_usdt_account)?;
+            }
+            let payment_config_state =
+                validation::unpack_payment_config(program_id, payment_config_account, token_sale_account)?;
+            // buyers paying in native SOL pass their wallet instead of a token account
+            let user_usdt_mint = if user_usdt_account.key == user_account.key {
+                spl_token::native_mint::id()
+            } else {
+                validation::unpack_token_account(user_usdt_account)?.mint
+            };
+            let payment_mint = match payment_config_state.payment_mint(&user_usdt_mint) {
+                Some(payment_mint) => *payment_mint,
+                None => {
+                    msg!("SOLR_ERROR_50: payment mint is not accepted by the token sale");
+                    msg!(&user_usdt_mint.to_string());
+                    return Err(TokenSaleError::PaymentMintNotAccepted.into());
+                }
+            };
+            if payment_mint.pool_token_account_pubkey != *pool_usdt_account.key {
+                msg!("SOLR_ERROR_43: pool token account of the payment mint does not match");
+                msg!(&payment_mint.pool_token_account_pubkey.to_string());
+                msg!(&pool_usdt_account.key.to_string());
+                return Err(TokenSaleError::PoolTokenAccountMismatch.into());
+            }
+            Some(payment_mint)
+        };
+        if token_sale_state.sale_mode != SaleMode::FirstComeFirstServed {
+            msg!("SOLR_ERROR_23: pro-rata sales accept commitments only");
+            return Err(TokenSaleError::InvalidSaleMode.into());
         }
-        if usd_amount > allocation_amount {
+        let allocation_amount = Self::user_allocation(
+            &token_sale_state,
+            token_sale_account,
+            user_account,
+            token_whitelist_map,
+            token_whitelist_account,
+            token_whitelist_program,
+            merkle_proof.as_ref(),
+        )?;
+        let clock = Clock::get()?;
+        let token_price = token_sale_state.current_price(clock.unix_timestamp as u64);
+        // purchases in other payment mints are counted in usd at the token sale price
+        let (token_purchase_amount, purchase_usd_amount) = match payment_mint {
+            Some(payment_mint) => {
+                // native SOL is priced by the oracle of the sale if it has one
+                let token_purchase_amount = if token_sale_state.has_oracle() &&
+                    payment_mint.mint_pubkey == spl_token::native_mint::id() {
+                    let oracle_account = next_account_info(account_info_iter)?;
+                    let price_account = validation::unpack_oracle_price(
+                        &token_sale_state,
+                        oracle_account,
+                        clock.unix_timestamp as u64,
+                    )?;
+                    let oracle_usd_amount = token_sale_state
+                        .oracle_usd_amount(usd_amount, payment_mint.decimals, price_account.price as u64, price_account.expo)
+                        .ok_or_else(Self::math_overflow)?;
+                    token_sale_state.token_amount(oracle_usd_amount, token_sale_state.token_sale_price)
+                } else {
+                    token_sale_state.payment_token_amount(usd_amount, payment_mint.price, payment_mint.decimals)
+                }.ok_or_else(Self::math_overflow)?;
+                let purchase_usd_amount = token_sale_state
+                    .usd_amount(token_purchase_amount, token_sale_state.token_sale_price)
+                    .ok_or_else(Self::math_overflow)?;
+                (token_purchase_amount, purchase_usd_amount)
+            }
+            None => {
+                let token_purchase_amount = token_sale_state.token_amount(usd_amount, token_price)
+                    .ok_or_else(Self::math_overflow)?;
+                (token_purchase_amount, usd_amount)
+            }
+        };
+        let mut user_purchase_record_state = Self::load_purchase_record(
+            program_id,
+            token_sale_account,
+            user_account,
+            user_purchase_record,
+            system_program_info,
+        )?;
+        // repeat purchases draw down the same allocation
+        let purchased_amount = user_purchase_record_state.usd_amount
+            .checked_add(purchase_usd_amount)
+            .ok_or_else(Self::math_overflow)?;
+        if purchased_amount > allocation_amount {
             msg!("SOLR_ERROR_11: amount exceeds your allocation");
-            msg!("{}", usd_amount);
+            msg!("{}", purchased_amount);
             msg!("{}", allocation_amount);
             return Err(TokenSaleError::ExceedsAllocation.into());
         }
-        let clock = Clock::get()?;
-        if (clock.unix_timestamp as u64) < token_sale_state.token_sale_time {
-            msg!("SOLR_ERROR_4: token sale has not started");
-            msg!("{}", clock.unix_timestamp);
-            msg!("{}", token_sale_state.token_sale_time);
-            return Err(TokenSaleError::TokenSaleNotStarted.into());
-        }
-        if token_sale_state.token_sale_paused {
-            msg!("SOLR_ERROR_12: token sale has been paused");
-            return Err(TokenSaleError::TokenSalePaused.into());
-        }
-        if token_sale_state.token_sale_ended {
-            msg!("SOLR_ERROR_13: token sale has ended");
-            return Err(TokenSaleError::TokenSaleEnded.into());
-        }
-        if token_sale_state.sale_token_account_pubkey != *token_sale_solr_account.key {
-            msg!("token sale account does not match");
-            msg!(&token_sale_state.sale_token_account_pubkey.to_string());
-            msg!(&token_sale_solr_account.key.to_string());
-            return Err(ProgramError::InvalidAccountData);
-        }
-        if token_sale_state.pool_token_account_pubkey != *pool_usdt_account.key {
-            msg!("pool usdt account does not match");
-            msg!(&token_sale_state.pool_token_account_pubkey.to_string());
-            msg!(&pool_usdt_account.key.to_string());
-            return Err(ProgramError::InvalidAccountData);
-        }
-        if token_sale_solr_account_info.amount == 0 {
+        Self::transition_status(&mut token_sale_state, SaleAction::Purchase)?;
+        // tokens credited to vesting records stay in the sale token account until claimed
+        let token_sale_available_amount = token_sale_solr_account_info.amount
+            .saturating_sub(token_sale_state.vesting_locked_amount);
+        if token_sale_available_amount == 0 {
             msg!("SOLR_ERROR_7: token sale complete");
-            msg!(&token_sale_solr_account_info.amount.to_string());
+            msg!(&token_sale_available_amount.to_string());
             return Err(TokenSaleError::TokenSaleComplete.into());
         }
-        if usd_amount < token_sale_state.usd_min_amount {
+        if purchased_amount < token_sale_state.usd_min_amount {
             msg!("SOLR_ERROR_8: amount less than minimum allocation");
-            msg!(&usd_amount.to_string());
+            msg!(&purchased_amount.to_string());
             msg!(&token_sale_state.usd_min_amount.to_string());
             return Err(TokenSaleError::AmountMinimum.into());
         }
-        if usd_amount > token_sale_state.usd_max_amount {
+        if purchased_amount > token_sale_state.usd_max_amount {
             msg!("SOLR_ERROR_9: amount more than maximum allocation");
-            msg!(&usd_amount.to_string());
+            msg!(&purchased_amount.to_string());
             msg!(&token_sale_state.usd_max_amount.to_string());
             return Err(TokenSaleError::AmountMaximum.into());
         }
-        let token_purchase_amount = usd_amount * token_sale_state.token_sale_price;
-        if token_purchase_amount > token_sale_solr_account_info.amount {
+        if token_sale_state.pricing_mode == PricingMode::DutchAuction && token_price > max_price {
+            msg!("SOLR_ERROR_26: auction price exceeds maximum price");
+            msg!(&token_price.to_string());
+            msg!(&max_price.to_string());
+            return Err(TokenSaleError::PriceExceedsMaximum.into());
+        }
+        if token_purchase_amount > token_sale_available_amount {
             msg!("SOLR_ERROR_10: amount exceeds tokens available for sale");
             msg!(&token_purchase_amount.to_string());
-            msg!(&token_sale_solr_account_info.amount.to_string());
+            msg!(&token_sale_available_amount.to_string());
             return Err(TokenSaleError::AmountExceeds.into());
         }
 
-        // Transfer USDT to the pool account
-        msg!("Transfer USDT to the pool account");
-        let transfer_usdt_to_pool_ix = spl_token::instruction::transfer(
-            token_program.key,
-            user_usdt_account.key,
-            pool_usdt_account.key,
-            user_account.key,
-            &[&user_account.key],
-            usd_amount,
-        )?;
-        invoke(
-            &transfer_usdt_to_pool_ix,
-            &[
-                user_usdt_account.clone(),
-                pool_usdt_account.clone(),
-                user_account.clone(),
-                token_program.clone(),
-            ],
-        )?;
+        if user_usdt_account.key == user_account.key {
+            // Transfer SOL to the wrapped SOL pool account
+            msg!("Transfer SOL to the pool account");
+            validation::check_system_program(system_program_info)?;
+            invoke(
+                &system_instruction::transfer(user_account.key, pool_usdt_account.key, usd_amount),
+                &[
+                    user_account.clone(),
+                    pool_usdt_account.clone(),
+                    system_program_info.clone(),
+                ],
+            )?;
+            invoke(
+                &Self::sync_native_instruction(token_program.key, pool_usdt_account.key),
+                &[
+                    pool_usdt_account.clone(),
+                    token_program.clone(),
+                ],
+            )?;
+        } else {
+            // Transfer USDT to the pool account
+            msg!("Transfer USDT to the pool account");
+            let transfer_usdt_to_pool_ix = spl_token::instruction::transfer(
+                token_program.key,
+                user_usdt_account.key,
+                pool_usdt_account.key,
+                user_account.key,
+                &[user_account.key],
+                usd_amount,
+            )?;
+            invoke(
+                &transfer_usdt_to_pool_ix,
+                &[
+                    user_usdt_account.clone(),
+                    pool_usdt_account.clone(),
+                    user_account.clone(),
+                    token_program.clone(),
+                ],
+            )?;
+        }
 
-        // Transfer SOLR to the user
-        msg!("Transfer SOLR to the user");
-        let (token_sale_program_address, _nonce) = Pubkey::find_program_address(&[b"solrsale"], program_id);
-        let transfer_solr_to_user_ix = spl_token::instruction::transfer(
-            token_program.key,
-            token_sale_solr_account.key,
-            user_solr_account.key,
-            &token_sale_program_address,
-            &[&token_sale_program_address],
-            token_purchase_amount,
-        )?;
-        msg!(&(&token_sale_program_address).to_string());
-        invoke_signed(
-            &transfer_solr_to_user_ix,
-            &[
-                token_sale_solr_account.clone(),
-                user_solr_account.clone(),
-                sale_pda.clone(),
-                token_program.clone(),
-            ],
-            &[&[&b"solrsale"[..], &[_nonce]]],
-        )?;
+        if !token_sale_state.has_vesting() {
+            // Transfer SOLR to the user
+            msg!("Transfer SOLR to the user");
+            let transfer_solr_to_user_ix = spl_token::instruction::transfer(
+                token_program.key,
+                token_sale_solr_account.key,
+                user_solr_account.key,
+                sale_pda.key,
+                &[sale_pda.key],
+                token_purchase_amount,
+            )?;
+            invoke_signed(
+                &transfer_solr_to_user_ix,
+                &[
+                    token_sale_solr_account.clone(),
+                    user_solr_account.clone(),
+                    sale_pda.clone(),
+                    token_program.clone(),
+                ],
+                &[&[&token_sale_account.key.to_bytes()[..32], &[token_sale_state.bump_seed]]],
+            )?;
+        }
 
-        // Update token whitelist data after successful purchase
-        // Purchase is allowed only once and allocation will be reset to zero
-        let accounts_to_send = vec![
-            AccountMeta::new_readonly(*user_account.key, true),
-            AccountMeta::new(*token_whitelist_account.key, false),
-            AccountMeta::new_readonly(*user_account.key, false),
-        ];
-        let data: Vec<u8> = vec![3]; // instruction to reset allocation to zero
-        let update_token_whitelist_ix = Instruction {
-            program_id: *token_whitelist_program.key,
-            accounts: accounts_to_send,
-            data,
-        };
-        invoke(
-            &update_token_whitelist_ix,
-            &[
-                user_account.clone(),
-                token_whitelist_account.clone(),
-                token_whitelist_program.clone(),
-            ],
-        )?;
+        // Record the purchase, vested SOLR stays in the sale token account until claimed
+        msg!("Record the purchase in the user purchase record");
+        user_purchase_record_state.usd_amount = purchased_amount;
+        user_purchase_record_state.token_amount = user_purchase_record_state.token_amount
+            .checked_add(token_purchase_amount)
+            .ok_or_else(Self::math_overflow)?;
+        if token_sale_state.has_vesting() {
+            token_sale_state.vesting_locked_amount = token_sale_state.vesting_locked_amount
+                .checked_add(token_purchase_amount)
+                .ok_or_else(Self::math_overflow)?;
+        } else {
+            user_purchase_record_state.claimed_amount = user_purchase_record_state.claimed_amount
+                .checked_add(token_purchase_amount)
+                .ok_or_else(Self::math_overflow)?;
+        }
+        token_sale_state.usd_raised_amount = token_sale_state.usd_raised_amount
+            .checked_add(purchase_usd_amount)
+            .ok_or_else(Self::math_overflow)?;
+
+        PurchaseRecord::pack(user_purchase_record_state, &mut user_purchase_record.data.borrow_mut())?;
+        TokenSale::pack(token_sale_state, &mut token_sale_account.data.borrow_mut())?;
 
         Ok(())
     }
@@ -433,33 +1047,24 @@ impl Processor {
     /// Processes [PauseTokenSale](enum.TokenSaleInstruction.html) instruction
     fn process_pause_sale(
         accounts: &[AccountInfo],
-        _program_id: &Pubkey,
+        program_id: &Pubkey,
     ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
 
-        let init_account = next_account_info(account_info_iter)?;
-        if !init_account.is_signer {
+        let guardian_account = next_account_info(account_info_iter)?;
+        if !guardian_account.is_signer {
             return Err(ProgramError::MissingRequiredSignature);
         }
 
         let token_sale_account = next_account_info(account_info_iter)?;
 
         // check if token sale can be paused
-        let mut token_sale_state = TokenSale::unpack(&token_sale_account.data.borrow())?;
-        if token_sale_state.init_pubkey != *init_account.key {
-            msg!("invalid signer");
-            msg!(&token_sale_state.init_pubkey.to_string());
-            msg!(&init_account.key.to_string());
-            return Err(ProgramError::InvalidAccountData);
-        }
-        if !token_sale_state.is_initialized() {
-            msg!("SOLR_ERROR_3: token sale needs to be initialized before pausing");
-            return Err(TokenSaleError::TokenSaleNotInit.into());
-        }
-        
+        let mut token_sale_state = validation::unpack_token_sale(program_id, token_sale_account)?;
+        validation::check_role(&token_sale_state, SaleRole::Guardian, guardian_account)?;
+
         // pause the sale
-        token_sale_state.token_sale_paused = true;
-        
+        Self::transition_status(&mut token_sale_state, SaleAction::Pause)?;
+
         TokenSale::pack(token_sale_state, &mut token_sale_account.data.borrow_mut())?;
 
         Ok(())
@@ -468,33 +1073,24 @@ impl Processor {
     /// Processes [ResumeTokenSale](enum.TokenSaleInstruction.html) instruction
     fn process_resume_sale(
         accounts: &[AccountInfo],
-        _program_id: &Pubkey,
+        program_id: &Pubkey,
     ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
 
-        let init_account = next_account_info(account_info_iter)?;
-        if !init_account.is_signer {
+        let operator_account = next_account_info(account_info_iter)?;
+        if !operator_account.is_signer {
             return Err(ProgramError::MissingRequiredSignature);
         }
 
         let token_sale_account = next_account_info(account_info_iter)?;
 
         // check if token sale can be resumed
-        let mut token_sale_state = TokenSale::unpack(&token_sale_account.data.borrow())?;
-        if token_sale_state.init_pubkey != *init_account.key {
-            msg!("invalid signer");
-            msg!(&token_sale_state.init_pubkey.to_string());
-            msg!(&init_account.key.to_string());
-            return Err(ProgramError::InvalidAccountData);
-        }
-        if !token_sale_state.is_initialized() {
-            msg!("SOLR_ERROR_3: token sale is not initialized");
-            return Err(TokenSaleError::TokenSaleNotInit.into());
-        }
-        
+        let mut token_sale_state = validation::unpack_token_sale(program_id, token_sale_account)?;
+        validation::check_role(&token_sale_state, SaleRole::Operator, operator_account)?;
+
         // resume the sale
-        token_sale_state.token_sale_paused = false;
-        
+        Self::transition_status(&mut token_sale_state, SaleAction::Resume)?;
+
         TokenSale::pack(token_sale_state, &mut token_sale_account.data.borrow_mut())?;
 
         Ok(())
@@ -503,7 +1099,33 @@ impl Processor {
     /// Processes [EndTokenSale](enum.TokenSaleInstruction.html) instruction
     fn process_end_sale(
         accounts: &[AccountInfo],
-        _program_id: &Pubkey,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+
+        let operator_account = next_account_info(account_info_iter)?;
+        if !operator_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let token_sale_account = next_account_info(account_info_iter)?;
+
+        // check if token sale can be ended
+        let mut token_sale_state = validation::unpack_token_sale(program_id, token_sale_account)?;
+        validation::check_role(&token_sale_state, SaleRole::Operator, operator_account)?;
+
+        // end the sale (can't be resumed once ended)
+        Self::transition_status(&mut token_sale_state, SaleAction::End)?;
+
+        TokenSale::pack(token_sale_state, &mut token_sale_account.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Processes [WithdrawUnsoldTokens](enum.TokenSaleInstruction.html) instruction
+    fn process_withdraw_unsold(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
     ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
 
@@ -514,22 +1136,1204 @@ impl Processor {
 
         let token_sale_account = next_account_info(account_info_iter)?;
 
-        // check if token sale can be ended
-        let mut token_sale_state = TokenSale::unpack(&token_sale_account.data.borrow())?;
-        if token_sale_state.init_pubkey != *init_account.key {
-            msg!("invalid signer");
-            msg!(&token_sale_state.init_pubkey.to_string());
-            msg!(&init_account.key.to_string());
-            return Err(ProgramError::InvalidAccountData);
+        let token_sale_solr_account = next_account_info(account_info_iter)?;
+        let destination_solr_account = next_account_info(account_info_iter)?;
+
+        let sale_pda = next_account_info(account_info_iter)?;
+        let token_program = next_account_info(account_info_iter)?;
+        validation::check_token_program(token_program)?;
+
+        // check if unsold tokens can be withdrawn
+        let mut token_sale_state = validation::unpack_token_sale(program_id, token_sale_account)?;
+        validation::check_role(&token_sale_state, SaleRole::Admin, init_account)?;
+        let is_finalized = token_sale_state.status == SaleStatus::Finalized;
+        Self::transition_status(&mut token_sale_state, SaleAction::WithdrawUnsold)?;
+        validation::check_sale_token_account(&token_sale_state, token_sale_solr_account)?;
+        validation::check_sale_authority(program_id, token_sale_account, &token_sale_state, sale_pda)?;
+        validation::check_unique_accounts(&[token_sale_solr_account, destination_solr_account])?;
+        let token_sale_solr_account_info = validation::unpack_token_account(token_sale_solr_account)?;
+        validation::unpack_token_account_with_mint(destination_solr_account, &token_sale_solr_account_info.mint)?;
+        // tokens owed to buyers are not unsold and stay claimable
+        let unsold_amount = token_sale_solr_account_info.amount
+            .saturating_sub(token_sale_state.reserved_token_amount().ok_or_else(Self::math_overflow)?);
+        // a finalized sale only holds more unsold tokens after refunds returned them
+        if is_finalized && unsold_amount == 0 {
+            msg!("SOLR_ERROR_16: unsold tokens already withdrawn");
+            return Err(TokenSaleError::TokenSaleWithdrawn.into());
+        }
+
+        // Transfer unsold SOLR to the destination account
+        msg!("Transfer unsold SOLR to the destination account");
+        msg!(&unsold_amount.to_string());
+        let transfer_solr_to_destination_ix = spl_token::instruction::transfer(
+            token_program.key,
+            token_sale_solr_account.key,
+            destination_solr_account.key,
+            sale_pda.key,
+            &[sale_pda.key],
+            unsold_amount,
+        )?;
+        invoke_signed(
+            &transfer_solr_to_destination_ix,
+            &[
+                token_sale_solr_account.clone(),
+                destination_solr_account.clone(),
+                sale_pda.clone(),
+                token_program.clone(),
+            ],
+            &[&[&token_sale_account.key.to_bytes()[..32], &[token_sale_state.bump_seed]]],
+        )?;
+
+        TokenSale::pack(token_sale_state, &mut token_sale_account.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Processes [ClaimVestedTokens](enum.TokenSaleInstruction.html) instruction
+    fn process_claim_vested(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+
+        let user_account = next_account_info(account_info_iter)?;
+        if !user_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let token_sale_account = next_account_info(account_info_iter)?;
+        let user_purchase_record = next_account_info(account_info_iter)?;
+
+        let token_sale_solr_account = next_account_info(account_info_iter)?;
+        let user_solr_account = next_account_info(account_info_iter)?;
+
+        let sale_pda = next_account_info(account_info_iter)?;
+        let token_program = next_account_info(account_info_iter)?;
+        validation::check_token_program(token_program)?;
+
+        // check if vested tokens can be claimed
+        let mut token_sale_state = validation::unpack_token_sale(program_id, token_sale_account)?;
+        let mut user_purchase_record_state = validation::unpack_purchase_record(
+            program_id,
+            user_purchase_record,
+            token_sale_account,
+            user_account,
+        )?;
+        validation::check_sale_token_account(&token_sale_state, token_sale_solr_account)?;
+        validation::check_sale_authority(program_id, token_sale_account, &token_sale_state, sale_pda)?;
+        validation::check_unique_accounts(&[user_purchase_record, token_sale_solr_account, user_solr_account])?;
+        let token_sale_solr_account_info = validation::unpack_token_account(token_sale_solr_account)?;
+        validation::unpack_token_account_with_mint(user_solr_account, &token_sale_solr_account_info.mint)?;
+        Self::transition_status(&mut token_sale_state, SaleAction::Claim)?;
+        if !token_sale_state.is_soft_cap_reached() {
+            msg!("SOLR_ERROR_20: soft cap has not been reached");
+            msg!(&token_sale_state.usd_raised_amount.to_string());
+            msg!(&token_sale_state.soft_cap_amount.to_string());
+            return Err(TokenSaleError::SoftCapNotReached.into());
         }
-        if !token_sale_state.is_initialized() {
-            msg!("SOLR_ERROR_3: token sale is not initialized");
-            return Err(TokenSaleError::TokenSaleNotInit.into());
+        let clock = Clock::get()?;
+        let claimable_amount = token_sale_state
+            .vested_amount(user_purchase_record_state.token_amount, clock.unix_timestamp as u64)
+            .saturating_sub(user_purchase_record_state.claimed_amount);
+        if claimable_amount == 0 {
+            msg!("SOLR_ERROR_19: no vested tokens to claim");
+            msg!("{}", clock.unix_timestamp);
+            return Err(TokenSaleError::NothingToClaim.into());
         }
-        
-        // end the sale (can't be resumed once ended)
-        token_sale_state.token_sale_ended = true;
-        
+
+        // Transfer vested SOLR to the user
+        msg!("Transfer vested SOLR to the user");
+        msg!(&claimable_amount.to_string());
+        let transfer_solr_to_user_ix = spl_token::instruction::transfer(
+            token_program.key,
+            token_sale_solr_account.key,
+            user_solr_account.key,
+            sale_pda.key,
+            &[sale_pda.key],
+            claimable_amount,
+        )?;
+        invoke_signed(
+            &transfer_solr_to_user_ix,
+            &[
+                token_sale_solr_account.clone(),
+                user_solr_account.clone(),
+                sale_pda.clone(),
+                token_program.clone(),
+            ],
+            &[&[&token_sale_account.key.to_bytes()[..32], &[token_sale_state.bump_seed]]],
+        )?;
+
+        user_purchase_record_state.claimed_amount = user_purchase_record_state.claimed_amount
+            .checked_add(claimable_amount)
+            .ok_or_else(Self::math_overflow)?;
+        token_sale_state.vesting_locked_amount = token_sale_state.vesting_locked_amount
+            .checked_sub(claimable_amount)
+            .ok_or_else(Self::math_overflow)?;
+
+        PurchaseRecord::pack(user_purchase_record_state, &mut user_purchase_record.data.borrow_mut())?;
+        TokenSale::pack(token_sale_state, &mut token_sale_account.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Processes [RefundPurchase](enum.TokenSaleInstruction.html) instruction
+    fn process_refund_purchase(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+
+        let user_account = next_account_info(account_info_iter)?;
+        if !user_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let token_sale_account = next_account_info(account_info_iter)?;
+        let user_purchase_record = next_account_info(account_info_iter)?;
+
+        let pool_usdt_account = next_account_info(account_info_iter)?;
+        let user_usdt_account = next_account_info(account_info_iter)?;
+
+        let token_sale_solr_account = next_account_info(account_info_iter)?;
+        let user_solr_account = next_account_info(account_info_iter)?;
+
+        let sale_pda = next_account_info(account_info_iter)?;
+        let token_program = next_account_info(account_info_iter)?;
+        validation::check_token_program(token_program)?;
+
+        // check if purchase can be refunded
+        let mut token_sale_state = validation::unpack_token_sale(program_id, token_sale_account)?;
+        Self::transition_status(&mut token_sale_state, SaleAction::Refund)?;
+        if token_sale_state.sale_mode != SaleMode::FirstComeFirstServed {
+            msg!("SOLR_ERROR_23: pro-rata commitments are refunded by SettleCommitment");
+            return Err(TokenSaleError::InvalidSaleMode.into());
+        }
+        if token_sale_state.is_soft_cap_reached() {
+            msg!("SOLR_ERROR_21: soft cap has been reached");
+            msg!(&token_sale_state.usd_raised_amount.to_string());
+            msg!(&token_sale_state.soft_cap_amount.to_string());
+            return Err(TokenSaleError::SoftCapReached.into());
+        }
+        let mut user_purchase_record_state = validation::unpack_purchase_record(
+            program_id,
+            user_purchase_record,
+            token_sale_account,
+            user_account,
+        )?;
+        if user_purchase_record_state.is_refunded {
+            msg!("SOLR_ERROR_22: purchase already refunded");
+            return Err(TokenSaleError::PurchaseRefunded.into());
+        }
+        validation::check_pool_token_account(&token_sale_state, pool_usdt_account)?;
+        validation::check_sale_token_account(&token_sale_state, token_sale_solr_account)?;
+        validation::check_sale_authority(program_id, token_sale_account, &token_sale_state, sale_pda)?;
+        validation::check_unique_accounts(&[
+            user_purchase_record,
+            pool_usdt_account,
+            user_usdt_account,
+            token_sale_solr_account,
+            user_solr_account,
+        ])?;
+        let pool_usdt_account_info = validation::unpack_token_account(pool_usdt_account)?;
+        let token_sale_solr_account_info = validation::unpack_token_account(token_sale_solr_account)?;
+        validation::unpack_token_account_with_mint(user_usdt_account, &pool_usdt_account_info.mint)?;
+        validation::unpack_token_account_with_mint(user_solr_account, &token_sale_solr_account_info.mint)?;
+
+        if user_purchase_record_state.claimed_amount > 0 {
+            // Return delivered SOLR to the sale token account
+            msg!("Return delivered SOLR to the sale token account");
+            let transfer_solr_to_sale_ix = spl_token::instruction::transfer(
+                token_program.key,
+                user_solr_account.key,
+                token_sale_solr_account.key,
+                user_account.key,
+                &[user_account.key],
+                user_purchase_record_state.claimed_amount,
+            )?;
+            invoke(
+                &transfer_solr_to_sale_ix,
+                &[
+                    user_solr_account.clone(),
+                    token_sale_solr_account.clone(),
+                    user_account.clone(),
+                    token_program.clone(),
+                ],
+            )?;
+        }
+
+        // Refund USDT from the escrow to the user
+        msg!("Refund USDT from the escrow to the user");
+        let transfer_usdt_to_user_ix = spl_token::instruction::transfer(
+            token_program.key,
+            pool_usdt_account.key,
+            user_usdt_account.key,
+            sale_pda.key,
+            &[sale_pda.key],
+            user_purchase_record_state.usd_amount,
+        )?;
+        invoke_signed(
+            &transfer_usdt_to_user_ix,
+            &[
+                pool_usdt_account.clone(),
+                user_usdt_account.clone(),
+                sale_pda.clone(),
+                token_program.clone(),
+            ],
+            &[&[&token_sale_account.key.to_bytes()[..32], &[token_sale_state.bump_seed]]],
+        )?;
+
+        // tokens still vesting are released back to the sale
+        let locked_amount = user_purchase_record_state.token_amount
+            .checked_sub(user_purchase_record_state.claimed_amount)
+            .ok_or_else(Self::math_overflow)?;
+        token_sale_state.vesting_locked_amount = token_sale_state.vesting_locked_amount
+            .checked_sub(locked_amount)
+            .ok_or_else(Self::math_overflow)?;
+        user_purchase_record_state.is_refunded = true;
+
+        PurchaseRecord::pack(user_purchase_record_state, &mut user_purchase_record.data.borrow_mut())?;
+        TokenSale::pack(token_sale_state, &mut token_sale_account.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Processes [WithdrawSaleProceeds](enum.TokenSaleInstruction.html) instruction
+    fn process_withdraw_proceeds(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+
+        let init_account = next_account_info(account_info_iter)?;
+        if !init_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let token_sale_account = next_account_info(account_info_iter)?;
+
+        let pool_usdt_account = next_account_info(account_info_iter)?;
+        let destination_usdt_account = next_account_info(account_info_iter)?;
+
+        let sale_pda = next_account_info(account_info_iter)?;
+        let token_program = next_account_info(account_info_iter)?;
+        validation::check_token_program(token_program)?;
+
+        // check if sale proceeds can be withdrawn
+        let mut token_sale_state = validation::unpack_token_sale(program_id, token_sale_account)?;
+        validation::check_role(&token_sale_state, SaleRole::Admin, init_account)?;
+        if !token_sale_state.has_escrow() {
+            msg!("SOLR_ERROR_58: sale proceeds are not held in escrow");
+            return Err(TokenSaleError::ProceedsNotEscrowed.into());
+        }
+        Self::transition_status(&mut token_sale_state, SaleAction::WithdrawProceeds)?;
+        let clock = Clock::get()?;
+        if token_sale_state.sale_mode == SaleMode::ProRata &&
+            !token_sale_state.is_ended(clock.unix_timestamp as u64) {
+            msg!("SOLR_ERROR_15: commit window has not closed");
+            return Err(TokenSaleError::TokenSaleNotEnded.into());
+        }
+        if !token_sale_state.is_soft_cap_reached() {
+            msg!("SOLR_ERROR_20: soft cap has not been reached");
+            msg!(&token_sale_state.usd_raised_amount.to_string());
+            msg!(&token_sale_state.soft_cap_amount.to_string());
+            return Err(TokenSaleError::SoftCapNotReached.into());
+        }
+        validation::check_pool_token_account(&token_sale_state, pool_usdt_account)?;
+        validation::check_sale_authority(program_id, token_sale_account, &token_sale_state, sale_pda)?;
+        validation::check_unique_accounts(&[pool_usdt_account, destination_usdt_account])?;
+        let pool_usdt_account_info = validation::unpack_token_account(pool_usdt_account)?;
+        validation::unpack_token_account_with_mint(destination_usdt_account, &pool_usdt_account_info.mint)?;
+        // pro-rata commitments stay in escrow until settled
+        let withdraw_amount = token_sale_state.sale_proceeds()
+            .saturating_sub(token_sale_state.usd_withdrawn_amount);
+
+        // Transfer escrowed USDT to the destination account
+        msg!("Transfer escrowed USDT to the destination account");
+        msg!(&withdraw_amount.to_string());
+        let transfer_usdt_to_destination_ix = spl_token::instruction::transfer(
+            token_program.key,
+            pool_usdt_account.key,
+            destination_usdt_account.key,
+            sale_pda.key,
+            &[sale_pda.key],
+            withdraw_amount,
+        )?;
+        invoke_signed(
+            &transfer_usdt_to_destination_ix,
+            &[
+                pool_usdt_account.clone(),
+                destination_usdt_account.clone(),
+                sale_pda.clone(),
+                token_program.clone(),
+            ],
+            &[&[&token_sale_account.key.to_bytes()[..32], &[token_sale_state.bump_seed]]],
+        )?;
+
+        token_sale_state.usd_withdrawn_amount = token_sale_state.usd_withdrawn_amount
+            .checked_add(withdraw_amount)
+            .ok_or_else(Self::math_overflow)?;
+
+        TokenSale::pack(token_sale_state, &mut token_sale_account.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Processes [CommitToSale](enum.TokenSaleInstruction.html) instruction
+    fn process_commit_to_sale(
+        accounts: &[AccountInfo],
+        usd_amount: u64,
+        merkle_proof: Option<MerkleProof>,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+
+        let user_account = next_account_info(account_info_iter)?;
+        if !user_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let token_sale_account = next_account_info(account_info_iter)?;
+
+        let user_usdt_account = next_account_info(account_info_iter)?;
+        let pool_usdt_account = next_account_info(account_info_iter)?;
+
+        let user_purchase_record = next_account_info(account_info_iter)?;
+        let token_program = next_account_info(account_info_iter)?;
+
+        let token_whitelist_map = next_account_info(account_info_iter)?;
+        let token_whitelist_account = next_account_info(account_info_iter)?;
+        let token_whitelist_program = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+
+        let mut token_sale_state = validation::unpack_token_sale(program_id, token_sale_account)?;
+
+        // check if commitment is allowed
+        validation::check_token_program(token_program)?;
+        if token_sale_state.sale_mode != SaleMode::ProRata {
+            msg!("SOLR_ERROR_23: first come first served sales accept purchases only");
+            return Err(TokenSaleError::InvalidSaleMode.into());
+        }
+        let allocation_amount = Self::user_allocation(
+            &token_sale_state,
+            token_sale_account,
+            user_account,
+            token_whitelist_map,
+            token_whitelist_account,
+            token_whitelist_program,
+            merkle_proof.as_ref(),
+        )?;
+        Self::transition_status(&mut token_sale_state, SaleAction::Commit)?;
+        validation::check_pool_token_account(&token_sale_state, pool_usdt_account)?;
+        validation::check_unique_accounts(&[user_usdt_account, pool_usdt_account, user_purchase_record])?;
+        let pool_usdt_account_info = validation::unpack_token_account(pool_usdt_account)?;
+        validation::unpack_token_account_with_mint(user_usdt_account, &pool_usdt_account_info.mint)?;
+        let mut user_purchase_record_state = Self::load_purchase_record(
+            program_id,
+            token_sale_account,
+            user_account,
+            user_purchase_record,
+            system_program_info,
+        )?;
+        let committed_amount = user_purchase_record_state.usd_amount
+            .checked_add(usd_amount)
+            .ok_or_else(Self::math_overflow)?;
+        if committed_amount > allocation_amount {
+            msg!("SOLR_ERROR_11: amount exceeds your allocation");
+            msg!("{}", committed_amount);
+            msg!("{}", allocation_amount);
+            return Err(TokenSaleError::ExceedsAllocation.into());
+        }
+        if committed_amount < token_sale_state.usd_min_amount {
+            msg!("SOLR_ERROR_8: amount less than minimum allocation");
+            msg!(&committed_amount.to_string());
+            msg!(&token_sale_state.usd_min_amount.to_string());
+            return Err(TokenSaleError::AmountMinimum.into());
+        }
+        if committed_amount > token_sale_state.usd_max_amount {
+            msg!("SOLR_ERROR_9: amount more than maximum allocation");
+            msg!(&committed_amount.to_string());
+            msg!(&token_sale_state.usd_max_amount.to_string());
+            return Err(TokenSaleError::AmountMaximum.into());
+        }
+
+        // Transfer USDT to the escrow
+        msg!("Transfer USDT to the escrow");
+        let transfer_usdt_to_pool_ix = spl_token::instruction::transfer(
+            token_program.key,
+            user_usdt_account.key,
+            pool_usdt_account.key,
+            user_account.key,
+            &[user_account.key],
+            usd_amount,
+        )?;
+        invoke(
+            &transfer_usdt_to_pool_ix,
+            &[
+                user_usdt_account.clone(),
+                pool_usdt_account.clone(),
+                user_account.clone(),
+                token_program.clone(),
+            ],
+        )?;
+
+        user_purchase_record_state.usd_amount = committed_amount;
+        token_sale_state.usd_raised_amount = token_sale_state.usd_raised_amount
+            .checked_add(usd_amount)
+            .ok_or_else(Self::math_overflow)?;
+
+        PurchaseRecord::pack(user_purchase_record_state, &mut user_purchase_record.data.borrow_mut())?;
+        TokenSale::pack(token_sale_state, &mut token_sale_account.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Processes [SettleCommitment](enum.TokenSaleInstruction.html) instruction
+    fn process_settle_commitment(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+
+        let user_account = next_account_info(account_info_iter)?;
+        if !user_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let token_sale_account = next_account_info(account_info_iter)?;
+        let user_purchase_record = next_account_info(account_info_iter)?;
+
+        let pool_usdt_account = next_account_info(account_info_iter)?;
+        let user_usdt_account = next_account_info(account_info_iter)?;
+
+        let token_sale_solr_account = next_account_info(account_info_iter)?;
+        let user_solr_account = next_account_info(account_info_iter)?;
+
+        let sale_pda = next_account_info(account_info_iter)?;
+        let token_program = next_account_info(account_info_iter)?;
+        validation::check_token_program(token_program)?;
+
+        // check if commitment can be settled
+        let mut token_sale_state = validation::unpack_token_sale(program_id, token_sale_account)?;
+        if token_sale_state.sale_mode != SaleMode::ProRata {
+            msg!("SOLR_ERROR_23: only pro-rata commitments can be settled");
+            return Err(TokenSaleError::InvalidSaleMode.into());
+        }
+        Self::transition_status(&mut token_sale_state, SaleAction::Settle)?;
+        let mut user_purchase_record_state = validation::unpack_purchase_record(
+            program_id,
+            user_purchase_record,
+            token_sale_account,
+            user_account,
+        )?;
+        if user_purchase_record_state.is_settled {
+            msg!("SOLR_ERROR_24: commitment already settled");
+            return Err(TokenSaleError::CommitmentSettled.into());
+        }
+        validation::check_pool_token_account(&token_sale_state, pool_usdt_account)?;
+        validation::check_sale_token_account(&token_sale_state, token_sale_solr_account)?;
+        validation::check_sale_authority(program_id, token_sale_account, &token_sale_state, sale_pda)?;
+        validation::check_unique_accounts(&[
+            user_purchase_record,
+            pool_usdt_account,
+            user_usdt_account,
+            token_sale_solr_account,
+            user_solr_account,
+        ])?;
+        let pool_usdt_account_info = validation::unpack_token_account(pool_usdt_account)?;
+        let token_sale_solr_account_info = validation::unpack_token_account(token_sale_solr_account)?;
+        validation::unpack_token_account_with_mint(user_usdt_account, &pool_usdt_account_info.mint)?;
+        validation::unpack_token_account_with_mint(user_solr_account, &token_sale_solr_account_info.mint)?;
+        let filled_usd_amount = token_sale_state.filled_usd_amount(user_purchase_record_state.usd_amount)
+            .ok_or_else(Self::math_overflow)?;
+        let refund_usd_amount = user_purchase_record_state.usd_amount
+            .checked_sub(filled_usd_amount)
+            .ok_or_else(Self::math_overflow)?;
+        let token_purchase_amount = token_sale_state.token_amount(filled_usd_amount, token_sale_state.token_sale_price)
+            .ok_or_else(Self::math_overflow)?;
+        msg!(&filled_usd_amount.to_string());
+        msg!(&refund_usd_amount.to_string());
+
+        if refund_usd_amount > 0 {
+            // Refund unfilled USDT from the escrow to the user
+            msg!("Refund unfilled USDT from the escrow to the user");
+            let transfer_usdt_to_user_ix = spl_token::instruction::transfer(
+                token_program.key,
+                pool_usdt_account.key,
+                user_usdt_account.key,
+                sale_pda.key,
+                &[sale_pda.key],
+                refund_usd_amount,
+            )?;
+            invoke_signed(
+                &transfer_usdt_to_user_ix,
+                &[
+                    pool_usdt_account.clone(),
+                    user_usdt_account.clone(),
+                    sale_pda.clone(),
+                    token_program.clone(),
+                ],
+                &[&[&token_sale_account.key.to_bytes()[..32], &[token_sale_state.bump_seed]]],
+            )?;
+        }
+
+        if token_sale_state.has_vesting() {
+            // vested SOLR stays in the sale token account until claimed
+            token_sale_state.vesting_locked_amount = token_sale_state.vesting_locked_amount
+                .checked_add(token_purchase_amount)
+                .ok_or_else(Self::math_overflow)?;
+        } else if token_purchase_amount > 0 {
+            // Transfer SOLR to the user
+            msg!("Transfer SOLR to the user");
+            let transfer_solr_to_user_ix = spl_token::instruction::transfer(
+                token_program.key,
+                token_sale_solr_account.key,
+                user_solr_account.key,
+                sale_pda.key,
+                &[sale_pda.key],
+                token_purchase_amount,
+            )?;
+            invoke_signed(
+                &transfer_solr_to_user_ix,
+                &[
+                    token_sale_solr_account.clone(),
+                    user_solr_account.clone(),
+                    sale_pda.clone(),
+                    token_program.clone(),
+                ],
+                &[&[&token_sale_account.key.to_bytes()[..32], &[token_sale_state.bump_seed]]],
+            )?;
+            user_purchase_record_state.claimed_amount = token_purchase_amount;
+        }
+
+        user_purchase_record_state.token_amount = token_purchase_amount;
+        user_purchase_record_state.is_settled = true;
+        token_sale_state.token_settled_amount = token_sale_state.token_settled_amount
+            .checked_add(token_purchase_amount)
+            .ok_or_else(Self::math_overflow)?;
+        token_sale_state.usd_filled_amount = token_sale_state.usd_filled_amount
+            .checked_add(filled_usd_amount)
+            .ok_or_else(Self::math_overflow)?;
+
+        PurchaseRecord::pack(user_purchase_record_state, &mut user_purchase_record.data.borrow_mut())?;
+        TokenSale::pack(token_sale_state, &mut token_sale_account.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Processes [MigrateTokenSale](enum.TokenSaleInstruction.html) instruction
+    fn process_migrate_sale(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+
+        let init_account = next_account_info(account_info_iter)?;
+        if !init_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let token_sale_account = next_account_info(account_info_iter)?;
+
+        let token_sale_solr_account = next_account_info(account_info_iter)?;
+        let pool_usdt_account = next_account_info(account_info_iter)?;
+
+        let sale_mint_account = next_account_info(account_info_iter)?;
+        let usd_mint_account = next_account_info(account_info_iter)?;
+
+        let sale_pda_v1 = next_account_info(account_info_iter)?;
+        let token_program = next_account_info(account_info_iter)?;
+        validation::check_token_program(token_program)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        validation::check_system_program(system_program_info)?;
+
+        // check if token sale can be migrated
+        validation::check_account_owner(token_sale_account, program_id)?;
+        let version = TokenSale::version(&token_sale_account.data.borrow());
+        if version != 1 || token_sale_account.data_len() != TokenSaleV1::LEN {
+            msg!("SOLR_ERROR_30: token sale account has no older layout to migrate from");
+            msg!("{}", version);
+            return Err(TokenSaleError::InvalidAccountVersion.into());
+        }
+        let token_sale_v1_state = TokenSaleV1::unpack(&token_sale_account.data.borrow())?;
+        if token_sale_v1_state.init_pubkey != *init_account.key {
+            msg!("SOLR_ERROR_41: invalid signer");
+            msg!(&token_sale_v1_state.init_pubkey.to_string());
+            msg!(&init_account.key.to_string());
+            return Err(TokenSaleError::InvalidSigner.into());
+        }
+        if token_sale_v1_state.sale_token_account_pubkey != *token_sale_solr_account.key {
+            msg!("SOLR_ERROR_42: token sale account does not match");
+            msg!(&token_sale_v1_state.sale_token_account_pubkey.to_string());
+            msg!(&token_sale_solr_account.key.to_string());
+            return Err(TokenSaleError::SaleTokenAccountMismatch.into());
+        }
+        if token_sale_v1_state.pool_token_account_pubkey != *pool_usdt_account.key {
+            msg!("SOLR_ERROR_43: pool usdt account does not match");
+            msg!(&token_sale_v1_state.pool_token_account_pubkey.to_string());
+            msg!(&pool_usdt_account.key.to_string());
+            return Err(TokenSaleError::PoolTokenAccountMismatch.into());
+        }
+        let sale_mint_state = validation::unpack_mint(sale_mint_account)?;
+        let usd_mint_state = validation::unpack_mint(usd_mint_account)?;
+        let token_sale_solr_account_info =
+            validation::unpack_token_account_with_mint(token_sale_solr_account, sale_mint_account.key)?;
+        validation::unpack_token_account_with_mint(pool_usdt_account, usd_mint_account.key)?;
+        let (token_sale_v1_program_address, bump_seed_v1) = Self::find_authority_v1_address(program_id);
+        if *sale_pda_v1.key != token_sale_v1_program_address {
+            msg!("SOLR_ERROR_14: invalid version 1 token sale program derived address");
+            msg!(&sale_pda_v1.key.to_string());
+            return Err(TokenSaleError::InvalidProgramAddress.into());
+        }
+        let (token_sale_program_address, bump_seed) =
+            Self::find_authority_address(program_id, token_sale_account.key);
+        let token_sale_state = token_sale_v1_state
+            .migrate(
+                token_sale_solr_account_info.amount,
+                sale_mint_state.decimals,
+                usd_mint_state.decimals,
+                bump_seed,
+            )
+            .ok_or_else(Self::math_overflow)?;
+
+        // Transfer token sale solr account ownership to the token sale program derived address
+        msg!("Transfer token sale solr account ownership to the token sale program derived address");
+        let transfer_ownership_ix = spl_token::instruction::set_authority(
+            token_program.key,
+            token_sale_solr_account.key,
+            Some(&token_sale_program_address),
+            spl_token::instruction::AuthorityType::AccountOwner,
+            sale_pda_v1.key,
+            &[sale_pda_v1.key],
+        )?;
+        invoke_signed(
+            &transfer_ownership_ix,
+            &[
+                token_sale_solr_account.clone(),
+                sale_pda_v1.clone(),
+                token_program.clone(),
+            ],
+            &[&[TOKEN_SALE_V1_AUTHORITY_SEED, &[bump_seed_v1]]],
+        )?;
+
+        // Grow the token sale account to the current layout, keeping it rent exempt
+        let required_lamports = Rent::get()?
+            .minimum_balance(TokenSale::LEN)
+            .saturating_sub(token_sale_account.lamports());
+        if required_lamports > 0 {
+            invoke(
+                &system_instruction::transfer(init_account.key, token_sale_account.key, required_lamports),
+                &[init_account.clone(), token_sale_account.clone(), system_program_info.clone()],
+            )?;
+        }
+        token_sale_account.realloc(TokenSale::LEN, false)?;
+
+        TokenSale::pack(token_sale_state, &mut token_sale_account.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Processes [UpdateSaleParameters](enum.TokenSaleInstruction.html) instruction
+    fn process_update_sale_parameters(
+        accounts: &[AccountInfo],
+        usd_min_amount: u64,
+        usd_max_amount: u64,
+        token_sale_price: u64,
+        token_sale_time: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+
+        let operator_account = next_account_info(account_info_iter)?;
+        if !operator_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let token_sale_account = next_account_info(account_info_iter)?;
+
+        // check if token sale parameters can be updated
+        let mut token_sale_state = validation::unpack_token_sale(program_id, token_sale_account)?;
+        validation::check_role(&token_sale_state, SaleRole::Operator, operator_account)?;
+        Self::transition_status(&mut token_sale_state, SaleAction::UpdateParameters)?;
+
+        // check if the new parameters are consistent
+        if usd_max_amount == 0 || usd_min_amount > usd_max_amount {
+            msg!("SOLR_ERROR_32: invalid purchase limits");
+            msg!("{}", usd_min_amount);
+            msg!("{}", usd_max_amount);
+            return Err(TokenSaleError::InvalidPurchaseLimits.into());
+        }
+        if token_sale_price == 0 && token_sale_state.pricing_mode == PricingMode::Fixed {
+            msg!("SOLR_ERROR_33: token sale price must be greater than zero");
+            return Err(TokenSaleError::InvalidTokenSalePrice.into());
+        }
+        let clock = Clock::get()?;
+        if token_sale_time < clock.unix_timestamp as u64 || token_sale_time >= token_sale_state.token_sale_end_time {
+            msg!("SOLR_ERROR_34: token sale time must not be in the past and must be before the end time");
+            msg!("{}", token_sale_time);
+            msg!("{}", token_sale_state.token_sale_end_time);
+            return Err(TokenSaleError::InvalidTokenSaleTime.into());
+        }
+
+        msg!("usd_min_amount {} -> {}", token_sale_state.usd_min_amount, usd_min_amount);
+        msg!("usd_max_amount {} -> {}", token_sale_state.usd_max_amount, usd_max_amount);
+        msg!("token_sale_price {} -> {}", token_sale_state.token_sale_price, token_sale_price);
+        msg!("token_sale_time {} -> {}", token_sale_state.token_sale_time, token_sale_time);
+        token_sale_state.usd_min_amount = usd_min_amount;
+        token_sale_state.usd_max_amount = usd_max_amount;
+        token_sale_state.token_sale_price = token_sale_price;
+        token_sale_state.token_sale_time = token_sale_time;
+
+        TokenSale::pack(token_sale_state, &mut token_sale_account.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Processes [ProposeAdmin](enum.TokenSaleInstruction.html) instruction
+    fn process_propose_admin(
+        accounts: &[AccountInfo],
+        new_admin_pubkey: Pubkey,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+
+        let init_account = next_account_info(account_info_iter)?;
+        if !init_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let token_sale_account = next_account_info(account_info_iter)?;
+
+        // check if the signer owns the token sale
+        let mut token_sale_state = validation::unpack_token_sale(program_id, token_sale_account)?;
+        validation::check_role(&token_sale_state, SaleRole::Admin, init_account)?;
+
+        msg!("pending admin {} -> {}", token_sale_state.pending_admin_pubkey, new_admin_pubkey);
+        token_sale_state.pending_admin_pubkey = new_admin_pubkey;
+
+        TokenSale::pack(token_sale_state, &mut token_sale_account.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Processes [AcceptAdmin](enum.TokenSaleInstruction.html) instruction
+    fn process_accept_admin(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+
+        let new_admin_account = next_account_info(account_info_iter)?;
+        if !new_admin_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let token_sale_account = next_account_info(account_info_iter)?;
+
+        // check if the signer is the proposed admin
+        let mut token_sale_state = validation::unpack_token_sale(program_id, token_sale_account)?;
+        if token_sale_state.pending_admin_pubkey == Pubkey::default() {
+            msg!("SOLR_ERROR_35: no admin has been proposed");
+            return Err(TokenSaleError::NoPendingAdmin.into());
+        }
+        if token_sale_state.pending_admin_pubkey != *new_admin_account.key {
+            msg!("SOLR_ERROR_41: signer is not the proposed admin");
+            msg!(&token_sale_state.pending_admin_pubkey.to_string());
+            msg!(&new_admin_account.key.to_string());
+            return Err(TokenSaleError::InvalidSigner.into());
+        }
+
+        msg!("admin {} -> {}", token_sale_state.init_pubkey, new_admin_account.key);
+        // roles still held by the outgoing admin move with the sale
+        if token_sale_state.guardian_pubkey == token_sale_state.init_pubkey {
+            token_sale_state.guardian_pubkey = *new_admin_account.key;
+        }
+        if token_sale_state.operator_pubkey == token_sale_state.init_pubkey {
+            token_sale_state.operator_pubkey = *new_admin_account.key;
+        }
+        token_sale_state.init_pubkey = *new_admin_account.key;
+        token_sale_state.pending_admin_pubkey = Pubkey::default();
+
+        TokenSale::pack(token_sale_state, &mut token_sale_account.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Processes [SetSaleRole](enum.TokenSaleInstruction.html) instruction
+    fn process_set_sale_role(
+        accounts: &[AccountInfo],
+        role: SaleRole,
+        role_pubkey: Pubkey,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+
+        let init_account = next_account_info(account_info_iter)?;
+        if !init_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let token_sale_account = next_account_info(account_info_iter)?;
+
+        // check if the signer manages the token sale roles
+        let mut token_sale_state = validation::unpack_token_sale(program_id, token_sale_account)?;
+        validation::check_role(&token_sale_state, SaleRole::Admin, init_account)?;
+
+        if role == SaleRole::Admin {
+            msg!("SOLR_ERROR_59: the admin is changed with ProposeAdmin and AcceptAdmin");
+            return Err(TokenSaleError::InvalidSaleRole.into());
+        }
+
+        msg!("{:?} {} -> {}", role, token_sale_state.role_pubkey(role), role_pubkey);
+        if role == SaleRole::Guardian {
+            token_sale_state.guardian_pubkey = role_pubkey;
+        } else {
+            token_sale_state.operator_pubkey = role_pubkey;
+        }
+
+        TokenSale::pack(token_sale_state, &mut token_sale_account.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Processes [CancelTokenSale](enum.TokenSaleInstruction.html) instruction
+    fn process_cancel_sale(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+
+        let init_account = next_account_info(account_info_iter)?;
+        if !init_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let token_sale_account = next_account_info(account_info_iter)?;
+
+        let token_sale_solr_account = next_account_info(account_info_iter)?;
+        let destination_solr_account = next_account_info(account_info_iter)?;
+
+        let sale_pda = next_account_info(account_info_iter)?;
+        let token_program = next_account_info(account_info_iter)?;
+        validation::check_token_program(token_program)?;
+
+        // check if token sale can be cancelled
+        let mut token_sale_state = validation::unpack_token_sale(program_id, token_sale_account)?;
+        validation::check_role(&token_sale_state, SaleRole::Admin, init_account)?;
+        Self::transition_status(&mut token_sale_state, SaleAction::Cancel)?;
+        if token_sale_state.usd_raised_amount > 0 || token_sale_state.vesting_locked_amount > 0 {
+            msg!("SOLR_ERROR_31: token sale has purchases");
+            return Err(TokenSaleError::TokenSaleStarted.into());
+        }
+        validation::check_sale_token_account(&token_sale_state, token_sale_solr_account)?;
+        validation::check_sale_authority(program_id, token_sale_account, &token_sale_state, sale_pda)?;
+        validation::check_unique_accounts(&[token_sale_solr_account, destination_solr_account])?;
+        let token_sale_solr_account_info = validation::unpack_token_account(token_sale_solr_account)?;
+        validation::unpack_token_account_with_mint(destination_solr_account, &token_sale_solr_account_info.mint)?;
+
+        // Transfer every SOLR in the sale back to the destination account
+        msg!("Transfer SOLR for sale to the destination account");
+        msg!(&token_sale_solr_account_info.amount.to_string());
+        let transfer_solr_to_destination_ix = spl_token::instruction::transfer(
+            token_program.key,
+            token_sale_solr_account.key,
+            destination_solr_account.key,
+            sale_pda.key,
+            &[sale_pda.key],
+            token_sale_solr_account_info.amount,
+        )?;
+        invoke_signed(
+            &transfer_solr_to_destination_ix,
+            &[
+                token_sale_solr_account.clone(),
+                destination_solr_account.clone(),
+                sale_pda.clone(),
+                token_program.clone(),
+            ],
+            &[&[&token_sale_account.key.to_bytes()[..32], &[token_sale_state.bump_seed]]],
+        )?;
+
+        TokenSale::pack(token_sale_state, &mut token_sale_account.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Processes [CloseTokenSale](enum.TokenSaleInstruction.html) instruction
+    fn process_close_sale(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+
+        let init_account = next_account_info(account_info_iter)?;
+        if !init_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let token_sale_account = next_account_info(account_info_iter)?;
+
+        let token_sale_solr_account = next_account_info(account_info_iter)?;
+        let pool_usdt_account = next_account_info(account_info_iter)?;
+
+        let sale_pda = next_account_info(account_info_iter)?;
+        let token_program = next_account_info(account_info_iter)?;
+        validation::check_token_program(token_program)?;
+        let payment_config_account = next_account_info(account_info_iter)?;
+
+        // check if token sale can be closed
+        let mut token_sale_state = validation::unpack_token_sale(program_id, token_sale_account)?;
+        validation::check_role(&token_sale_state, SaleRole::Admin, init_account)?;
+        Self::transition_status(&mut token_sale_state, SaleAction::Close)?;
+        validation::check_sale_token_account(&token_sale_state, token_sale_solr_account)?;
+        validation::check_pool_token_account(&token_sale_state, pool_usdt_account)?;
+        validation::check_sale_authority(program_id, token_sale_account, &token_sale_state, sale_pda)?;
+        validation::check_unique_accounts(&[
+            init_account,
+            token_sale_account,
+            token_sale_solr_account,
+            pool_usdt_account,
+            payment_config_account,
+        ])?;
+        let close_payment_config = !payment_config_account.data_is_empty();
+        if close_payment_config {
+            validation::unpack_payment_config(program_id, payment_config_account, token_sale_account)?;
+        }
+        let token_sale_solr_amount = validation::unpack_token_account(token_sale_solr_account)?.amount;
+        if token_sale_solr_amount > 0 {
+            msg!("SOLR_ERROR_37: sale token account is not empty");
+            msg!(&token_sale_solr_amount.to_string());
+            return Err(TokenSaleError::TokenAccountNotEmpty.into());
+        }
+        // escrowed funds not refunded or withdrawn yet keep the sale open
+        let pool_usdt_account_info = validation::unpack_token_account(pool_usdt_account)?;
+        let close_pool_usdt_account = pool_usdt_account_info.owner == *sale_pda.key;
+        if close_pool_usdt_account && pool_usdt_account_info.amount > 0 {
+            msg!("SOLR_ERROR_37: pool usdt account is not empty");
+            msg!(&pool_usdt_account_info.amount.to_string());
+            return Err(TokenSaleError::TokenAccountNotEmpty.into());
+        }
+
+        // Close the token accounts held by the token sale program derived address
+        msg!("Close token sale solr account");
+        let close_solr_account_ix = spl_token::instruction::close_account(
+            token_program.key,
+            token_sale_solr_account.key,
+            init_account.key,
+            sale_pda.key,
+            &[sale_pda.key],
+        )?;
+        invoke_signed(
+            &close_solr_account_ix,
+            &[
+                token_sale_solr_account.clone(),
+                init_account.clone(),
+                sale_pda.clone(),
+                token_program.clone(),
+            ],
+            &[&[&token_sale_account.key.to_bytes()[..32], &[token_sale_state.bump_seed]]],
+        )?;
+        if close_pool_usdt_account {
+            msg!("Close pool usdt account");
+            let close_usdt_account_ix = spl_token::instruction::close_account(
+                token_program.key,
+                pool_usdt_account.key,
+                init_account.key,
+                sale_pda.key,
+                &[sale_pda.key],
+            )?;
+            invoke_signed(
+                &close_usdt_account_ix,
+                &[
+                    pool_usdt_account.clone(),
+                    init_account.clone(),
+                    sale_pda.clone(),
+                    token_program.clone(),
+                ],
+                &[&[&token_sale_account.key.to_bytes()[..32], &[token_sale_state.bump_seed]]],
+            )?;
+        }
+
+        // Return the rent of the token sale and its payment config to the owner and wipe their data
+        Self::close_program_account(token_sale_account, init_account)?;
+        if close_payment_config {
+            Self::close_program_account(payment_config_account, init_account)?;
+        }
+
+        Ok(())
+    }
+
+    /// Processes [SetPaymentMint](enum.TokenSaleInstruction.html) instruction
+    fn process_set_payment_mint(
+        accounts: &[AccountInfo],
+        price: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+
+        let init_account = next_account_info(account_info_iter)?;
+        if !init_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let token_sale_account = next_account_info(account_info_iter)?;
+        let payment_config_account = next_account_info(account_info_iter)?;
+
+        let payment_mint_account = next_account_info(account_info_iter)?;
+        let pool_token_account = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+
+        // check if the payment mints of the token sale can be changed
+        let mut token_sale_state = validation::unpack_token_sale(program_id, token_sale_account)?;
+        validation::check_role(&token_sale_state, SaleRole::Admin, init_account)?;
+        Self::transition_status(&mut token_sale_state, SaleAction::UpdateParameters)?;
+        if token_sale_state.has_escrow() || token_sale_state.pricing_mode != PricingMode::Fixed {
+            msg!("SOLR_ERROR_23: sales with escrow or auction pricing accept a single payment mint");
+            return Err(TokenSaleError::InvalidSaleMode.into());
+        }
+        let payment_mint_state = validation::unpack_mint(payment_mint_account)?;
+        validation::unpack_token_account_with_mint(pool_token_account, payment_mint_account.key)?;
+
+        let (payment_config_address, payment_config_bump_seed) =
+            Self::find_payment_config_address(program_id, token_sale_account.key);
+        if payment_config_address != *payment_config_account.key {
+            msg!("SOLR_ERROR_14: invalid payment config program derived address");
+            msg!(&payment_config_account.key.to_string());
+            return Err(TokenSaleError::InvalidProgramAddress.into());
+        }
+        if payment_config_account.data_is_empty() {
+            Self::create_pda_account(
+                init_account,
+                payment_config_account,
+                system_program_info,
+                PaymentConfig::LEN,
+                program_id,
+                &[
+                    PAYMENT_CONFIG_SEED,
+                    token_sale_account.key.as_ref(),
+                    &[payment_config_bump_seed],
+                ],
+            )?;
+        }
+        validation::check_account_owner(payment_config_account, program_id)?;
+        let mut payment_config_state = PaymentConfig::unpack_unchecked(&payment_config_account.data.borrow())?;
+        if !payment_config_state.is_initialized() {
+            payment_config_state.is_initialized = true;
+            payment_config_state.token_sale_pubkey = *token_sale_account.key;
+            payment_config_state.bump_seed = payment_config_bump_seed;
+            payment_config_state.payment_mints.clear();
+        }
+
+        // replace the entry of the payment mint, a zero price removes it
+        msg!("payment mint {} price {}", payment_mint_account.key, price);
+        payment_config_state.payment_mints
+            .retain(|payment_mint| payment_mint.mint_pubkey != *payment_mint_account.key);
+        if price > 0 {
+            if payment_config_state.payment_mints.len() >= MAX_PAYMENT_MINTS {
+                msg!("SOLR_ERROR_51: token sale accepts at most {} payment mints", MAX_PAYMENT_MINTS);
+                return Err(TokenSaleError::TooManyPaymentMints.into());
+            }
+            payment_config_state.payment_mints.push(PaymentMint {
+                mint_pubkey: *payment_mint_account.key,
+                pool_token_account_pubkey: *pool_token_account.key,
+                price,
+                decimals: payment_mint_state.decimals,
+            });
+        }
+
+        PaymentConfig::pack(payment_config_state, &mut payment_config_account.data.borrow_mut())?;
+        TokenSale::pack(token_sale_state, &mut token_sale_account.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Processes [SetOracle](enum.TokenSaleInstruction.html) instruction
+    fn process_set_oracle(
+        accounts: &[AccountInfo],
+        max_age: u64,
+        max_confidence_bps: u16,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+
+        let init_account = next_account_info(account_info_iter)?;
+        if !init_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let token_sale_account = next_account_info(account_info_iter)?;
+        let oracle_account = next_account_info(account_info_iter)?;
+
+        // check if the oracle of the token sale can be changed
+        let mut token_sale_state = validation::unpack_token_sale(program_id, token_sale_account)?;
+        validation::check_role(&token_sale_state, SaleRole::Admin, init_account)?;
+        Self::transition_status(&mut token_sale_state, SaleAction::UpdateParameters)?;
+
+        if max_age == 0 {
+            token_sale_state.oracle_pubkey = Pubkey::default();
+            token_sale_state.oracle_max_confidence_bps = 0;
+        } else {
+            validation::unpack_price_account(oracle_account)?;
+            token_sale_state.oracle_pubkey = *oracle_account.key;
+            token_sale_state.oracle_max_confidence_bps = max_confidence_bps;
+        }
+        token_sale_state.oracle_max_age = max_age;
+        msg!("oracle {} max age {} max confidence {}", token_sale_state.oracle_pubkey, max_age, max_confidence_bps);
+
+        TokenSale::pack(token_sale_state, &mut token_sale_account.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Processes [SetVoucherSigner](enum.TokenSaleInstruction.html) instruction
+    fn process_set_voucher_signer(
+        accounts: &[AccountInfo],
+        voucher_signer_pubkey: Pubkey,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+
+        let init_account = next_account_info(account_info_iter)?;
+        if !init_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let token_sale_account = next_account_info(account_info_iter)?;
+
+        // check if the allocations of the token sale can be changed
+        let mut token_sale_state = validation::unpack_token_sale(program_id, token_sale_account)?;
+        validation::check_role(&token_sale_state, SaleRole::Admin, init_account)?;
+        Self::transition_status(&mut token_sale_state, SaleAction::UpdateParameters)?;
+
+        token_sale_state.voucher_signer_pubkey = voucher_signer_pubkey;
+        msg!("voucher signer {}", voucher_signer_pubkey);
+
+        TokenSale::pack(token_sale_state, &mut token_sale_account.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Processes [SetMerkleRoot](enum.TokenSaleInstruction.html) instruction
+    fn process_set_merkle_root(
+        accounts: &[AccountInfo],
+        merkle_root: [u8; 32],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+
+        let init_account = next_account_info(account_info_iter)?;
+        if !init_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let token_sale_account = next_account_info(account_info_iter)?;
+
+        // check if the allocations of the token sale can be changed
+        let mut token_sale_state = validation::unpack_token_sale(program_id, token_sale_account)?;
+        validation::check_role(&token_sale_state, SaleRole::Admin, init_account)?;
+        Self::transition_status(&mut token_sale_state, SaleAction::UpdateParameters)?;
+
+        token_sale_state.merkle_root = merkle_root;
+        msg!("merkle root {}", solana_program::hash::Hash::new_from_array(merkle_root));
+
         TokenSale::pack(token_sale_state, &mut token_sale_account.data.borrow_mut())?;
 
         Ok(())
@@ -556,6 +2360,52 @@ impl PrintProgramError for TokenSaleError {
             TokenSaleError::ExceedsAllocation => msg!("Error: Amount Exceeds Your Allocation"),
             TokenSaleError::TokenSalePaused => msg!("Error: Token Sale Paused"),
             TokenSaleError::TokenSaleEnded => msg!("Error: Token Sale Ended"),
+            TokenSaleError::InvalidProgramAddress => msg!("Error: Invalid program address generated from bump seed and key"),
+            TokenSaleError::TokenSaleNotEnded => msg!("Error: Token Sale Not Ended"),
+            TokenSaleError::TokenSaleWithdrawn => msg!("Error: Token Sale Withdrawn"),
+            TokenSaleError::TokenSaleClosed => msg!("Error: Token Sale Closed"),
+            TokenSaleError::InvalidVestingSchedule => msg!("Error: Invalid Vesting Schedule"),
+            TokenSaleError::NothingToClaim => msg!("Error: No Vested Tokens To Claim"),
+            TokenSaleError::SoftCapNotReached => msg!("Error: Soft Cap Not Reached"),
+            TokenSaleError::SoftCapReached => msg!("Error: Soft Cap Reached"),
+            TokenSaleError::PurchaseRefunded => msg!("Error: Purchase Already Refunded"),
+            TokenSaleError::InvalidSaleMode => msg!("Error: Invalid Sale Mode"),
+            TokenSaleError::CommitmentSettled => msg!("Error: Commitment Already Settled"),
+            TokenSaleError::InvalidAuctionSchedule => msg!("Error: Invalid Auction Schedule"),
+            TokenSaleError::PriceExceedsMaximum => msg!("Error: Price Exceeds Maximum"),
+            TokenSaleError::MathOverflow => msg!("Error: Arithmetic Overflow"),
+            TokenSaleError::TokenSaleNotFunded => msg!("Error: Token Sale Not Funded"),
+            TokenSaleError::TokenSaleNotPaused => msg!("Error: Token Sale Not Paused"),
+            TokenSaleError::InvalidAccountVersion => msg!("Error: Invalid Account Version"),
+            TokenSaleError::TokenSaleStarted => msg!("Error: Token Sale Started"),
+            TokenSaleError::InvalidPurchaseLimits => msg!("Error: Invalid Purchase Limits"),
+            TokenSaleError::InvalidTokenSalePrice => msg!("Error: Invalid Token Sale Price"),
+            TokenSaleError::InvalidTokenSaleTime => msg!("Error: Invalid Token Sale Time"),
+            TokenSaleError::NoPendingAdmin => msg!("Error: No Pending Admin"),
+            TokenSaleError::TokenSaleCancelled => msg!("Error: Token Sale Cancelled"),
+            TokenSaleError::TokenAccountNotEmpty => msg!("Error: Token Account Not Empty"),
+            TokenSaleError::InvalidTokenProgram => msg!("Error: Invalid Token Program"),
+            TokenSaleError::InvalidSystemProgram => msg!("Error: Invalid System Program"),
+            TokenSaleError::InvalidAccountOwner => msg!("Error: Invalid Account Owner"),
+            TokenSaleError::InvalidSigner => msg!("Error: Invalid Signer"),
+            TokenSaleError::SaleTokenAccountMismatch => msg!("Error: Sale Token Account Mismatch"),
+            TokenSaleError::PoolTokenAccountMismatch => msg!("Error: Pool Token Account Mismatch"),
+            TokenSaleError::MintMismatch => msg!("Error: Token Account Mint Mismatch"),
+            TokenSaleError::PurchaseRecordMismatch => msg!("Error: Purchase Record Mismatch"),
+            TokenSaleError::InvalidWhitelist => msg!("Error: Invalid Whitelist Account"),
+            TokenSaleError::DuplicateAccount => msg!("Error: Duplicate Account"),
+            TokenSaleError::InvalidTokenSaleAmount => msg!("Error: Invalid Token Sale Amount"),
+            TokenSaleError::InvalidSaleTokenBalance => msg!("Error: Invalid Sale Token Balance"),
+            TokenSaleError::PaymentMintNotAccepted => msg!("Error: Payment Mint Not Accepted"),
+            TokenSaleError::TooManyPaymentMints => msg!("Error: Too Many Payment Mints"),
+            TokenSaleError::InvalidOracle => msg!("Error: Invalid Oracle"),
+            TokenSaleError::StaleOraclePrice => msg!("Error: Stale Oracle Price"),
+            TokenSaleError::OracleConfidenceTooWide => msg!("Error: Oracle Confidence Too Wide"),
+            TokenSaleError::InvalidVoucher => msg!("Error: Invalid Voucher"),
+            TokenSaleError::VoucherExpired => msg!("Error: Voucher Expired"),
+            TokenSaleError::InvalidMerkleProof => msg!("Error: Invalid Merkle Proof"),
+            TokenSaleError::ProceedsNotEscrowed => msg!("Error: Sale Proceeds Not Escrowed"),
+            TokenSaleError::InvalidSaleRole => msg!("Error: Invalid Sale Role"),
         }
     }
 }
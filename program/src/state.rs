@@ -1,3 +1,5 @@
+use std::convert::TryFrom;
+
 use solana_program::{
     program_error::ProgramError,
     program_pack::{IsInitialized, Pack, Sealed},
@@ -6,6 +8,174 @@ use solana_program::{
 
 use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
 
+use crate::error::TokenSaleError;
+
+/// How tokens are allocated between buyers
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SaleMode {
+    /// Buyers purchase with ExecuteTokenSale until the tokens run out
+    FirstComeFirstServed,
+    /// Buyers commit with CommitToSale and are filled pro-rata with SettleCommitment
+    /// once the commit window closes
+    ProRata,
+}
+
+impl SaleMode {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(SaleMode::FirstComeFirstServed),
+            1 => Some(SaleMode::ProRata),
+            _ => None,
+        }
+    }
+}
+
+/// How the price of tokens for sale is set
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PricingMode {
+    /// Tokens sell at `token_sale_price` for the whole sale
+    Fixed,
+    /// Price starts at `auction_start_price` when the sale goes live and decays by
+    /// `auction_decay_amount` every `auction_decay_interval` seconds down to `auction_floor_price`
+    DutchAuction,
+}
+
+impl PricingMode {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(PricingMode::Fixed),
+            1 => Some(PricingMode::DutchAuction),
+            _ => None,
+        }
+    }
+}
+
+/// Lifecycle of a token sale. A funded sale is stored as Funded and moves through
+/// Scheduled, Live and Ended with the clock; every other status changes only through
+/// the transitions in [SaleStatus::transition].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SaleStatus {
+    /// Created but not funded yet
+    Initialized,
+    /// Tokens for sale have been deposited
+    Funded,
+    /// Funded and waiting for `token_sale_time`
+    Scheduled,
+    /// Accepting purchases or commitments until `token_sale_end_time`
+    Live,
+    /// Paused by the admin, ends at `token_sale_end_time` unless resumed
+    Paused,
+    /// Ended by the admin or past `token_sale_end_time`
+    Ended,
+    /// Unsold tokens have been withdrawn, tokens returned by refunds can be withdrawn again
+    Finalized,
+    /// Cancelled before going live, the tokens for sale have been returned
+    Cancelled,
+}
+
+impl SaleStatus {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(SaleStatus::Initialized),
+            1 => Some(SaleStatus::Funded),
+            2 => Some(SaleStatus::Scheduled),
+            3 => Some(SaleStatus::Live),
+            4 => Some(SaleStatus::Paused),
+            5 => Some(SaleStatus::Ended),
+            6 => Some(SaleStatus::Finalized),
+            7 => Some(SaleStatus::Cancelled),
+            _ => None,
+        }
+    }
+
+    /// Transition table of the sale lifecycle. Returns the status after `action`,
+    /// or the error describing why `action` is not allowed in this status.
+    pub fn transition(self, action: SaleAction) -> Result<SaleStatus, TokenSaleError> {
+        use SaleAction::*;
+        use SaleStatus::*;
+        match (self, action) {
+            (Initialized, Fund) => Ok(Funded),
+            (Scheduled, Pause) | (Live, Pause) => Ok(Paused),
+            (Paused, Resume) => Ok(Funded),
+            (Scheduled, End) | (Live, End) | (Paused, End) => Ok(Ended),
+            (Ended, WithdrawUnsold) | (Finalized, WithdrawUnsold) => Ok(Finalized),
+            (Live, Purchase) | (Live, Commit) => Ok(Live),
+            (Live, Claim) | (Paused, Claim) | (Ended, Claim) | (Finalized, Claim) => Ok(self),
+            (Live, WithdrawProceeds) | (Paused, WithdrawProceeds) |
+            (Ended, WithdrawProceeds) | (Finalized, WithdrawProceeds) => Ok(self),
+            (Ended, Refund) | (Finalized, Refund) | (Ended, Settle) | (Finalized, Settle) => Ok(self),
+            (Initialized, UpdateParameters) => Ok(Initialized),
+            (Scheduled, UpdateParameters) => Ok(Funded),
+            (Initialized, Cancel) | (Scheduled, Cancel) => Ok(Cancelled),
+            (Ended, Close) | (Finalized, Close) | (Cancelled, Close) => Ok(self),
+
+            (Cancelled, _) => Err(TokenSaleError::TokenSaleCancelled),
+
+            (Initialized, _) => Err(TokenSaleError::TokenSaleNotFunded),
+            (_, Fund) => Err(TokenSaleError::TokenSaleFunded),
+            (Ended, _) | (Finalized, _) => match action {
+                Purchase | Commit => Err(TokenSaleError::TokenSaleClosed),
+                _ => Err(TokenSaleError::TokenSaleEnded),
+            },
+            (_, WithdrawUnsold) | (_, Refund) | (_, Settle) | (_, Close) => Err(TokenSaleError::TokenSaleNotEnded),
+            (Paused, _) => Err(TokenSaleError::TokenSalePaused),
+            (_, UpdateParameters) | (_, Cancel) => Err(TokenSaleError::TokenSaleStarted),
+            (_, Resume) => Err(TokenSaleError::TokenSaleNotPaused),
+            _ => Err(TokenSaleError::TokenSaleNotStarted),
+        }
+    }
+}
+
+/// Instructions that move a token sale through its lifecycle
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SaleAction {
+    Fund,
+    Purchase,
+    Commit,
+    Pause,
+    Resume,
+    End,
+    WithdrawUnsold,
+    Claim,
+    Refund,
+    WithdrawProceeds,
+    Settle,
+    UpdateParameters,
+    Cancel,
+    Close,
+}
+
+/// Accounts allowed to manage a token sale
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SaleRole {
+    /// Owner of the sale (`init_pubkey`), withdraws from the sale and manages the other roles
+    Admin,
+    /// Pauses the sale in an emergency
+    Guardian,
+    /// Resumes and ends the sale and updates its parameters
+    Operator,
+}
+
+impl SaleRole {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(SaleRole::Admin),
+            1 => Some(SaleRole::Guardian),
+            2 => Some(SaleRole::Operator),
+            _ => None,
+        }
+    }
+}
+
+/// Token sale and auction prices are expressed in usd per whole token for sale
+/// multiplied by this value, independent of the decimals of either mint
+pub const PRICE_MULTIPLIER: u64 = 1_000_000;
+
+/// Layout version of token sale accounts. The version is stored in the first byte,
+/// which holds `is_initialized` in the original layout and so reads as version 1 there.
+/// Zero marks an account that is not initialized.
+pub const TOKEN_SALE_VERSION: u8 = 2;
+
 pub struct TokenSale {
     pub is_initialized: bool,
     pub init_pubkey: Pubkey,
@@ -18,8 +188,245 @@ pub struct TokenSale {
     pub usd_max_amount: u64,
     pub token_sale_price: u64,
     pub token_sale_time: u64,
-    pub token_sale_paused: bool,
-    pub token_sale_ended: bool,
+    pub token_sale_end_time: u64,
+    pub status: SaleStatus,
+    pub bump_seed: u8,
+    pub vesting_tge_percent: u8,
+    pub vesting_start_time: u64,
+    pub vesting_cliff_time: u64,
+    pub vesting_end_time: u64,
+    pub vesting_locked_amount: u64,
+    pub soft_cap_amount: u64,
+    pub usd_raised_amount: u64,
+    pub sale_mode: SaleMode,
+    pub usd_withdrawn_amount: u64,
+    pub token_settled_amount: u64,
+    /// Usd amount filled by settled pro-rata commitments, each rounded down
+    pub usd_filled_amount: u64,
+    pub pricing_mode: PricingMode,
+    pub auction_start_price: u64,
+    pub auction_floor_price: u64,
+    pub auction_decay_amount: u64,
+    pub auction_decay_interval: u64,
+    pub sale_mint_decimals: u8,
+    pub usd_mint_decimals: u8,
+    pub pending_admin_pubkey: Pubkey,
+    pub guardian_pubkey: Pubkey,
+    pub operator_pubkey: Pubkey,
+    /// Price account quoting native SOL in usd, or the default pubkey without an oracle
+    pub oracle_pubkey: Pubkey,
+    /// Oldest oracle price accepted, in seconds
+    pub oracle_max_age: u64,
+    /// Widest oracle confidence interval accepted, in basis points of the price
+    pub oracle_max_confidence_bps: u16,
+    /// Key signing allocation vouchers used instead of the whitelist program, or the
+    /// default pubkey to read allocations from the whitelist program
+    pub voucher_signer_pubkey: Pubkey,
+    /// Root of the merkle whitelist of user allocations used instead of the whitelist
+    /// program, or all zeros to read allocations from the whitelist program
+    pub merkle_root: [u8; 32],
+}
+
+impl TokenSale {
+    /// Layout version of a token sale account
+    pub fn version(src: &[u8]) -> u8 {
+        src.first().copied().unwrap_or(0)
+    }
+
+    /// Account holding `role` in the sale
+    pub fn role_pubkey(&self, role: SaleRole) -> Pubkey {
+        match role {
+            SaleRole::Admin => self.init_pubkey,
+            SaleRole::Guardian => self.guardian_pubkey,
+            SaleRole::Operator => self.operator_pubkey,
+        }
+    }
+
+    /// Status of the sale at `unix_timestamp`. A funded sale is scheduled until its
+    /// start time and live until its end time, and a paused sale still ends on time.
+    pub fn current_status(&self, unix_timestamp: u64) -> SaleStatus {
+        match self.status {
+            SaleStatus::Funded | SaleStatus::Scheduled | SaleStatus::Live | SaleStatus::Paused
+                if unix_timestamp >= self.token_sale_end_time => SaleStatus::Ended,
+            SaleStatus::Funded | SaleStatus::Scheduled | SaleStatus::Live
+                if unix_timestamp < self.token_sale_time => SaleStatus::Scheduled,
+            SaleStatus::Funded | SaleStatus::Scheduled | SaleStatus::Live => SaleStatus::Live,
+            status => status,
+        }
+    }
+
+    /// A sale counts as ended once it is ended explicitly or its end time has passed
+    pub fn is_ended(&self, unix_timestamp: u64) -> bool {
+        matches!(self.current_status(unix_timestamp), SaleStatus::Ended | SaleStatus::Finalized)
+    }
+
+    /// Tokens in the sale token account owed to buyers: credited to purchase records
+    /// and not claimed yet, or filled by pro-rata commitments not settled yet
+    pub fn reserved_token_amount(&self) -> Option<u64> {
+        let unsettled_amount = match self.sale_mode {
+            SaleMode::FirstComeFirstServed => 0,
+            SaleMode::ProRata if self.is_soft_cap_reached() => {
+                let filled_amount = self.usd_raised_amount.min(self.usd_cap_amount()?);
+                self.token_amount(filled_amount, self.token_sale_price)?
+                    .saturating_sub(self.token_settled_amount)
+            },
+            SaleMode::ProRata => 0,
+        };
+        self.vesting_locked_amount.checked_add(unsettled_amount)
+    }
+
+    /// Price in effect at `unix_timestamp`
+    pub fn current_price(&self, unix_timestamp: u64) -> u64 {
+        match self.pricing_mode {
+            PricingMode::Fixed => self.token_sale_price,
+            PricingMode::DutchAuction => self.auction_price(unix_timestamp),
+        }
+    }
+
+    /// Tokens bought with `usd_amount` at `price`, both in base units of their mints.
+    /// Rounds down so the sale never hands out more tokens than were paid for.
+    pub fn token_amount(&self, usd_amount: u64, price: u64) -> Option<u64> {
+        self.payment_token_amount(usd_amount, price, self.usd_mint_decimals)
+    }
+
+    /// Tokens bought with `payment_amount` of a mint with `payment_mint_decimals` at
+    /// `price` in that mint, both amounts in base units. Rounds down like [TokenSale::token_amount].
+    pub fn payment_token_amount(&self, payment_amount: u64, price: u64, payment_mint_decimals: u8) -> Option<u64> {
+        let numerator = (payment_amount as u128)
+            .checked_mul(PRICE_MULTIPLIER as u128)?
+            .checked_mul(10u128.checked_pow(self.sale_mint_decimals as u32)?)?;
+        let denominator = (price as u128)
+            .checked_mul(10u128.checked_pow(payment_mint_decimals as u32)?)?;
+        u64::try_from(numerator.checked_div(denominator)?).ok()
+    }
+
+    /// Usd paid for `token_amount` at `price`, both in base units of their mints.
+    /// Rounds down so the usd amount never buys more than `token_amount`.
+    pub fn usd_amount(&self, token_amount: u64, price: u64) -> Option<u64> {
+        let numerator = (token_amount as u128)
+            .checked_mul(price as u128)?
+            .checked_mul(10u128.checked_pow(self.usd_mint_decimals as u32)?)?;
+        let denominator = (PRICE_MULTIPLIER as u128)
+            .checked_mul(10u128.checked_pow(self.sale_mint_decimals as u32)?)?;
+        u64::try_from(numerator.checked_div(denominator)?).ok()
+    }
+
+    /// Auction price at `unix_timestamp`, decayed in steps from the start price to the floor price
+    pub fn auction_price(&self, unix_timestamp: u64) -> u64 {
+        let decay_steps = unix_timestamp
+            .saturating_sub(self.token_sale_time)
+            .checked_div(self.auction_decay_interval)
+            .unwrap_or(0);
+        let decay_amount = decay_steps.saturating_mul(self.auction_decay_amount);
+        self.auction_start_price
+            .saturating_sub(decay_amount)
+            .max(self.auction_floor_price)
+    }
+
+    /// Payments in native SOL are priced by the oracle instead of a fixed price
+    pub fn has_oracle(&self) -> bool {
+        self.oracle_pubkey != Pubkey::default()
+    }
+
+    /// Allocations are read from signed vouchers instead of the whitelist program
+    pub fn has_voucher_signer(&self) -> bool {
+        self.voucher_signer_pubkey != Pubkey::default()
+    }
+
+    /// Allocations are proven against a merkle root instead of read from the whitelist program
+    pub fn has_merkle_root(&self) -> bool {
+        self.merkle_root != [0; 32]
+    }
+
+    /// Usd value of `payment_amount` of a mint with `payment_mint_decimals` at an oracle
+    /// `price` scaled by 10^`expo`, in base units of the usd mint. Rounds down.
+    pub fn oracle_usd_amount(&self, payment_amount: u64, payment_mint_decimals: u8, price: u64, expo: i32) -> Option<u64> {
+        let mut numerator = (payment_amount as u128)
+            .checked_mul(price as u128)?
+            .checked_mul(10u128.checked_pow(self.usd_mint_decimals as u32)?)?;
+        let mut denominator = 10u128.checked_pow(payment_mint_decimals as u32)?;
+        if expo < 0 {
+            denominator = denominator.checked_mul(10u128.checked_pow(expo.unsigned_abs())?)?;
+        } else {
+            numerator = numerator.checked_mul(10u128.checked_pow(expo as u32)?)?;
+        }
+        u64::try_from(numerator.checked_div(denominator)?).ok()
+    }
+
+    /// Purchased tokens are vested unless the whole purchase unlocks at TGE
+    pub fn has_vesting(&self) -> bool {
+        self.vesting_tge_percent < 100
+    }
+
+    /// Sale proceeds are held in escrow by the sale authority until the soft cap is reached
+    pub fn has_soft_cap(&self) -> bool {
+        self.soft_cap_amount > 0
+    }
+
+    pub fn is_soft_cap_reached(&self) -> bool {
+        self.usd_raised_amount >= self.soft_cap_amount
+    }
+
+    /// Sale proceeds are held in escrow by the sale authority while they may be refunded
+    pub fn has_escrow(&self) -> bool {
+        self.has_soft_cap() || self.sale_mode == SaleMode::ProRata
+    }
+
+    /// Usd amount needed to buy every token for sale
+    pub fn usd_cap_amount(&self) -> Option<u64> {
+        self.usd_amount(self.token_sale_amount, self.token_sale_price)
+    }
+
+    /// Usd amount of a pro-rata commitment filled once the commit window has closed.
+    /// Commitments are filled in full unless the sale is oversubscribed, and not at
+    /// all if the sale missed its soft cap.
+    pub fn filled_usd_amount(&self, committed_amount: u64) -> Option<u64> {
+        if !self.is_soft_cap_reached() {
+            return Some(0);
+        }
+        let usd_cap_amount = self.usd_cap_amount()?;
+        if self.usd_raised_amount <= usd_cap_amount {
+            return Some(committed_amount);
+        }
+        let filled_amount = committed_amount as u128 * usd_cap_amount as u128 / self.usd_raised_amount as u128;
+        u64::try_from(filled_amount).ok()
+    }
+
+    /// Usd amount the sale has earned, excluding funds that may still be refunded.
+    /// Pro-rata sales earn the fills of settled commitments only, so the escrow always
+    /// covers the rounded down fills and refunds of commitments not settled yet.
+    pub fn sale_proceeds(&self) -> u64 {
+        if !self.is_soft_cap_reached() {
+            return 0;
+        }
+        match self.sale_mode {
+            SaleMode::FirstComeFirstServed => self.usd_raised_amount,
+            SaleMode::ProRata => self.usd_filled_amount,
+        }
+    }
+
+    /// Amount of `total_amount` unlocked at `unix_timestamp` under the vesting schedule.
+    /// The TGE share unlocks at `vesting_start_time`, nothing more unlocks before
+    /// `vesting_cliff_time`, and the rest unlocks linearly until `vesting_end_time`.
+    pub fn vested_amount(&self, total_amount: u64, unix_timestamp: u64) -> u64 {
+        if !self.has_vesting() {
+            return total_amount;
+        }
+        if unix_timestamp < self.vesting_start_time {
+            return 0;
+        }
+        let tge_amount = (total_amount as u128 * self.vesting_tge_percent as u128 / 100) as u64;
+        if unix_timestamp < self.vesting_cliff_time {
+            return tge_amount;
+        }
+        if unix_timestamp >= self.vesting_end_time {
+            return total_amount;
+        }
+        let elapsed = (unix_timestamp - self.vesting_start_time) as u128;
+        let duration = (self.vesting_end_time - self.vesting_start_time) as u128;
+        let linear_amount = (total_amount - tge_amount) as u128 * elapsed / duration;
+        tge_amount + linear_amount as u64
+    }
 }
 
 impl Sealed for TokenSale {}
@@ -31,9 +438,340 @@ impl IsInitialized for TokenSale {
 }
 
 impl Pack for TokenSale {
-    const LEN: usize = 203;
+    const LEN: usize = 522;
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
         let src = array_ref![src, 0, TokenSale::LEN];
+        let (
+            is_initialized,
+            init_pubkey,
+            sale_token_account_pubkey,
+            pool_token_account_pubkey,
+            whitelist_map_pubkey,
+            whitelist_program_pubkey,
+            token_sale_amount,
+            usd_min_amount,
+            usd_max_amount,
+            token_sale_price,
+            token_sale_time,
+            token_sale_end_time,
+            status,
+            bump_seed,
+            vesting_tge_percent,
+            vesting_start_time,
+            vesting_cliff_time,
+            vesting_end_time,
+            vesting_locked_amount,
+            soft_cap_amount,
+            usd_raised_amount,
+            sale_mode,
+            usd_withdrawn_amount,
+            token_settled_amount,
+            usd_filled_amount,
+            pricing_mode,
+            auction_start_price,
+            auction_floor_price,
+            auction_decay_amount,
+            auction_decay_interval,
+            sale_mint_decimals,
+            usd_mint_decimals,
+            pending_admin_pubkey,
+            guardian_pubkey,
+            operator_pubkey,
+            oracle_pubkey,
+            oracle_max_age,
+            oracle_max_confidence_bps,
+            voucher_signer_pubkey,
+            merkle_root,
+        ) = array_refs![src, 1, 32, 32, 32, 32, 32, 8, 8, 8, 8, 8, 8, 1, 1, 1, 8, 8, 8, 8, 8, 8, 1, 8, 8, 8, 1, 8, 8, 8, 8, 1, 1, 32, 32, 32, 32, 8, 2, 32, 32];
+
+        Ok(TokenSale {
+            is_initialized: match is_initialized {
+                [0] => false,
+                [TOKEN_SALE_VERSION] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            init_pubkey: Pubkey::new_from_array(*init_pubkey),
+            sale_token_account_pubkey: Pubkey::new_from_array(*sale_token_account_pubkey),
+            pool_token_account_pubkey: Pubkey::new_from_array(*pool_token_account_pubkey),
+            whitelist_map_pubkey: Pubkey::new_from_array(*whitelist_map_pubkey),
+            whitelist_program_pubkey: Pubkey::new_from_array(*whitelist_program_pubkey),
+            token_sale_amount: u64::from_le_bytes(*token_sale_amount),
+            usd_min_amount: u64::from_le_bytes(*usd_min_amount),
+            usd_max_amount: u64::from_le_bytes(*usd_max_amount),
+            token_sale_price: u64::from_le_bytes(*token_sale_price),
+            token_sale_time: u64::from_le_bytes(*token_sale_time),
+            token_sale_end_time: u64::from_le_bytes(*token_sale_end_time),
+            status: SaleStatus::from_u8(status[0]).ok_or(ProgramError::InvalidAccountData)?,
+            bump_seed: bump_seed[0],
+            vesting_tge_percent: vesting_tge_percent[0],
+            vesting_start_time: u64::from_le_bytes(*vesting_start_time),
+            vesting_cliff_time: u64::from_le_bytes(*vesting_cliff_time),
+            vesting_end_time: u64::from_le_bytes(*vesting_end_time),
+            vesting_locked_amount: u64::from_le_bytes(*vesting_locked_amount),
+            soft_cap_amount: u64::from_le_bytes(*soft_cap_amount),
+            usd_raised_amount: u64::from_le_bytes(*usd_raised_amount),
+            sale_mode: SaleMode::from_u8(sale_mode[0]).ok_or(ProgramError::InvalidAccountData)?,
+            usd_withdrawn_amount: u64::from_le_bytes(*usd_withdrawn_amount),
+            token_settled_amount: u64::from_le_bytes(*token_settled_amount),
+            usd_filled_amount: u64::from_le_bytes(*usd_filled_amount),
+            pricing_mode: PricingMode::from_u8(pricing_mode[0]).ok_or(ProgramError::InvalidAccountData)?,
+            auction_start_price: u64::from_le_bytes(*auction_start_price),
+            auction_floor_price: u64::from_le_bytes(*auction_floor_price),
+            auction_decay_amount: u64::from_le_bytes(*auction_decay_amount),
+            auction_decay_interval: u64::from_le_bytes(*auction_decay_interval),
+            sale_mint_decimals: sale_mint_decimals[0],
+            usd_mint_decimals: usd_mint_decimals[0],
+            pending_admin_pubkey: Pubkey::new_from_array(*pending_admin_pubkey),
+            guardian_pubkey: Pubkey::new_from_array(*guardian_pubkey),
+            operator_pubkey: Pubkey::new_from_array(*operator_pubkey),
+            oracle_pubkey: Pubkey::new_from_array(*oracle_pubkey),
+            oracle_max_age: u64::from_le_bytes(*oracle_max_age),
+            oracle_max_confidence_bps: u16::from_le_bytes(*oracle_max_confidence_bps),
+            voucher_signer_pubkey: Pubkey::new_from_array(*voucher_signer_pubkey),
+            merkle_root: *merkle_root,
+        })
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, TokenSale::LEN];
+        let (
+            is_initialized_dst,
+            init_pubkey_dst,
+            sale_token_account_pubkey_dst,
+            pool_token_account_pubkey_dst,
+            whitelist_map_pubkey_dst,
+            whitelist_program_pubkey_dst,
+            token_sale_amount_dst,
+            usd_min_amount_dst,
+            usd_max_amount_dst,
+            token_sale_price_dst,
+            token_sale_time_dst,
+            token_sale_end_time_dst,
+            status_dst,
+            bump_seed_dst,
+            vesting_tge_percent_dst,
+            vesting_start_time_dst,
+            vesting_cliff_time_dst,
+            vesting_end_time_dst,
+            vesting_locked_amount_dst,
+            soft_cap_amount_dst,
+            usd_raised_amount_dst,
+            sale_mode_dst,
+            usd_withdrawn_amount_dst,
+            token_settled_amount_dst,
+            usd_filled_amount_dst,
+            pricing_mode_dst,
+            auction_start_price_dst,
+            auction_floor_price_dst,
+            auction_decay_amount_dst,
+            auction_decay_interval_dst,
+            sale_mint_decimals_dst,
+            usd_mint_decimals_dst,
+            pending_admin_pubkey_dst,
+            guardian_pubkey_dst,
+            operator_pubkey_dst,
+            oracle_pubkey_dst,
+            oracle_max_age_dst,
+            oracle_max_confidence_bps_dst,
+            voucher_signer_pubkey_dst,
+            merkle_root_dst,
+        ) = mut_array_refs![dst, 1, 32, 32, 32, 32, 32, 8, 8, 8, 8, 8, 8, 1, 1, 1, 8, 8, 8, 8, 8, 8, 1, 8, 8, 8, 1, 8, 8, 8, 8, 1, 1, 32, 32, 32, 32, 8, 2, 32, 32];
+
+        let TokenSale {
+            is_initialized,
+            init_pubkey,
+            sale_token_account_pubkey,
+            pool_token_account_pubkey,
+            whitelist_map_pubkey,
+            whitelist_program_pubkey,
+            token_sale_amount,
+            usd_min_amount,
+            usd_max_amount,
+            token_sale_price,
+            token_sale_time,
+            token_sale_end_time,
+            status,
+            bump_seed,
+            vesting_tge_percent,
+            vesting_start_time,
+            vesting_cliff_time,
+            vesting_end_time,
+            vesting_locked_amount,
+            soft_cap_amount,
+            usd_raised_amount,
+            sale_mode,
+            usd_withdrawn_amount,
+            token_settled_amount,
+            usd_filled_amount,
+            pricing_mode,
+            auction_start_price,
+            auction_floor_price,
+            auction_decay_amount,
+            auction_decay_interval,
+            sale_mint_decimals,
+            usd_mint_decimals,
+            pending_admin_pubkey,
+            guardian_pubkey,
+            operator_pubkey,
+            oracle_pubkey,
+            oracle_max_age,
+            oracle_max_confidence_bps,
+            voucher_signer_pubkey,
+            merkle_root,
+        } = self;
+
+        is_initialized_dst[0] = if *is_initialized { TOKEN_SALE_VERSION } else { 0 };
+        init_pubkey_dst.copy_from_slice(init_pubkey.as_ref());
+        sale_token_account_pubkey_dst.copy_from_slice(sale_token_account_pubkey.as_ref());
+        pool_token_account_pubkey_dst.copy_from_slice(pool_token_account_pubkey.as_ref());
+        whitelist_map_pubkey_dst.copy_from_slice(whitelist_map_pubkey.as_ref());
+        whitelist_program_pubkey_dst.copy_from_slice(whitelist_program_pubkey.as_ref());
+        *token_sale_amount_dst = token_sale_amount.to_le_bytes();
+        *usd_min_amount_dst = usd_min_amount.to_le_bytes();
+        *usd_max_amount_dst = usd_max_amount.to_le_bytes();
+        *token_sale_price_dst = token_sale_price.to_le_bytes();
+        *token_sale_time_dst = token_sale_time.to_le_bytes();
+        *token_sale_end_time_dst = token_sale_end_time.to_le_bytes();
+        status_dst[0] = *status as u8;
+        bump_seed_dst[0] = *bump_seed;
+        vesting_tge_percent_dst[0] = *vesting_tge_percent;
+        *vesting_start_time_dst = vesting_start_time.to_le_bytes();
+        *vesting_cliff_time_dst = vesting_cliff_time.to_le_bytes();
+        *vesting_end_time_dst = vesting_end_time.to_le_bytes();
+        *vesting_locked_amount_dst = vesting_locked_amount.to_le_bytes();
+        *soft_cap_amount_dst = soft_cap_amount.to_le_bytes();
+        *usd_raised_amount_dst = usd_raised_amount.to_le_bytes();
+        sale_mode_dst[0] = *sale_mode as u8;
+        *usd_withdrawn_amount_dst = usd_withdrawn_amount.to_le_bytes();
+        *token_settled_amount_dst = token_settled_amount.to_le_bytes();
+        *usd_filled_amount_dst = usd_filled_amount.to_le_bytes();
+        pricing_mode_dst[0] = *pricing_mode as u8;
+        *auction_start_price_dst = auction_start_price.to_le_bytes();
+        *auction_floor_price_dst = auction_floor_price.to_le_bytes();
+        *auction_decay_amount_dst = auction_decay_amount.to_le_bytes();
+        *auction_decay_interval_dst = auction_decay_interval.to_le_bytes();
+        sale_mint_decimals_dst[0] = *sale_mint_decimals;
+        usd_mint_decimals_dst[0] = *usd_mint_decimals;
+        pending_admin_pubkey_dst.copy_from_slice(pending_admin_pubkey.as_ref());
+        guardian_pubkey_dst.copy_from_slice(guardian_pubkey.as_ref());
+        operator_pubkey_dst.copy_from_slice(operator_pubkey.as_ref());
+        oracle_pubkey_dst.copy_from_slice(oracle_pubkey.as_ref());
+        *oracle_max_age_dst = oracle_max_age.to_le_bytes();
+        *oracle_max_confidence_bps_dst = oracle_max_confidence_bps.to_le_bytes();
+        voucher_signer_pubkey_dst.copy_from_slice(voucher_signer_pubkey.as_ref());
+        *merkle_root_dst = *merkle_root;
+    }
+}
+
+/// Seed of the program derived address that held the sale token accounts of every
+/// version 1 token sale
+pub const TOKEN_SALE_V1_AUTHORITY_SEED: &[u8] = b"solrsale";
+
+/// Token sale account in the original, version 1 layout. These sales are read only
+/// until MigrateTokenSale moves them to the current layout.
+pub struct TokenSaleV1 {
+    pub is_initialized: bool,
+    pub init_pubkey: Pubkey,
+    pub sale_token_account_pubkey: Pubkey,
+    pub pool_token_account_pubkey: Pubkey,
+    pub whitelist_map_pubkey: Pubkey,
+    pub whitelist_program_pubkey: Pubkey,
+    pub token_sale_amount: u64,
+    pub usd_min_amount: u64,
+    pub usd_max_amount: u64,
+    pub token_sale_price: u64,
+    pub token_sale_time: u64,
+    pub token_sale_paused: bool,
+    pub token_sale_ended: bool,
+}
+
+impl TokenSaleV1 {
+    /// Converts the sale to the current layout. Version 1 sales have no end time, vesting,
+    /// soft cap or escrow, and price the sale in token base units per usd base unit, which
+    /// is converted to usd per whole token rounded up in favor of the sale. Version 1 sales
+    /// are funded separately, so a sale token account holding less than the token sale
+    /// amount leaves the sale waiting for FundTokenSale.
+    pub fn migrate(
+        &self,
+        sale_token_amount: u64,
+        sale_mint_decimals: u8,
+        usd_mint_decimals: u8,
+        bump_seed: u8,
+    ) -> Option<TokenSale> {
+        let numerator = (PRICE_MULTIPLIER as u128)
+            .checked_mul(10u128.checked_pow(sale_mint_decimals as u32)?)?;
+        let denominator = (self.token_sale_price as u128)
+            .checked_mul(10u128.checked_pow(usd_mint_decimals as u32)?)?;
+        let token_sale_price = numerator
+            .checked_add(denominator.checked_sub(1)?)?
+            .checked_div(denominator)?;
+        let status = if self.token_sale_ended {
+            SaleStatus::Ended
+        } else if sale_token_amount < self.token_sale_amount {
+            SaleStatus::Initialized
+        } else if self.token_sale_paused {
+            SaleStatus::Paused
+        } else {
+            SaleStatus::Funded
+        };
+
+        Some(TokenSale {
+            is_initialized: self.is_initialized,
+            init_pubkey: self.init_pubkey,
+            sale_token_account_pubkey: self.sale_token_account_pubkey,
+            pool_token_account_pubkey: self.pool_token_account_pubkey,
+            whitelist_map_pubkey: self.whitelist_map_pubkey,
+            whitelist_program_pubkey: self.whitelist_program_pubkey,
+            token_sale_amount: self.token_sale_amount,
+            usd_min_amount: self.usd_min_amount,
+            usd_max_amount: self.usd_max_amount,
+            token_sale_price: u64::try_from(token_sale_price).ok()?,
+            token_sale_time: self.token_sale_time,
+            token_sale_end_time: u64::MAX,
+            status,
+            bump_seed,
+            vesting_tge_percent: 100,
+            vesting_start_time: 0,
+            vesting_cliff_time: 0,
+            vesting_end_time: 0,
+            vesting_locked_amount: 0,
+            soft_cap_amount: 0,
+            usd_raised_amount: 0,
+            sale_mode: SaleMode::FirstComeFirstServed,
+            usd_withdrawn_amount: 0,
+            token_settled_amount: 0,
+            usd_filled_amount: 0,
+            pricing_mode: PricingMode::Fixed,
+            auction_start_price: 0,
+            auction_floor_price: 0,
+            auction_decay_amount: 0,
+            auction_decay_interval: 0,
+            sale_mint_decimals,
+            usd_mint_decimals,
+            pending_admin_pubkey: Pubkey::default(),
+            guardian_pubkey: self.init_pubkey,
+            operator_pubkey: self.init_pubkey,
+            oracle_pubkey: Pubkey::default(),
+            oracle_max_age: 0,
+            oracle_max_confidence_bps: 0,
+            voucher_signer_pubkey: Pubkey::default(),
+            merkle_root: [0; 32],
+        })
+    }
+}
+
+impl Sealed for TokenSaleV1 {}
+
+impl IsInitialized for TokenSaleV1 {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for TokenSaleV1 {
+    const LEN: usize = 203;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, TokenSaleV1::LEN];
         let (
             is_initialized,
             init_pubkey,
@@ -50,7 +788,7 @@ impl Pack for TokenSale {
             token_sale_ended,
         ) = array_refs![src, 1, 32, 32, 32, 32, 32, 8, 8, 8, 8, 8, 1, 1];
 
-        Ok(TokenSale {
+        Ok(TokenSaleV1 {
             is_initialized: match is_initialized {
                 [0] => false,
                 [1] => true,
@@ -80,7 +818,7 @@ impl Pack for TokenSale {
     }
 
     fn pack_into_slice(&self, dst: &mut [u8]) {
-        let dst = array_mut_ref![dst, 0, TokenSale::LEN];
+        let dst = array_mut_ref![dst, 0, TokenSaleV1::LEN];
         let (
             is_initialized_dst,
             init_pubkey_dst,
@@ -97,7 +835,7 @@ impl Pack for TokenSale {
             token_sale_ended_dst,
         ) = mut_array_refs![dst, 1, 32, 32, 32, 32, 32, 8, 8, 8, 8, 8, 1, 1];
 
-        let TokenSale {
+        let TokenSaleV1 {
             is_initialized,
             init_pubkey,
             sale_token_account_pubkey,
@@ -128,3 +866,492 @@ impl Pack for TokenSale {
         token_sale_ended_dst[0] = *token_sale_ended as u8;
     }
 }
+
+/// Seed prefix of the per-buyer purchase record program derived address
+pub const PURCHASE_RECORD_SEED: &[u8] = b"purchase";
+
+pub struct PurchaseRecord {
+    pub is_initialized: bool,
+    pub token_sale_pubkey: Pubkey,
+    pub user_pubkey: Pubkey,
+    pub usd_amount: u64,
+    pub token_amount: u64,
+    pub claimed_amount: u64,
+    pub is_refunded: bool,
+    pub is_settled: bool,
+    pub bump_seed: u8,
+}
+
+impl Sealed for PurchaseRecord {}
+
+impl IsInitialized for PurchaseRecord {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for PurchaseRecord {
+    const LEN: usize = 92;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, PurchaseRecord::LEN];
+        let (
+            is_initialized,
+            token_sale_pubkey,
+            user_pubkey,
+            usd_amount,
+            token_amount,
+            claimed_amount,
+            is_refunded,
+            is_settled,
+            bump_seed,
+        ) = array_refs![src, 1, 32, 32, 8, 8, 8, 1, 1, 1];
+
+        Ok(PurchaseRecord {
+            is_initialized: match is_initialized {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            token_sale_pubkey: Pubkey::new_from_array(*token_sale_pubkey),
+            user_pubkey: Pubkey::new_from_array(*user_pubkey),
+            usd_amount: u64::from_le_bytes(*usd_amount),
+            token_amount: u64::from_le_bytes(*token_amount),
+            claimed_amount: u64::from_le_bytes(*claimed_amount),
+            is_refunded: match is_refunded {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            is_settled: match is_settled {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            bump_seed: bump_seed[0],
+        })
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, PurchaseRecord::LEN];
+        let (
+            is_initialized_dst,
+            token_sale_pubkey_dst,
+            user_pubkey_dst,
+            usd_amount_dst,
+            token_amount_dst,
+            claimed_amount_dst,
+            is_refunded_dst,
+            is_settled_dst,
+            bump_seed_dst,
+        ) = mut_array_refs![dst, 1, 32, 32, 8, 8, 8, 1, 1, 1];
+
+        let PurchaseRecord {
+            is_initialized,
+            token_sale_pubkey,
+            user_pubkey,
+            usd_amount,
+            token_amount,
+            claimed_amount,
+            is_refunded,
+            is_settled,
+            bump_seed,
+        } = self;
+
+        is_initialized_dst[0] = *is_initialized as u8;
+        token_sale_pubkey_dst.copy_from_slice(token_sale_pubkey.as_ref());
+        user_pubkey_dst.copy_from_slice(user_pubkey.as_ref());
+        *usd_amount_dst = usd_amount.to_le_bytes();
+        *token_amount_dst = token_amount.to_le_bytes();
+        *claimed_amount_dst = claimed_amount.to_le_bytes();
+        is_refunded_dst[0] = *is_refunded as u8;
+        is_settled_dst[0] = *is_settled as u8;
+        bump_seed_dst[0] = *bump_seed;
+    }
+}
+
+/// Seed prefix of the per-sale payment config program derived address
+pub const PAYMENT_CONFIG_SEED: &[u8] = b"payment";
+
+/// Most payment mints a sale accepts in addition to the mint of its pool token account
+pub const MAX_PAYMENT_MINTS: usize = 4;
+
+/// Payment mint accepted by a sale in addition to the mint of its pool token account
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PaymentMint {
+    pub mint_pubkey: Pubkey,
+    pub pool_token_account_pubkey: Pubkey,
+    /// Price of a whole token for sale in this mint, multiplied by PRICE_MULTIPLIER
+    pub price: u64,
+    pub decimals: u8,
+}
+
+impl PaymentMint {
+    const LEN: usize = 73;
+}
+
+/// Payment mints of a token sale, stored next to the sale at a program derived address.
+/// Purchases in these mints are counted in usd at the token sale price, so allocations,
+/// purchase limits and the amount raised stay in one usd unit.
+pub struct PaymentConfig {
+    pub is_initialized: bool,
+    pub token_sale_pubkey: Pubkey,
+    pub bump_seed: u8,
+    pub payment_mints: Vec<PaymentMint>,
+}
+
+impl PaymentConfig {
+    /// Payment mint entry of `mint_pubkey`
+    pub fn payment_mint(&self, mint_pubkey: &Pubkey) -> Option<&PaymentMint> {
+        self.payment_mints.iter().find(|payment_mint| payment_mint.mint_pubkey == *mint_pubkey)
+    }
+}
+
+impl Sealed for PaymentConfig {}
+
+impl IsInitialized for PaymentConfig {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for PaymentConfig {
+    const LEN: usize = 35 + MAX_PAYMENT_MINTS * PaymentMint::LEN;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, PaymentConfig::LEN];
+        let (
+            is_initialized,
+            token_sale_pubkey,
+            bump_seed,
+            payment_mint_count,
+            payment_mints,
+        ) = array_refs![src, 1, 32, 1, 1, MAX_PAYMENT_MINTS * PaymentMint::LEN];
+
+        let payment_mint_count = payment_mint_count[0] as usize;
+        if payment_mint_count > MAX_PAYMENT_MINTS {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let payment_mints = payment_mints
+            .chunks_exact(PaymentMint::LEN)
+            .take(payment_mint_count)
+            .map(|payment_mint| {
+                let payment_mint = array_ref![payment_mint, 0, PaymentMint::LEN];
+                let (mint_pubkey, pool_token_account_pubkey, price, decimals) =
+                    array_refs![payment_mint, 32, 32, 8, 1];
+                PaymentMint {
+                    mint_pubkey: Pubkey::new_from_array(*mint_pubkey),
+                    pool_token_account_pubkey: Pubkey::new_from_array(*pool_token_account_pubkey),
+                    price: u64::from_le_bytes(*price),
+                    decimals: decimals[0],
+                }
+            })
+            .collect();
+
+        Ok(PaymentConfig {
+            is_initialized: match is_initialized {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            token_sale_pubkey: Pubkey::new_from_array(*token_sale_pubkey),
+            bump_seed: bump_seed[0],
+            payment_mints,
+        })
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, PaymentConfig::LEN];
+        let (
+            is_initialized_dst,
+            token_sale_pubkey_dst,
+            bump_seed_dst,
+            payment_mint_count_dst,
+            payment_mints_dst,
+        ) = mut_array_refs![dst, 1, 32, 1, 1, MAX_PAYMENT_MINTS * PaymentMint::LEN];
+
+        let PaymentConfig {
+            is_initialized,
+            token_sale_pubkey,
+            bump_seed,
+            payment_mints,
+        } = self;
+
+        is_initialized_dst[0] = *is_initialized as u8;
+        token_sale_pubkey_dst.copy_from_slice(token_sale_pubkey.as_ref());
+        bump_seed_dst[0] = *bump_seed;
+        payment_mint_count_dst[0] = payment_mints.len() as u8;
+        payment_mints_dst.fill(0);
+        for (payment_mint, payment_mint_dst) in payment_mints.iter().zip(payment_mints_dst.chunks_exact_mut(PaymentMint::LEN)) {
+            let payment_mint_dst = array_mut_ref![payment_mint_dst, 0, PaymentMint::LEN];
+            let (mint_pubkey_dst, pool_token_account_pubkey_dst, price_dst, decimals_dst) =
+                mut_array_refs![payment_mint_dst, 32, 32, 8, 1];
+            mint_pubkey_dst.copy_from_slice(payment_mint.mint_pubkey.as_ref());
+            pool_token_account_pubkey_dst.copy_from_slice(payment_mint.pool_token_account_pubkey.as_ref());
+            *price_dst = payment_mint.price.to_le_bytes();
+            decimals_dst[0] = payment_mint.decimals;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vesting_sale(vesting_tge_percent: u8) -> TokenSale {
+        let mut token_sale = TokenSale::unpack_unchecked(&[0; TokenSale::LEN]).unwrap();
+        token_sale.vesting_tge_percent = vesting_tge_percent;
+        token_sale.vesting_start_time = 1000;
+        token_sale.vesting_cliff_time = 2000;
+        token_sale.vesting_end_time = 5000;
+        token_sale
+    }
+
+    #[test]
+    fn test_vested_amount() {
+        let token_sale = vesting_sale(20);
+        assert_eq!(token_sale.vested_amount(1000, 999), 0);
+        assert_eq!(token_sale.vested_amount(1000, 1000), 200);
+        assert_eq!(token_sale.vested_amount(1000, 1999), 200);
+        assert_eq!(token_sale.vested_amount(1000, 2000), 400);
+        assert_eq!(token_sale.vested_amount(1000, 3000), 600);
+        assert_eq!(token_sale.vested_amount(1000, 5000), 1000);
+        assert_eq!(token_sale.vested_amount(1000, 9999), 1000);
+    }
+
+    #[test]
+    fn test_vested_amount_without_vesting() {
+        let token_sale = vesting_sale(100);
+        assert!(!token_sale.has_vesting());
+        assert_eq!(token_sale.vested_amount(1000, 0), 1000);
+    }
+
+    #[test]
+    fn test_filled_usd_amount() {
+        let mut token_sale = vesting_sale(100);
+        token_sale.sale_mode = SaleMode::ProRata;
+        token_sale.token_sale_amount = 10000;
+        token_sale.token_sale_price = PRICE_MULTIPLIER / 10;
+        token_sale.usd_raised_amount = 800;
+        assert_eq!(token_sale.filled_usd_amount(300), Some(300));
+
+        token_sale.usd_raised_amount = 4000;
+        assert_eq!(token_sale.filled_usd_amount(300), Some(75));
+        assert_eq!(token_sale.filled_usd_amount(4000), Some(1000));
+
+        token_sale.soft_cap_amount = 5000;
+        assert_eq!(token_sale.filled_usd_amount(300), Some(0));
+    }
+
+    #[test]
+    fn test_sale_proceeds() {
+        let mut token_sale = vesting_sale(100);
+        token_sale.sale_mode = SaleMode::ProRata;
+        token_sale.token_sale_amount = 10000;
+        token_sale.token_sale_price = PRICE_MULTIPLIER / 10;
+        token_sale.usd_raised_amount = 4000;
+        token_sale.usd_filled_amount = 75;
+        assert_eq!(token_sale.sale_proceeds(), 75);
+
+        // three commitments of 1 against a cap of 2 all fill 0
+        token_sale.token_sale_amount = 20;
+        token_sale.usd_raised_amount = 3;
+        token_sale.usd_filled_amount = 0;
+        assert_eq!(token_sale.filled_usd_amount(1), Some(0));
+        assert_eq!(token_sale.sale_proceeds(), 0);
+
+        token_sale.sale_mode = SaleMode::FirstComeFirstServed;
+        assert_eq!(token_sale.sale_proceeds(), 3);
+
+        token_sale.soft_cap_amount = 5;
+        assert_eq!(token_sale.sale_proceeds(), 0);
+    }
+
+    #[test]
+    fn test_token_amount() {
+        let mut token_sale = vesting_sale(100);
+        token_sale.sale_mint_decimals = 9;
+        token_sale.usd_mint_decimals = 6;
+        // 0.15 usd per token
+        let price = 150_000;
+        assert_eq!(token_sale.token_amount(3_000_000, price), Some(20_000_000_000));
+        assert_eq!(token_sale.usd_amount(20_000_000_000, price), Some(3_000_000));
+        // rounds down in favor of the sale
+        assert_eq!(token_sale.token_amount(1, price), Some(6_666));
+        assert_eq!(token_sale.usd_amount(6_666, price), Some(0));
+        assert_eq!(token_sale.token_amount(1_000_000, 0), None);
+        assert_eq!(token_sale.token_amount(u64::MAX, 1), None);
+    }
+
+    #[test]
+    fn test_oracle_usd_amount() {
+        let mut token_sale = vesting_sale(100);
+        token_sale.usd_mint_decimals = 6;
+        // 0.1 sol at 123.45 usd with an exponent of -8
+        let price = 12_345_000_000;
+        assert_eq!(token_sale.oracle_usd_amount(100_000_000, 9, price, -8), Some(12_345_000));
+        assert_eq!(token_sale.oracle_usd_amount(100_000_000, 9, 12_345, 2), Some(123_450_000_000));
+        assert_eq!(token_sale.oracle_usd_amount(1, 9, price, -8), Some(0));
+        assert_eq!(token_sale.oracle_usd_amount(u64::MAX, 0, u64::MAX, 0), None);
+    }
+
+    #[test]
+    fn test_payment_config() {
+        let mut token_sale = vesting_sale(100);
+        token_sale.sale_mint_decimals = 9;
+        token_sale.usd_mint_decimals = 6;
+        let payment_mint = PaymentMint {
+            mint_pubkey: Pubkey::new_unique(),
+            pool_token_account_pubkey: Pubkey::new_unique(),
+            // 0.3 per token in a mint with 8 decimals
+            price: 300_000,
+            decimals: 8,
+        };
+        assert_eq!(
+            token_sale.payment_token_amount(90_000_000, payment_mint.price, payment_mint.decimals),
+            Some(3_000_000_000),
+        );
+
+        let payment_config = PaymentConfig {
+            is_initialized: true,
+            token_sale_pubkey: Pubkey::new_unique(),
+            bump_seed: 254,
+            payment_mints: vec![payment_mint],
+        };
+        let mut data = [0; PaymentConfig::LEN];
+        payment_config.pack_into_slice(&mut data);
+        let unpacked = PaymentConfig::unpack(&data).unwrap();
+        assert_eq!(unpacked.token_sale_pubkey, payment_config.token_sale_pubkey);
+        assert_eq!(unpacked.bump_seed, 254);
+        assert_eq!(unpacked.payment_mints, vec![payment_mint]);
+        assert_eq!(unpacked.payment_mint(&payment_mint.mint_pubkey), Some(&payment_mint));
+        assert_eq!(unpacked.payment_mint(&Pubkey::new_unique()), None);
+
+        data[34] = MAX_PAYMENT_MINTS as u8 + 1;
+        assert!(PaymentConfig::unpack(&data).is_err());
+    }
+
+    #[test]
+    fn test_auction_price() {
+        let mut token_sale = vesting_sale(100);
+        token_sale.pricing_mode = PricingMode::DutchAuction;
+        token_sale.token_sale_time = 1000;
+        token_sale.auction_start_price = 50;
+        token_sale.auction_floor_price = 20;
+        token_sale.auction_decay_amount = 5;
+        token_sale.auction_decay_interval = 60;
+        assert_eq!(token_sale.auction_price(1000), 50);
+        assert_eq!(token_sale.auction_price(1059), 50);
+        assert_eq!(token_sale.auction_price(1060), 45);
+        assert_eq!(token_sale.auction_price(1300), 25);
+        assert_eq!(token_sale.auction_price(1360), 20);
+        assert_eq!(token_sale.auction_price(99999), 20);
+    }
+
+    #[test]
+    fn test_vested_amount_without_linear_period() {
+        let mut token_sale = vesting_sale(50);
+        token_sale.vesting_cliff_time = 1000;
+        token_sale.vesting_end_time = 1000;
+        assert_eq!(token_sale.vested_amount(1000, 999), 0);
+        assert_eq!(token_sale.vested_amount(1000, 1000), 1000);
+    }
+
+    #[test]
+    fn test_current_status() {
+        let mut token_sale = vesting_sale(100);
+        token_sale.token_sale_time = 1000;
+        token_sale.token_sale_end_time = 2000;
+        assert_eq!(token_sale.current_status(1500), SaleStatus::Initialized);
+
+        token_sale.status = SaleStatus::Funded;
+        assert_eq!(token_sale.current_status(999), SaleStatus::Scheduled);
+        assert_eq!(token_sale.current_status(1000), SaleStatus::Live);
+        assert_eq!(token_sale.current_status(2000), SaleStatus::Ended);
+
+        token_sale.status = SaleStatus::Paused;
+        assert_eq!(token_sale.current_status(1500), SaleStatus::Paused);
+        assert_eq!(token_sale.current_status(2000), SaleStatus::Ended);
+
+        token_sale.status = SaleStatus::Ended;
+        assert_eq!(token_sale.current_status(1500), SaleStatus::Ended);
+    }
+
+    #[test]
+    fn test_status_transition() {
+        use SaleAction::*;
+        use SaleStatus::*;
+        assert_eq!(Initialized.transition(Fund).unwrap(), Funded);
+        assert_eq!(Scheduled.transition(Pause).unwrap(), Paused);
+        assert_eq!(Paused.transition(Resume).unwrap(), Funded);
+        assert_eq!(Live.transition(Purchase).unwrap(), Live);
+        assert_eq!(Live.transition(End).unwrap(), Ended);
+        assert_eq!(Ended.transition(WithdrawUnsold).unwrap(), Finalized);
+        assert_eq!(Finalized.transition(WithdrawUnsold).unwrap(), Finalized);
+        assert_eq!(Finalized.transition(Refund).unwrap(), Finalized);
+        assert_eq!(Initialized.transition(UpdateParameters).unwrap(), Initialized);
+        assert_eq!(Scheduled.transition(UpdateParameters).unwrap(), Funded);
+        assert_eq!(Scheduled.transition(Cancel).unwrap(), Cancelled);
+        assert_eq!(Cancelled.transition(Close).unwrap(), Cancelled);
+
+        assert!(matches!(Initialized.transition(Purchase), Err(TokenSaleError::TokenSaleNotFunded)));
+        assert!(matches!(Live.transition(Fund), Err(TokenSaleError::TokenSaleFunded)));
+        assert!(matches!(Scheduled.transition(Commit), Err(TokenSaleError::TokenSaleNotStarted)));
+        assert!(matches!(Live.transition(Resume), Err(TokenSaleError::TokenSaleNotPaused)));
+        assert!(matches!(Paused.transition(Purchase), Err(TokenSaleError::TokenSalePaused)));
+        assert!(matches!(Paused.transition(Settle), Err(TokenSaleError::TokenSaleNotEnded)));
+        assert!(matches!(Ended.transition(Purchase), Err(TokenSaleError::TokenSaleClosed)));
+        assert!(matches!(Ended.transition(Resume), Err(TokenSaleError::TokenSaleEnded)));
+        assert!(matches!(Live.transition(UpdateParameters), Err(TokenSaleError::TokenSaleStarted)));
+        assert!(matches!(Paused.transition(Cancel), Err(TokenSaleError::TokenSalePaused)));
+        assert!(matches!(Cancelled.transition(Fund), Err(TokenSaleError::TokenSaleCancelled)));
+        assert!(matches!(Live.transition(Close), Err(TokenSaleError::TokenSaleNotEnded)));
+    }
+
+    #[test]
+    fn test_migrate_token_sale_v1() {
+        let mut data = [0; TokenSaleV1::LEN];
+        TokenSaleV1 {
+            is_initialized: true,
+            init_pubkey: Pubkey::new_unique(),
+            sale_token_account_pubkey: Pubkey::new_unique(),
+            pool_token_account_pubkey: Pubkey::new_unique(),
+            whitelist_map_pubkey: Pubkey::new_unique(),
+            whitelist_program_pubkey: Pubkey::new_unique(),
+            token_sale_amount: 1000,
+            usd_min_amount: 10,
+            usd_max_amount: 500,
+            token_sale_price: 10,
+            token_sale_time: 123456789,
+            token_sale_paused: true,
+            token_sale_ended: false,
+        }.pack_into_slice(&mut data);
+        assert_eq!(TokenSale::version(&data), 1);
+
+        let token_sale_v1 = TokenSaleV1::unpack(&data).unwrap();
+        let token_sale = token_sale_v1.migrate(1000, 6, 6, 255).unwrap();
+        assert_eq!(token_sale.init_pubkey, token_sale_v1.init_pubkey);
+        assert_eq!(token_sale.sale_token_account_pubkey, token_sale_v1.sale_token_account_pubkey);
+        assert_eq!(token_sale.pool_token_account_pubkey, token_sale_v1.pool_token_account_pubkey);
+        assert_eq!(token_sale.whitelist_map_pubkey, token_sale_v1.whitelist_map_pubkey);
+        assert_eq!(token_sale.whitelist_program_pubkey, token_sale_v1.whitelist_program_pubkey);
+        assert_eq!(token_sale.token_sale_amount, 1000);
+        assert_eq!(token_sale.usd_min_amount, 10);
+        assert_eq!(token_sale.usd_max_amount, 500);
+        assert_eq!(token_sale.token_sale_time, 123456789);
+        assert_eq!(token_sale.status, SaleStatus::Paused);
+        // 10 tokens per usd is 0.1 usd per token
+        assert_eq!(token_sale.token_sale_price, PRICE_MULTIPLIER / 10);
+        assert_eq!(token_sale.token_amount(50, token_sale.token_sale_price), Some(500));
+
+        let mut data = [0; TokenSale::LEN];
+        token_sale.pack_into_slice(&mut data);
+        assert_eq!(TokenSale::version(&data), TOKEN_SALE_VERSION);
+        assert_eq!(TokenSale::unpack(&data).unwrap().token_sale_price, PRICE_MULTIPLIER / 10);
+
+        // not funded yet
+        assert_eq!(token_sale_v1.migrate(999, 6, 6, 255).unwrap().status, SaleStatus::Initialized);
+
+        let mut token_sale_v1 = token_sale_v1;
+        token_sale_v1.token_sale_price = 0;
+        assert!(token_sale_v1.migrate(1000, 6, 6, 255).is_none());
+    }
+}
@@ -0,0 +1,317 @@
+//! Account checks shared by every token sale instruction. Each failed check is
+//! logged with its error code and reported as a specific [TokenSaleError].
+
+use solana_program::{
+    account_info::AccountInfo,
+    ed25519_program,
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack},
+    pubkey::Pubkey,
+    system_program,
+    sysvar::instructions::{self, load_current_index_checked, load_instruction_at_checked},
+};
+use spl_token::state::{Account as TokenAccount, Mint};
+use crate::{
+    error::TokenSaleError,
+    merkle::MerkleProof,
+    oracle::{PriceAccount, PYTH_PROGRAM_ID},
+    processor::Processor,
+    state::{PaymentConfig, PurchaseRecord, SaleRole, TokenSale, TOKEN_SALE_VERSION},
+    voucher::Voucher,
+};
+
+/// Checks that `token_program` is the SPL token program
+pub fn check_token_program(token_program: &AccountInfo) -> ProgramResult {
+    if !spl_token::check_id(token_program.key) {
+        msg!("SOLR_ERROR_38: invalid token program");
+        msg!(&token_program.key.to_string());
+        return Err(TokenSaleError::InvalidTokenProgram.into());
+    }
+    Ok(())
+}
+
+/// Checks that `system_program_info` is the system program
+pub fn check_system_program(system_program_info: &AccountInfo) -> ProgramResult {
+    if !system_program::check_id(system_program_info.key) {
+        msg!("SOLR_ERROR_39: invalid system program");
+        msg!(&system_program_info.key.to_string());
+        return Err(TokenSaleError::InvalidSystemProgram.into());
+    }
+    Ok(())
+}
+
+/// Checks that `account` is owned by `owner`
+pub fn check_account_owner(account: &AccountInfo, owner: &Pubkey) -> ProgramResult {
+    if account.owner != owner {
+        msg!("SOLR_ERROR_40: account is not owned by the expected program");
+        msg!(&account.key.to_string());
+        msg!(&account.owner.to_string());
+        return Err(TokenSaleError::InvalidAccountOwner.into());
+    }
+    Ok(())
+}
+
+/// Checks that no account is passed twice, so one account cannot stand in for another
+pub fn check_unique_accounts(accounts: &[&AccountInfo]) -> ProgramResult {
+    for (index, account) in accounts.iter().enumerate() {
+        if accounts[..index].iter().any(|other| other.key == account.key) {
+            msg!("SOLR_ERROR_47: account is passed more than once");
+            msg!(&account.key.to_string());
+            return Err(TokenSaleError::DuplicateAccount.into());
+        }
+    }
+    Ok(())
+}
+
+/// Unpacks an initialized token sale owned by the program, rejecting accounts that
+/// still need MigrateTokenSale
+pub fn unpack_token_sale(program_id: &Pubkey, token_sale_account: &AccountInfo) -> Result<TokenSale, ProgramError> {
+    check_account_owner(token_sale_account, program_id)?;
+    let version = TokenSale::version(&token_sale_account.data.borrow());
+    if version != 0 && version != TOKEN_SALE_VERSION {
+        msg!("SOLR_ERROR_30: token sale account needs to be migrated");
+        msg!("{}", version);
+        return Err(TokenSaleError::InvalidAccountVersion.into());
+    }
+    let token_sale_state = TokenSale::unpack_unchecked(&token_sale_account.data.borrow())?;
+    if !token_sale_state.is_initialized() {
+        msg!("SOLR_ERROR_3: token sale is not initialized");
+        return Err(TokenSaleError::TokenSaleNotInit.into());
+    }
+    Ok(token_sale_state)
+}
+
+/// Checks that `signer_account` holds `role` in the token sale
+pub fn check_role(token_sale_state: &TokenSale, role: SaleRole, signer_account: &AccountInfo) -> ProgramResult {
+    let role_pubkey = token_sale_state.role_pubkey(role);
+    if role_pubkey != *signer_account.key {
+        msg!("SOLR_ERROR_41: signer does not hold the {:?} role", role);
+        msg!(&role_pubkey.to_string());
+        msg!(&signer_account.key.to_string());
+        return Err(TokenSaleError::InvalidSigner.into());
+    }
+    Ok(())
+}
+
+/// Checks that `token_sale_solr_account` is the sale token account of the token sale
+pub fn check_sale_token_account(token_sale_state: &TokenSale, token_sale_solr_account: &AccountInfo) -> ProgramResult {
+    if token_sale_state.sale_token_account_pubkey != *token_sale_solr_account.key {
+        msg!("SOLR_ERROR_42: token sale account does not match");
+        msg!(&token_sale_state.sale_token_account_pubkey.to_string());
+        msg!(&token_sale_solr_account.key.to_string());
+        return Err(TokenSaleError::SaleTokenAccountMismatch.into());
+    }
+    Ok(())
+}
+
+/// Checks that `pool_usdt_account` is the pool token account of the token sale
+pub fn check_pool_token_account(token_sale_state: &TokenSale, pool_usdt_account: &AccountInfo) -> ProgramResult {
+    if token_sale_state.pool_token_account_pubkey != *pool_usdt_account.key {
+        msg!("SOLR_ERROR_43: pool usdt account does not match");
+        msg!(&token_sale_state.pool_token_account_pubkey.to_string());
+        msg!(&pool_usdt_account.key.to_string());
+        return Err(TokenSaleError::PoolTokenAccountMismatch.into());
+    }
+    Ok(())
+}
+
+/// Checks that `sale_pda` is the program derived address holding the token sale accounts
+pub fn check_sale_authority(
+    program_id: &Pubkey,
+    token_sale_account: &AccountInfo,
+    token_sale_state: &TokenSale,
+    sale_pda: &AccountInfo,
+) -> ProgramResult {
+    if *sale_pda.key != Processor::authority_id(program_id, token_sale_account.key, token_sale_state.bump_seed)? {
+        msg!("SOLR_ERROR_14: invalid token sale program derived address");
+        msg!(&sale_pda.key.to_string());
+        return Err(TokenSaleError::InvalidProgramAddress.into());
+    }
+    Ok(())
+}
+
+/// Unpacks a mint owned by the token program
+pub fn unpack_mint(mint_account: &AccountInfo) -> Result<Mint, ProgramError> {
+    check_account_owner(mint_account, &spl_token::id())?;
+    Mint::unpack(&mint_account.data.borrow())
+}
+
+/// Unpacks a token account owned by the token program
+pub fn unpack_token_account(token_account: &AccountInfo) -> Result<TokenAccount, ProgramError> {
+    check_account_owner(token_account, &spl_token::id())?;
+    TokenAccount::unpack(&token_account.data.borrow())
+}
+
+/// Unpacks a token account owned by the token program holding tokens of `mint`
+pub fn unpack_token_account_with_mint(token_account: &AccountInfo, mint: &Pubkey) -> Result<TokenAccount, ProgramError> {
+    let token_account_state = unpack_token_account(token_account)?;
+    if token_account_state.mint != *mint {
+        msg!("SOLR_ERROR_44: token account mint does not match");
+        msg!(&token_account.key.to_string());
+        msg!(&mint.to_string());
+        return Err(TokenSaleError::MintMismatch.into());
+    }
+    Ok(token_account_state)
+}
+
+/// Unpacks the purchase record of `user_account` in the token sale
+pub fn unpack_purchase_record(
+    program_id: &Pubkey,
+    user_purchase_record: &AccountInfo,
+    token_sale_account: &AccountInfo,
+    user_account: &AccountInfo,
+) -> Result<PurchaseRecord, ProgramError> {
+    check_account_owner(user_purchase_record, program_id)?;
+    let user_purchase_record_state = PurchaseRecord::unpack(&user_purchase_record.data.borrow())?;
+    if user_purchase_record_state.token_sale_pubkey != *token_sale_account.key ||
+        user_purchase_record_state.user_pubkey != *user_account.key {
+        msg!("SOLR_ERROR_45: user purchase record does not match");
+        msg!(&user_purchase_record_state.token_sale_pubkey.to_string());
+        msg!(&user_purchase_record_state.user_pubkey.to_string());
+        return Err(TokenSaleError::PurchaseRecordMismatch.into());
+    }
+    Ok(user_purchase_record_state)
+}
+
+/// Unpacks the payment config of the token sale
+pub fn unpack_payment_config(
+    program_id: &Pubkey,
+    payment_config_account: &AccountInfo,
+    token_sale_account: &AccountInfo,
+) -> Result<PaymentConfig, ProgramError> {
+    check_account_owner(payment_config_account, program_id)?;
+    let payment_config_state = PaymentConfig::unpack(&payment_config_account.data.borrow())?;
+    if payment_config_state.token_sale_pubkey != *token_sale_account.key {
+        msg!("SOLR_ERROR_14: invalid payment config program derived address");
+        msg!(&payment_config_account.key.to_string());
+        return Err(TokenSaleError::InvalidProgramAddress.into());
+    }
+    Ok(payment_config_state)
+}
+
+/// Unpacks a price account owned by the Pyth program
+pub fn unpack_price_account(oracle_account: &AccountInfo) -> Result<PriceAccount, ProgramError> {
+    if *oracle_account.owner != PYTH_PROGRAM_ID {
+        msg!("SOLR_ERROR_52: oracle price account is not owned by the Pyth program");
+        msg!(&oracle_account.key.to_string());
+        msg!(&oracle_account.owner.to_string());
+        return Err(TokenSaleError::InvalidOracle.into());
+    }
+    PriceAccount::unpack(&oracle_account.data.borrow()).map_err(|_| {
+        msg!("SOLR_ERROR_52: invalid oracle price account");
+        msg!(&oracle_account.key.to_string());
+        TokenSaleError::InvalidOracle.into()
+    })
+}
+
+/// Unpacks the price of the oracle of the token sale at `unix_timestamp`, rejecting
+/// prices that are not trading, too old or too uncertain
+pub fn unpack_oracle_price(
+    token_sale_state: &TokenSale,
+    oracle_account: &AccountInfo,
+    unix_timestamp: u64,
+) -> Result<PriceAccount, ProgramError> {
+    if token_sale_state.oracle_pubkey != *oracle_account.key {
+        msg!("SOLR_ERROR_52: oracle account does not match");
+        msg!(&token_sale_state.oracle_pubkey.to_string());
+        msg!(&oracle_account.key.to_string());
+        return Err(TokenSaleError::InvalidOracle.into());
+    }
+    let price_account = unpack_price_account(oracle_account)?;
+    if !price_account.is_trading() || price_account.price <= 0 {
+        msg!("SOLR_ERROR_52: oracle price is not trading");
+        msg!("{}", price_account.status);
+        msg!("{}", price_account.price);
+        return Err(TokenSaleError::InvalidOracle.into());
+    }
+    let age = unix_timestamp.saturating_sub(price_account.timestamp.max(0) as u64);
+    if age > token_sale_state.oracle_max_age {
+        msg!("SOLR_ERROR_53: oracle price is stale");
+        msg!("{}", age);
+        msg!("{}", token_sale_state.oracle_max_age);
+        return Err(TokenSaleError::StaleOraclePrice.into());
+    }
+    let confidence_bps = price_account.conf as u128 * 10_000 / price_account.price as u128;
+    if confidence_bps > token_sale_state.oracle_max_confidence_bps as u128 {
+        msg!("SOLR_ERROR_54: oracle confidence interval is too wide");
+        msg!("{}", confidence_bps);
+        msg!("{}", token_sale_state.oracle_max_confidence_bps);
+        return Err(TokenSaleError::OracleConfidenceTooWide.into());
+    }
+    Ok(price_account)
+}
+
+/// Unpacks the voucher of `user_account` verified by the Ed25519 program instruction
+/// right before the current instruction, rejecting vouchers not signed by the voucher
+/// signer of the token sale or expired at `unix_timestamp`
+pub fn unpack_voucher(
+    token_sale_state: &TokenSale,
+    token_sale_account: &AccountInfo,
+    user_account: &AccountInfo,
+    instructions_sysvar: &AccountInfo,
+    unix_timestamp: u64,
+) -> Result<Voucher, ProgramError> {
+    if !instructions::check_id(instructions_sysvar.key) {
+        msg!("SOLR_ERROR_55: invalid instructions sysvar");
+        msg!(&instructions_sysvar.key.to_string());
+        return Err(TokenSaleError::InvalidVoucher.into());
+    }
+    let current_index = load_current_index_checked(instructions_sysvar)? as usize;
+    let voucher_instruction = match current_index.checked_sub(1) {
+        Some(index) => load_instruction_at_checked(index, instructions_sysvar)?,
+        None => {
+            msg!("SOLR_ERROR_55: voucher is not verified by the previous instruction");
+            return Err(TokenSaleError::InvalidVoucher.into());
+        }
+    };
+    if !ed25519_program::check_id(&voucher_instruction.program_id) {
+        msg!("SOLR_ERROR_55: voucher is not verified by the previous instruction");
+        msg!(&voucher_instruction.program_id.to_string());
+        return Err(TokenSaleError::InvalidVoucher.into());
+    }
+    let (signer_pubkey, voucher) = Voucher::unpack_ed25519_instruction_data(&voucher_instruction.data)
+        .map_err(|_| {
+            msg!("SOLR_ERROR_55: invalid voucher");
+            TokenSaleError::InvalidVoucher
+        })?;
+    if signer_pubkey != token_sale_state.voucher_signer_pubkey {
+        msg!("SOLR_ERROR_55: voucher is not signed by the voucher signer");
+        msg!(&token_sale_state.voucher_signer_pubkey.to_string());
+        msg!(&signer_pubkey.to_string());
+        return Err(TokenSaleError::InvalidVoucher.into());
+    }
+    if voucher.token_sale_pubkey != *token_sale_account.key || voucher.user_pubkey != *user_account.key {
+        msg!("SOLR_ERROR_55: voucher does not match");
+        msg!(&voucher.token_sale_pubkey.to_string());
+        msg!(&voucher.user_pubkey.to_string());
+        return Err(TokenSaleError::InvalidVoucher.into());
+    }
+    if unix_timestamp >= voucher.expiry_time {
+        msg!("SOLR_ERROR_56: voucher has expired");
+        msg!("{}", voucher.expiry_time);
+        return Err(TokenSaleError::VoucherExpired.into());
+    }
+    Ok(voucher)
+}
+
+/// Returns the allocation of `user_account` proven by `merkle_proof` against the merkle
+/// root of the token sale
+pub fn check_merkle_proof(
+    token_sale_state: &TokenSale,
+    user_account: &AccountInfo,
+    merkle_proof: Option<&MerkleProof>,
+) -> Result<u64, ProgramError> {
+    let merkle_proof = merkle_proof.ok_or_else(|| {
+        msg!("SOLR_ERROR_57: merkle proof of the allocation is missing");
+        TokenSaleError::InvalidMerkleProof
+    })?;
+    if !merkle_proof.verify(&token_sale_state.merkle_root, user_account.key) {
+        msg!("SOLR_ERROR_57: invalid merkle proof");
+        msg!(&user_account.key.to_string());
+        msg!("{}", merkle_proof.allocation_amount);
+        return Err(TokenSaleError::InvalidMerkleProof.into());
+    }
+    Ok(merkle_proof.allocation_amount)
+}
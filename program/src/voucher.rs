@@ -0,0 +1,168 @@
+//! Allocation vouchers signed by the voucher signer of a sale. A voucher is verified by
+//! an Ed25519 program instruction placed right before the purchase in the transaction.
+
+use std::convert::TryInto;
+
+use solana_program::{
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey::Pubkey,
+};
+
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+
+/// Offset of the signature offsets in Ed25519 program instruction data
+const ED25519_OFFSETS_START: usize = 2;
+
+/// Length of the signature offsets in Ed25519 program instruction data
+const ED25519_OFFSETS_LEN: usize = 14;
+
+/// Instruction index referring to the Ed25519 program instruction itself
+const ED25519_CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+/// Usd allocation of a user in a token sale, usable until the expiry time
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Voucher {
+    pub token_sale_pubkey: Pubkey,
+    pub user_pubkey: Pubkey,
+    pub allocation_amount: u64,
+    pub expiry_time: u64,
+}
+
+impl Voucher {
+    /// Signer and voucher of Ed25519 program instruction data verifying a single
+    /// signature, with the signer and the voucher held in the instruction itself
+    pub fn unpack_ed25519_instruction_data(data: &[u8]) -> Result<(Pubkey, Voucher), ProgramError> {
+        if data.len() < ED25519_OFFSETS_START + ED25519_OFFSETS_LEN || data[0] != 1 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let offsets = array_ref![data, ED25519_OFFSETS_START, ED25519_OFFSETS_LEN];
+        let (
+            _signature_offset,
+            signature_instruction_index,
+            public_key_offset,
+            public_key_instruction_index,
+            message_data_offset,
+            message_data_size,
+            message_instruction_index,
+        ) = array_refs![offsets, 2, 2, 2, 2, 2, 2, 2];
+
+        if u16::from_le_bytes(*signature_instruction_index) != ED25519_CURRENT_INSTRUCTION ||
+            u16::from_le_bytes(*public_key_instruction_index) != ED25519_CURRENT_INSTRUCTION ||
+            u16::from_le_bytes(*message_instruction_index) != ED25519_CURRENT_INSTRUCTION ||
+            u16::from_le_bytes(*message_data_size) as usize != Voucher::LEN {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let public_key_offset = u16::from_le_bytes(*public_key_offset) as usize;
+        let signer_pubkey = data
+            .get(public_key_offset..public_key_offset + 32)
+            .and_then(|slice| slice.try_into().ok())
+            .map(Pubkey::new_from_array)
+            .ok_or(ProgramError::InvalidInstructionData)?;
+        let message_data_offset = u16::from_le_bytes(*message_data_offset) as usize;
+        let message = data
+            .get(message_data_offset..message_data_offset + Voucher::LEN)
+            .ok_or(ProgramError::InvalidInstructionData)?;
+        Ok((signer_pubkey, Voucher::unpack(message)?))
+    }
+}
+
+impl Sealed for Voucher {}
+
+impl IsInitialized for Voucher {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+}
+
+impl Pack for Voucher {
+    const LEN: usize = 80;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, Voucher::LEN];
+        let (
+            token_sale_pubkey,
+            user_pubkey,
+            allocation_amount,
+            expiry_time,
+        ) = array_refs![src, 32, 32, 8, 8];
+
+        Ok(Voucher {
+            token_sale_pubkey: Pubkey::new_from_array(*token_sale_pubkey),
+            user_pubkey: Pubkey::new_from_array(*user_pubkey),
+            allocation_amount: u64::from_le_bytes(*allocation_amount),
+            expiry_time: u64::from_le_bytes(*expiry_time),
+        })
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, Voucher::LEN];
+        let (
+            token_sale_pubkey_dst,
+            user_pubkey_dst,
+            allocation_amount_dst,
+            expiry_time_dst,
+        ) = mut_array_refs![dst, 32, 32, 8, 8];
+
+        token_sale_pubkey_dst.copy_from_slice(self.token_sale_pubkey.as_ref());
+        user_pubkey_dst.copy_from_slice(self.user_pubkey.as_ref());
+        *allocation_amount_dst = self.allocation_amount.to_le_bytes();
+        *expiry_time_dst = self.expiry_time.to_le_bytes();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ed25519_instruction_data(signer: &Pubkey, message: &[u8]) -> Vec<u8> {
+        // offsets as written by the Ed25519 program instruction builder of solana-sdk
+        let public_key_offset: u16 = 16;
+        let signature_offset: u16 = 48;
+        let message_data_offset: u16 = 112;
+        let mut data = vec![1, 0];
+        for value in [
+            signature_offset,
+            u16::MAX,
+            public_key_offset,
+            u16::MAX,
+            message_data_offset,
+            message.len() as u16,
+            u16::MAX,
+        ] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(signer.as_ref());
+        data.extend_from_slice(&[0; 64]);
+        data.extend_from_slice(message);
+        data
+    }
+
+    #[test]
+    fn test_unpack_ed25519_instruction_data() {
+        let signer = Pubkey::new_unique();
+        let voucher = Voucher {
+            token_sale_pubkey: Pubkey::new_unique(),
+            user_pubkey: Pubkey::new_unique(),
+            allocation_amount: 50_000_000,
+            expiry_time: 100_000,
+        };
+        let mut message = vec![0; Voucher::LEN];
+        Voucher::pack(voucher, &mut message).unwrap();
+
+        let data = ed25519_instruction_data(&signer, &message);
+        assert_eq!(Voucher::unpack_ed25519_instruction_data(&data), Ok((signer, voucher)));
+
+        // signatures over data held in another instruction are rejected
+        let mut other_instruction_data = data.clone();
+        other_instruction_data[14..16].copy_from_slice(&0u16.to_le_bytes());
+        assert_eq!(
+            Voucher::unpack_ed25519_instruction_data(&other_instruction_data),
+            Err(ProgramError::InvalidInstructionData),
+        );
+        let short_data = ed25519_instruction_data(&signer, &message[..Voucher::LEN - 8]);
+        assert_eq!(
+            Voucher::unpack_ed25519_instruction_data(&short_data),
+            Err(ProgramError::InvalidInstructionData),
+        );
+    }
+}
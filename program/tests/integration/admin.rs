@@ -0,0 +1,353 @@
+use crate::helpers::*;
+use solana_program::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solr_token_sale::{
+    error::TokenSaleError,
+    instruction,
+    state::{SaleRole, SaleStatus, PRICE_MULTIPLIER},
+};
+
+#[tokio::test]
+async fn test_pause_and_resume_token_sale() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let buyer = test.add_buyer(50 * ONE_USD, 100 * ONE_USD).await;
+    test.set_time(SALE_TIME).await;
+
+    test.pause().await.unwrap();
+    assert_eq!(test.token_sale_state().await.status, SaleStatus::Paused);
+    let result = test.execute(&buyer, 10 * ONE_USD).await;
+    assert_sale_error(result, TokenSaleError::TokenSalePaused);
+    let result = test.pause().await;
+    assert_sale_error(result, TokenSaleError::TokenSalePaused);
+
+    test.resume().await.unwrap();
+    assert_eq!(test.token_sale_state().await.status, SaleStatus::Funded);
+    test.execute(&buyer, 10 * ONE_USD).await.unwrap();
+    assert_eq!(test.token_sale_state().await.status, SaleStatus::Live);
+}
+
+#[tokio::test]
+async fn test_resume_token_sale_not_paused() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    test.set_time(SALE_TIME).await;
+
+    let result = test.resume().await;
+    assert_sale_error(result, TokenSaleError::TokenSaleNotPaused);
+}
+
+#[tokio::test]
+async fn test_pause_token_sale_not_funded() {
+    let mut test = TokenSaleTest::start().await;
+    test.init(&SaleParams::default()).await.unwrap();
+
+    let result = test.pause().await;
+    assert_sale_error(result, TokenSaleError::TokenSaleNotFunded);
+}
+
+#[tokio::test]
+async fn test_paused_token_sale_ends_on_time() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    test.pause().await.unwrap();
+
+    test.set_time(SALE_END_TIME).await;
+    let result = test.resume().await;
+    assert_sale_error(result, TokenSaleError::TokenSaleEnded);
+}
+
+#[tokio::test]
+async fn test_pause_token_sale_invalid_signer() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let buyer = test.add_buyer(50 * ONE_USD, 100 * ONE_USD).await;
+
+    let instruction =
+        instruction::pause_token_sale(&test.program_id, &buyer.pubkey(), &test.token_sale.pubkey());
+    let result = test.process(&[instruction], &[&buyer.keypair]).await;
+    assert_sale_error(result, TokenSaleError::InvalidSigner);
+}
+
+#[tokio::test]
+async fn test_end_token_sale() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let buyer = test.add_buyer(50 * ONE_USD, 100 * ONE_USD).await;
+    test.set_time(SALE_TIME).await;
+
+    test.end().await.unwrap();
+    assert_eq!(test.token_sale_state().await.status, SaleStatus::Ended);
+
+    let result = test.execute(&buyer, 10 * ONE_USD).await;
+    assert_sale_error(result, TokenSaleError::TokenSaleClosed);
+    let result = test.end().await;
+    assert_sale_error(result, TokenSaleError::TokenSaleEnded);
+    let result = test.pause().await;
+    assert_sale_error(result, TokenSaleError::TokenSaleEnded);
+    let result = test.resume().await;
+    assert_sale_error(result, TokenSaleError::TokenSaleEnded);
+}
+
+#[tokio::test]
+async fn test_withdraw_unsold_tokens() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let buyer = test.add_buyer(50 * ONE_USD, 100 * ONE_USD).await;
+    let owner_pubkey = test.owner.pubkey();
+    let destination = test.create_token_account(&test.sale_mint.clone(), &owner_pubkey).await;
+    test.set_time(SALE_TIME).await;
+    test.execute(&buyer, 10 * ONE_USD).await.unwrap();
+
+    let result = test.withdraw_unsold(&destination).await;
+    assert_sale_error(result, TokenSaleError::TokenSaleNotEnded);
+
+    test.set_time(SALE_END_TIME).await;
+    test.withdraw_unsold(&destination).await.unwrap();
+    assert_eq!(test.token_balance(&destination).await, 900 * ONE_TOKEN);
+    assert_eq!(test.token_sale_state().await.status, SaleStatus::Finalized);
+
+    let result = test.withdraw_unsold(&destination).await;
+    assert_sale_error(result, TokenSaleError::TokenSaleWithdrawn);
+}
+
+#[tokio::test]
+async fn test_update_sale_parameters() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let buyer = test.add_buyer(50 * ONE_USD, 100 * ONE_USD).await;
+
+    test.update_parameters(2 * ONE_USD, 20 * ONE_USD, PRICE_MULTIPLIER / 5, SALE_TIME + 1000)
+        .await
+        .unwrap();
+    let token_sale_state = test.token_sale_state().await;
+    assert_eq!(token_sale_state.usd_min_amount, 2 * ONE_USD);
+    assert_eq!(token_sale_state.usd_max_amount, 20 * ONE_USD);
+    assert_eq!(token_sale_state.token_sale_price, PRICE_MULTIPLIER / 5);
+    assert_eq!(token_sale_state.token_sale_time, SALE_TIME + 1000);
+    assert_eq!(token_sale_state.status, SaleStatus::Funded);
+
+    test.set_time(SALE_TIME).await;
+    let result = test.execute(&buyer, 10 * ONE_USD).await;
+    assert_sale_error(result, TokenSaleError::TokenSaleNotStarted);
+
+    test.set_time(SALE_TIME + 1000).await;
+    let result = test.execute(&buyer, 30 * ONE_USD).await;
+    assert_sale_error(result, TokenSaleError::AmountMaximum);
+    test.execute(&buyer, 10 * ONE_USD).await.unwrap();
+    assert_eq!(test.token_balance(&buyer.token_account).await, 50 * ONE_TOKEN);
+
+    let result = test.update_parameters(ONE_USD, 100 * ONE_USD, PRICE_MULTIPLIER / 10, SALE_TIME + 2000).await;
+    assert_sale_error(result, TokenSaleError::TokenSaleStarted);
+}
+
+#[tokio::test]
+async fn test_update_sale_parameters_before_funding() {
+    let mut test = TokenSaleTest::start().await;
+    test.init(&SaleParams::default()).await.unwrap();
+
+    test.update_parameters(ONE_USD, 100 * ONE_USD, PRICE_MULTIPLIER / 20, SALE_TIME).await.unwrap();
+    let token_sale_state = test.token_sale_state().await;
+    assert_eq!(token_sale_state.token_sale_price, PRICE_MULTIPLIER / 20);
+    assert_eq!(token_sale_state.status, SaleStatus::Initialized);
+}
+
+#[tokio::test]
+async fn test_update_sale_parameters_inconsistent() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+
+    let result = test.update_parameters(100 * ONE_USD, ONE_USD, PRICE_MULTIPLIER / 10, SALE_TIME).await;
+    assert_sale_error(result, TokenSaleError::InvalidPurchaseLimits);
+    let result = test.update_parameters(ONE_USD, 100 * ONE_USD, 0, SALE_TIME).await;
+    assert_sale_error(result, TokenSaleError::InvalidTokenSalePrice);
+    let result = test.update_parameters(ONE_USD, 100 * ONE_USD, PRICE_MULTIPLIER / 10, SALE_TIME - 2).await;
+    assert_sale_error(result, TokenSaleError::InvalidTokenSaleTime);
+    let result = test.update_parameters(ONE_USD, 100 * ONE_USD, PRICE_MULTIPLIER / 10, SALE_END_TIME).await;
+    assert_sale_error(result, TokenSaleError::InvalidTokenSaleTime);
+}
+
+#[tokio::test]
+async fn test_update_sale_parameters_invalid_signer() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let buyer = test.add_buyer(50 * ONE_USD, 100 * ONE_USD).await;
+
+    let instruction = instruction::update_sale_parameters(
+        &test.program_id,
+        &buyer.pubkey(),
+        &test.token_sale.pubkey(),
+        ONE_USD,
+        100 * ONE_USD,
+        PRICE_MULTIPLIER,
+        SALE_TIME,
+    );
+    let result = test.process(&[instruction], &[&buyer.keypair]).await;
+    assert_sale_error(result, TokenSaleError::InvalidSigner);
+}
+
+#[tokio::test]
+async fn test_admin_handover() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let new_admin = Keypair::new();
+
+    test.propose_admin(&new_admin.pubkey()).await.unwrap();
+    let token_sale_state = test.token_sale_state().await;
+    assert_eq!(token_sale_state.init_pubkey, test.owner.pubkey());
+    assert_eq!(token_sale_state.pending_admin_pubkey, new_admin.pubkey());
+
+    test.accept_admin(&new_admin).await.unwrap();
+    let token_sale_state = test.token_sale_state().await;
+    assert_eq!(token_sale_state.init_pubkey, new_admin.pubkey());
+    assert_eq!(token_sale_state.pending_admin_pubkey, Pubkey::default());
+    assert_eq!(token_sale_state.guardian_pubkey, new_admin.pubkey());
+    assert_eq!(token_sale_state.operator_pubkey, new_admin.pubkey());
+
+    // the previous admin keeps none of its roles
+    let result = test.set_role(SaleRole::Guardian, &test.owner.pubkey()).await;
+    assert_sale_error(result, TokenSaleError::InvalidSigner);
+    let result = test.pause().await;
+    assert_sale_error(result, TokenSaleError::InvalidSigner);
+    let result = test.update_parameters(2 * ONE_USD, 20 * ONE_USD, PRICE_MULTIPLIER / 5, SALE_TIME).await;
+    assert_sale_error(result, TokenSaleError::InvalidSigner);
+
+    let guardian = Keypair::new();
+    let instruction = instruction::set_sale_role(
+        &test.program_id,
+        &new_admin.pubkey(),
+        &test.token_sale.pubkey(),
+        SaleRole::Guardian,
+        &guardian.pubkey(),
+    );
+    test.process(&[instruction], &[&new_admin]).await.unwrap();
+    assert_eq!(test.token_sale_state().await.guardian_pubkey, guardian.pubkey());
+}
+
+#[tokio::test]
+async fn test_admin_handover_keeps_delegated_roles() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let new_admin = Keypair::new();
+    let operator = Keypair::new();
+
+    test.set_role(SaleRole::Operator, &operator.pubkey()).await.unwrap();
+    test.propose_admin(&new_admin.pubkey()).await.unwrap();
+    test.accept_admin(&new_admin).await.unwrap();
+    let token_sale_state = test.token_sale_state().await;
+    assert_eq!(token_sale_state.guardian_pubkey, new_admin.pubkey());
+    assert_eq!(token_sale_state.operator_pubkey, operator.pubkey());
+}
+
+#[tokio::test]
+async fn test_accept_admin_not_proposed() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let new_admin = Keypair::new();
+    let other = Keypair::new();
+
+    let result = test.accept_admin(&new_admin).await;
+    assert_sale_error(result, TokenSaleError::NoPendingAdmin);
+
+    test.propose_admin(&new_admin.pubkey()).await.unwrap();
+    let result = test.accept_admin(&other).await;
+    assert_sale_error(result, TokenSaleError::InvalidSigner);
+
+    test.propose_admin(&Pubkey::default()).await.unwrap();
+    let result = test.accept_admin(&new_admin).await;
+    assert_sale_error(result, TokenSaleError::NoPendingAdmin);
+    assert_eq!(test.token_sale_state().await.init_pubkey, test.owner.pubkey());
+}
+
+#[tokio::test]
+async fn test_propose_admin_invalid_signer() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let buyer = test.add_buyer(50 * ONE_USD, 100 * ONE_USD).await;
+
+    let instruction = instruction::propose_admin(
+        &test.program_id,
+        &buyer.pubkey(),
+        &test.token_sale.pubkey(),
+        &buyer.pubkey(),
+    );
+    let result = test.process(&[instruction], &[&buyer.keypair]).await;
+    assert_sale_error(result, TokenSaleError::InvalidSigner);
+}
+
+#[tokio::test]
+async fn test_sale_roles() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let guardian = Keypair::new();
+    let operator = Keypair::new();
+    let owner_pubkey = test.owner.pubkey();
+    let destination = test.create_token_account(&test.sale_mint.clone(), &owner_pubkey).await;
+    test.set_time(SALE_TIME).await;
+
+    test.set_role(SaleRole::Guardian, &guardian.pubkey()).await.unwrap();
+    test.set_role(SaleRole::Operator, &operator.pubkey()).await.unwrap();
+    let token_sale_state = test.token_sale_state().await;
+    assert_eq!(token_sale_state.guardian_pubkey, guardian.pubkey());
+    assert_eq!(token_sale_state.operator_pubkey, operator.pubkey());
+
+    // only the guardian pauses
+    let result = test.pause().await;
+    assert_sale_error(result, TokenSaleError::InvalidSigner);
+    let instruction =
+        instruction::pause_token_sale(&test.program_id, &operator.pubkey(), &test.token_sale.pubkey());
+    let result = test.process(&[instruction], &[&operator]).await;
+    assert_sale_error(result, TokenSaleError::InvalidSigner);
+    let instruction =
+        instruction::pause_token_sale(&test.program_id, &guardian.pubkey(), &test.token_sale.pubkey());
+    test.process(&[instruction], &[&guardian]).await.unwrap();
+
+    // only the operator resumes and ends
+    let instruction =
+        instruction::resume_token_sale(&test.program_id, &guardian.pubkey(), &test.token_sale.pubkey());
+    let result = test.process(&[instruction], &[&guardian]).await;
+    assert_sale_error(result, TokenSaleError::InvalidSigner);
+    let result = test.resume().await;
+    assert_sale_error(result, TokenSaleError::InvalidSigner);
+    let instruction =
+        instruction::resume_token_sale(&test.program_id, &operator.pubkey(), &test.token_sale.pubkey());
+    test.process(&[instruction], &[&operator]).await.unwrap();
+    let instruction =
+        instruction::end_token_sale(&test.program_id, &operator.pubkey(), &test.token_sale.pubkey());
+    test.process(&[instruction], &[&operator]).await.unwrap();
+
+    // only the admin withdraws
+    let instruction = instruction::withdraw_unsold_tokens(
+        &test.program_id,
+        &operator.pubkey(),
+        &test.token_sale.pubkey(),
+        &test.sale_token_account,
+        &destination,
+    );
+    let result = test.process(&[instruction], &[&operator]).await;
+    assert_sale_error(result, TokenSaleError::InvalidSigner);
+    test.withdraw_unsold(&destination).await.unwrap();
+    assert_eq!(test.token_balance(&destination).await, 1000 * ONE_TOKEN);
+}
+
+#[tokio::test]
+async fn test_set_sale_role_admin_only() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let operator = Keypair::new();
+    test.set_role(SaleRole::Operator, &operator.pubkey()).await.unwrap();
+
+    let instruction = instruction::set_sale_role(
+        &test.program_id,
+        &operator.pubkey(),
+        &test.token_sale.pubkey(),
+        SaleRole::Guardian,
+        &operator.pubkey(),
+    );
+    let result = test.process(&[instruction], &[&operator]).await;
+    assert_sale_error(result, TokenSaleError::InvalidSigner);
+
+    let result = test.set_role(SaleRole::Admin, &operator.pubkey()).await;
+    assert_sale_error(result, TokenSaleError::InvalidSaleRole);
+    assert_eq!(test.token_sale_state().await.init_pubkey, test.owner.pubkey());
+}
@@ -0,0 +1,51 @@
+use crate::helpers::*;
+use solr_token_sale::{
+    error::TokenSaleError,
+    state::{PricingMode, PRICE_MULTIPLIER},
+};
+
+fn auction_params() -> SaleParams {
+    SaleParams {
+        pricing_mode: PricingMode::DutchAuction,
+        auction_start_price: PRICE_MULTIPLIER / 2,
+        auction_floor_price: PRICE_MULTIPLIER / 5,
+        auction_decay_amount: PRICE_MULTIPLIER / 20,
+        auction_decay_interval: 60,
+        ..SaleParams::default()
+    }
+}
+
+#[tokio::test]
+async fn test_dutch_auction_price_decays() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&auction_params()).await;
+    let buyer = test.add_buyer(50 * ONE_USD, 100 * ONE_USD).await;
+
+    // 0.5 usd per token at go-live
+    test.set_time(SALE_TIME).await;
+    test.execute(&buyer, 10 * ONE_USD).await.unwrap();
+    assert_eq!(test.token_balance(&buyer.token_account).await, 20 * ONE_TOKEN);
+
+    // 0.4 usd per token after two decay intervals
+    test.set_time(SALE_TIME + 120).await;
+    test.execute(&buyer, 10 * ONE_USD).await.unwrap();
+    assert_eq!(test.token_balance(&buyer.token_account).await, 45 * ONE_TOKEN);
+
+    // 0.2 usd per token once the floor is reached
+    test.set_time(SALE_TIME + 6000).await;
+    test.execute(&buyer, 10 * ONE_USD).await.unwrap();
+    assert_eq!(test.token_balance(&buyer.token_account).await, 95 * ONE_TOKEN);
+}
+
+#[tokio::test]
+async fn test_dutch_auction_price_exceeds_maximum() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&auction_params()).await;
+    let buyer = test.add_buyer(50 * ONE_USD, 100 * ONE_USD).await;
+    test.set_time(SALE_TIME + 60).await;
+
+    let result = test.execute_with_max_price(&buyer, 10 * ONE_USD, PRICE_MULTIPLIER * 2 / 5).await;
+    assert_sale_error(result, TokenSaleError::PriceExceedsMaximum);
+
+    test.execute_with_max_price(&buyer, 10 * ONE_USD, PRICE_MULTIPLIER * 9 / 20).await.unwrap();
+}
@@ -0,0 +1,68 @@
+use crate::helpers::*;
+use solana_sdk::signature::Signer;
+use solr_token_sale::{error::TokenSaleError, instruction, state::SaleStatus};
+
+#[tokio::test]
+async fn test_cancel_token_sale() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let buyer = test.add_buyer(50 * ONE_USD, 100 * ONE_USD).await;
+    let owner_pubkey = test.owner.pubkey();
+    let destination = test.create_token_account(&test.sale_mint.clone(), &owner_pubkey).await;
+
+    test.cancel(&destination).await.unwrap();
+    assert_eq!(test.token_balance(&destination).await, 1000 * ONE_TOKEN);
+    assert_eq!(test.token_balance(&test.sale_token_account.clone()).await, 0);
+    assert_eq!(test.token_sale_state().await.status, SaleStatus::Cancelled);
+
+    test.set_time(SALE_TIME).await;
+    let result = test.execute(&buyer, 10 * ONE_USD).await;
+    assert_sale_error(result, TokenSaleError::TokenSaleCancelled);
+    let result = test.resume().await;
+    assert_sale_error(result, TokenSaleError::TokenSaleCancelled);
+    let result = test.cancel(&destination).await;
+    assert_sale_error(result, TokenSaleError::TokenSaleCancelled);
+}
+
+#[tokio::test]
+async fn test_cancel_token_sale_not_funded() {
+    let mut test = TokenSaleTest::start().await;
+    test.init(&SaleParams::default()).await.unwrap();
+    let owner_pubkey = test.owner.pubkey();
+    let destination = test.create_token_account(&test.sale_mint.clone(), &owner_pubkey).await;
+
+    test.cancel(&destination).await.unwrap();
+    assert_eq!(test.token_sale_state().await.status, SaleStatus::Cancelled);
+    let result = test.fund(1000 * ONE_TOKEN).await;
+    assert_sale_error(result, TokenSaleError::TokenSaleCancelled);
+}
+
+#[tokio::test]
+async fn test_cancel_token_sale_started() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let owner_pubkey = test.owner.pubkey();
+    let destination = test.create_token_account(&test.sale_mint.clone(), &owner_pubkey).await;
+    test.set_time(SALE_TIME).await;
+
+    let result = test.cancel(&destination).await;
+    assert_sale_error(result, TokenSaleError::TokenSaleStarted);
+    assert_eq!(test.token_balance(&destination).await, 0);
+}
+
+#[tokio::test]
+async fn test_cancel_token_sale_invalid_signer() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let buyer = test.add_buyer(50 * ONE_USD, 100 * ONE_USD).await;
+
+    let instruction = instruction::cancel_token_sale(
+        &test.program_id,
+        &buyer.pubkey(),
+        &test.token_sale.pubkey(),
+        &test.sale_token_account,
+        &buyer.token_account,
+    );
+    let result = test.process(&[instruction], &[&buyer.keypair]).await;
+    assert_sale_error(result, TokenSaleError::InvalidSigner);
+}
@@ -0,0 +1,83 @@
+use crate::helpers::*;
+use solana_sdk::signature::Signer;
+use solr_token_sale::error::TokenSaleError;
+
+#[tokio::test]
+async fn test_close_token_sale() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let buyer = test.add_buyer(50 * ONE_USD, 100 * ONE_USD).await;
+    let owner_pubkey = test.owner.pubkey();
+    let destination = test.create_token_account(&test.sale_mint.clone(), &owner_pubkey).await;
+    test.set_time(SALE_TIME).await;
+    test.execute(&buyer, 10 * ONE_USD).await.unwrap();
+
+    test.set_time(SALE_END_TIME).await;
+    let result = test.close().await;
+    assert_sale_error(result, TokenSaleError::TokenAccountNotEmpty);
+
+    test.withdraw_unsold(&destination).await.unwrap();
+    let owner_lamports = test.get_account(&owner_pubkey).await.lamports;
+    let token_sale_lamports = test.get_account(&test.token_sale.pubkey()).await.lamports;
+    let sale_token_lamports = test.get_account(&test.sale_token_account.clone()).await.lamports;
+    test.close().await.unwrap();
+
+    assert!(!test.account_exists(&test.token_sale.pubkey()).await);
+    assert!(!test.account_exists(&test.sale_token_account.clone()).await);
+    // the pool account is not held in escrow and stays with the owner
+    assert!(test.account_exists(&test.pool_usd_account.clone()).await);
+    assert_eq!(
+        test.get_account(&owner_pubkey).await.lamports,
+        owner_lamports + token_sale_lamports + sale_token_lamports,
+    );
+}
+
+#[tokio::test]
+async fn test_close_token_sale_not_ended() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    test.set_time(SALE_TIME).await;
+
+    let result = test.close().await;
+    assert_sale_error(result, TokenSaleError::TokenSaleNotEnded);
+}
+
+#[tokio::test]
+async fn test_close_token_sale_with_escrow() {
+    let mut test = TokenSaleTest::start().await;
+    let params = SaleParams {
+        soft_cap_amount: 50 * ONE_USD,
+        ..SaleParams::default()
+    };
+    test.init_and_fund(&params).await;
+    let buyer = test.add_buyer(60 * ONE_USD, 100 * ONE_USD).await;
+    let owner_pubkey = test.owner.pubkey();
+    let sale_destination = test.create_token_account(&test.sale_mint.clone(), &owner_pubkey).await;
+    let usd_destination = test.create_token_account(&test.usd_mint.clone(), &owner_pubkey).await;
+    test.set_time(SALE_TIME).await;
+    test.execute(&buyer, 60 * ONE_USD).await.unwrap();
+
+    test.set_time(SALE_END_TIME).await;
+    test.withdraw_unsold(&sale_destination).await.unwrap();
+    let result = test.close().await;
+    assert_sale_error(result, TokenSaleError::TokenAccountNotEmpty);
+
+    test.withdraw_proceeds(&usd_destination).await.unwrap();
+    test.close().await.unwrap();
+    assert!(!test.account_exists(&test.token_sale.pubkey()).await);
+    assert!(!test.account_exists(&test.sale_token_account.clone()).await);
+    assert!(!test.account_exists(&test.pool_usd_account.clone()).await);
+}
+
+#[tokio::test]
+async fn test_close_cancelled_token_sale() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let owner_pubkey = test.owner.pubkey();
+    let destination = test.create_token_account(&test.sale_mint.clone(), &owner_pubkey).await;
+
+    test.cancel(&destination).await.unwrap();
+    test.close().await.unwrap();
+    assert!(!test.account_exists(&test.token_sale.pubkey()).await);
+    assert_eq!(test.token_balance(&destination).await, 1000 * ONE_TOKEN);
+}
@@ -0,0 +1,51 @@
+use crate::helpers::*;
+use solana_program::{program_pack::Pack, system_instruction};
+use solana_sdk::signature::{Keypair, Signer};
+use solr_token_sale::{error::TokenSaleError, state::{SaleStatus, TokenSale}};
+
+#[tokio::test]
+async fn test_fund_token_sale() {
+    let mut test = TokenSaleTest::start().await;
+    let params = SaleParams::default();
+    test.init(&params).await.unwrap();
+    assert_eq!(test.token_sale_state().await.status, SaleStatus::Initialized);
+    test.fund(params.token_sale_amount).await.unwrap();
+    assert_eq!(test.token_balance(&test.sale_token_account.clone()).await, params.token_sale_amount);
+    assert_eq!(test.token_sale_state().await.status, SaleStatus::Funded);
+}
+
+#[tokio::test]
+async fn test_fund_token_sale_wrong_amount() {
+    let mut test = TokenSaleTest::start().await;
+    let params = SaleParams::default();
+    test.init(&params).await.unwrap();
+    let result = test.fund(params.token_sale_amount + 1).await;
+    assert_sale_error(result, TokenSaleError::TokenSaleAmountExceeds);
+}
+
+#[tokio::test]
+async fn test_fund_token_sale_twice() {
+    let mut test = TokenSaleTest::start().await;
+    let params = SaleParams::default();
+    test.init_and_fund(&params).await;
+    let result = test.fund(params.token_sale_amount).await;
+    assert_sale_error(result, TokenSaleError::TokenSaleFunded);
+}
+
+#[tokio::test]
+async fn test_fund_token_sale_not_initialized() {
+    let mut test = TokenSaleTest::start().await;
+    let rent = test.context.banks_client.get_rent().await.unwrap();
+    let create_account = system_instruction::create_account(
+        &test.context.payer.pubkey(),
+        &test.token_sale.pubkey(),
+        rent.minimum_balance(TokenSale::LEN),
+        TokenSale::LEN as u64,
+        &test.program_id,
+    );
+    let token_sale = Keypair::from_bytes(&test.token_sale.to_bytes()).unwrap();
+    test.process(&[create_account], &[&token_sale]).await.unwrap();
+
+    let result = test.fund(SaleParams::default().token_sale_amount).await;
+    assert_sale_error(result, TokenSaleError::TokenSaleNotInit);
+}
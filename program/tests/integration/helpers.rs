@@ -0,0 +1,891 @@
+use crate::mock_whitelist;
+use solana_program::{
+    clock::Clock, instruction::Instruction, program_pack::Pack, pubkey::Pubkey, system_instruction,
+};
+use solana_program_test::{processor, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::{Account, AccountSharedData},
+    ed25519_instruction,
+    instruction::InstructionError,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use solr_token_sale::{
+    error::TokenSaleError,
+    instruction::{self, TokenSaleInstruction},
+    merkle::MerkleProof,
+    oracle::{PriceAccount, PYTH_PROGRAM_ID},
+    processor::Processor,
+    state::{PricingMode, PurchaseRecord, SaleMode, SaleRole, TokenSale, TokenSaleV1, PRICE_MULTIPLIER},
+    voucher::Voucher,
+};
+
+pub const SALE_TIME: u64 = 100_000;
+pub const SALE_END_TIME: u64 = 200_000;
+pub const DECIMALS: u8 = 6;
+pub const ONE_TOKEN: u64 = 1_000_000;
+pub const ONE_USD: u64 = 1_000_000;
+
+/// Parameters of InitTokenSale, defaulting to a first come first served sale of
+/// 1000 tokens at 0.1 usd without vesting or soft cap
+#[derive(Clone)]
+pub struct SaleParams {
+    pub token_sale_amount: u64,
+    pub usd_min_amount: u64,
+    pub usd_max_amount: u64,
+    pub token_sale_price: u64,
+    pub token_sale_time: u64,
+    pub token_sale_end_time: u64,
+    pub vesting_tge_percent: u8,
+    pub vesting_start_time: u64,
+    pub vesting_cliff_time: u64,
+    pub vesting_end_time: u64,
+    pub soft_cap_amount: u64,
+    pub sale_mode: SaleMode,
+    pub pricing_mode: PricingMode,
+    pub auction_start_price: u64,
+    pub auction_floor_price: u64,
+    pub auction_decay_amount: u64,
+    pub auction_decay_interval: u64,
+}
+
+impl Default for SaleParams {
+    fn default() -> Self {
+        SaleParams {
+            token_sale_amount: 1000 * ONE_TOKEN,
+            usd_min_amount: ONE_USD,
+            usd_max_amount: 100 * ONE_USD,
+            token_sale_price: PRICE_MULTIPLIER / 10,
+            token_sale_time: SALE_TIME,
+            token_sale_end_time: SALE_END_TIME,
+            vesting_tge_percent: 100,
+            vesting_start_time: 0,
+            vesting_cliff_time: 0,
+            vesting_end_time: 0,
+            soft_cap_amount: 0,
+            sale_mode: SaleMode::FirstComeFirstServed,
+            pricing_mode: PricingMode::Fixed,
+            auction_start_price: 0,
+            auction_floor_price: 0,
+            auction_decay_amount: 0,
+            auction_decay_interval: 0,
+        }
+    }
+}
+
+pub struct Buyer {
+    pub keypair: Keypair,
+    pub usd_account: Pubkey,
+    pub token_account: Pubkey,
+}
+
+impl Buyer {
+    pub fn pubkey(&self) -> Pubkey {
+        self.keypair.pubkey()
+    }
+}
+
+pub struct TokenSaleTest {
+    pub context: ProgramTestContext,
+    pub program_id: Pubkey,
+    pub whitelist_program_id: Pubkey,
+    pub owner: Keypair,
+    pub mint_authority: Keypair,
+    pub token_sale: Keypair,
+    pub sale_mint: Pubkey,
+    pub usd_mint: Pubkey,
+    pub owner_token_account: Pubkey,
+    pub sale_token_account: Pubkey,
+    pub pool_usd_account: Pubkey,
+    pub whitelist_map: Pubkey,
+    pub whitelist_account: Pubkey,
+}
+
+impl TokenSaleTest {
+    pub async fn start() -> Self {
+        Self::start_with_decimals(DECIMALS, DECIMALS).await
+    }
+
+    /// Starts a test validator with both mints, the owner token accounts and an
+    /// empty whitelist. The token sale itself is not initialized.
+    pub async fn start_with_decimals(sale_decimals: u8, usd_decimals: u8) -> Self {
+        let program_id = Pubkey::new_unique();
+        let whitelist_program_id = Pubkey::new_unique();
+        let mut program_test = ProgramTest::new(
+            "solr_token_sale",
+            program_id,
+            processor!(Processor::process),
+        );
+        program_test.add_program(
+            "mock_whitelist",
+            whitelist_program_id,
+            processor!(mock_whitelist::process_instruction),
+        );
+        program_test.prefer_bpf(false);
+
+        let owner = Keypair::new();
+        let whitelist_map = Pubkey::new_unique();
+        let whitelist_account = Pubkey::new_unique();
+        program_test.add_account(
+            whitelist_map,
+            Account {
+                lamports: 1_000_000_000,
+                data: mock_whitelist::whitelist_data(&owner.pubkey(), &[(whitelist_account, 0)]),
+                owner: whitelist_program_id,
+                ..Account::default()
+            },
+        );
+        program_test.add_account(
+            whitelist_account,
+            Account {
+                lamports: 1_000_000_000,
+                data: mock_whitelist::whitelist_data(&owner.pubkey(), &[]),
+                owner: whitelist_program_id,
+                ..Account::default()
+            },
+        );
+
+        let context = program_test.start_with_context().await;
+        let mut test = TokenSaleTest {
+            context,
+            program_id,
+            whitelist_program_id,
+            owner,
+            mint_authority: Keypair::new(),
+            token_sale: Keypair::new(),
+            sale_mint: Pubkey::default(),
+            usd_mint: Pubkey::default(),
+            owner_token_account: Pubkey::default(),
+            sale_token_account: Pubkey::default(),
+            pool_usd_account: Pubkey::default(),
+            whitelist_map,
+            whitelist_account,
+        };
+        test.airdrop(&test.owner.pubkey(), 10_000_000_000).await;
+
+        test.sale_mint = test.create_mint(sale_decimals).await;
+        test.usd_mint = test.create_mint(usd_decimals).await;
+        let owner_pubkey = test.owner.pubkey();
+        test.owner_token_account = test.create_token_account(&test.sale_mint.clone(), &owner_pubkey).await;
+        test.sale_token_account = test.create_token_account(&test.sale_mint.clone(), &owner_pubkey).await;
+        test.pool_usd_account = test.create_token_account(&test.usd_mint.clone(), &owner_pubkey).await;
+        test.mint_to(&test.sale_mint.clone(), &test.owner_token_account.clone(), 1_000_000 * ONE_TOKEN).await;
+        test.set_time(SALE_TIME - 1).await;
+        test
+    }
+
+    /// Sends `instructions` in one transaction paid by the payer and signed by `signers`
+    pub async fn process(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<(), TransactionError> {
+        let recent_blockhash = self.context.get_new_latest_blockhash().await.unwrap();
+        let mut all_signers = vec![&self.context.payer];
+        all_signers.extend_from_slice(signers);
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.context.payer.pubkey()),
+            &all_signers,
+            recent_blockhash,
+        );
+        self.context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .map_err(|error| error.unwrap())
+    }
+
+    pub async fn airdrop(&mut self, to: &Pubkey, lamports: u64) {
+        let instruction = system_instruction::transfer(&self.context.payer.pubkey(), to, lamports);
+        self.process(&[instruction], &[]).await.unwrap();
+    }
+
+    pub async fn set_time(&mut self, unix_timestamp: u64) {
+        let mut clock: Clock = self.context.banks_client.get_sysvar().await.unwrap();
+        clock.unix_timestamp = unix_timestamp as i64;
+        self.context.set_sysvar(&clock);
+    }
+
+    pub async fn create_mint(&mut self, decimals: u8) -> Pubkey {
+        let mint = Keypair::new();
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+        let instructions = [
+            system_instruction::create_account(
+                &self.context.payer.pubkey(),
+                &mint.pubkey(),
+                rent.minimum_balance(spl_token::state::Mint::LEN),
+                spl_token::state::Mint::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_mint(
+                &spl_token::id(),
+                &mint.pubkey(),
+                &self.mint_authority.pubkey(),
+                None,
+                decimals,
+            )
+            .unwrap(),
+        ];
+        self.process(&instructions, &[&mint]).await.unwrap();
+        mint.pubkey()
+    }
+
+    pub async fn create_token_account(&mut self, mint: &Pubkey, owner: &Pubkey) -> Pubkey {
+        let account = Keypair::new();
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+        let instructions = [
+            system_instruction::create_account(
+                &self.context.payer.pubkey(),
+                &account.pubkey(),
+                rent.minimum_balance(spl_token::state::Account::LEN),
+                spl_token::state::Account::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_account(&spl_token::id(), &account.pubkey(), mint, owner)
+                .unwrap(),
+        ];
+        self.process(&instructions, &[&account]).await.unwrap();
+        account.pubkey()
+    }
+
+    pub async fn mint_to(&mut self, mint: &Pubkey, account: &Pubkey, amount: u64) {
+        let instruction = spl_token::instruction::mint_to(
+            &spl_token::id(),
+            mint,
+            account,
+            &self.mint_authority.pubkey(),
+            &[],
+            amount,
+        )
+        .unwrap();
+        let mint_authority = Keypair::from_bytes(&self.mint_authority.to_bytes()).unwrap();
+        self.process(&[instruction], &[&mint_authority]).await.unwrap();
+    }
+
+    /// Creates a funded buyer holding `usd_balance` with `allocation` in the whitelist
+    pub async fn add_buyer(&mut self, allocation: u64, usd_balance: u64) -> Buyer {
+        let buyer = self.add_unlisted_buyer(usd_balance).await;
+        self.set_allocation(&buyer.pubkey(), allocation).await;
+        buyer
+    }
+
+    /// Creates a funded buyer who is not in the whitelist
+    pub async fn add_unlisted_buyer(&mut self, usd_balance: u64) -> Buyer {
+        let keypair = Keypair::new();
+        self.airdrop(&keypair.pubkey(), 1_000_000_000).await;
+        let usd_account = self.create_token_account(&self.usd_mint.clone(), &keypair.pubkey()).await;
+        let token_account = self.create_token_account(&self.sale_mint.clone(), &keypair.pubkey()).await;
+        self.mint_to(&self.usd_mint.clone(), &usd_account, usd_balance).await;
+        Buyer {
+            keypair,
+            usd_account,
+            token_account,
+        }
+    }
+
+    pub async fn set_allocation(&mut self, user: &Pubkey, allocation: u64) {
+        let instruction = mock_whitelist::set_allocation(
+            &self.whitelist_program_id,
+            &self.whitelist_account,
+            user,
+            allocation,
+        );
+        self.process(&[instruction], &[]).await.unwrap();
+    }
+
+    pub fn init_instruction(&self, params: &SaleParams) -> Instruction {
+        instruction::init_token_sale(
+            &self.program_id,
+            &self.owner.pubkey(),
+            &self.token_sale.pubkey(),
+            &self.pool_usd_account,
+            &self.sale_token_account,
+            &self.whitelist_map,
+            &self.whitelist_program_id,
+            &self.sale_mint,
+            &self.usd_mint,
+            params.token_sale_amount,
+            params.usd_min_amount,
+            params.usd_max_amount,
+            params.token_sale_price,
+            params.token_sale_time,
+            params.token_sale_end_time,
+            params.vesting_tge_percent,
+            params.vesting_start_time,
+            params.vesting_cliff_time,
+            params.vesting_end_time,
+            params.soft_cap_amount,
+            params.sale_mode,
+            params.pricing_mode,
+            params.auction_start_price,
+            params.auction_floor_price,
+            params.auction_decay_amount,
+            params.auction_decay_interval,
+        )
+    }
+
+    /// Creates the token sale account with `lamports` and initializes the sale
+    pub async fn init_with_lamports(
+        &mut self,
+        params: &SaleParams,
+        lamports: u64,
+    ) -> Result<(), TransactionError> {
+        let instructions = [
+            system_instruction::create_account(
+                &self.context.payer.pubkey(),
+                &self.token_sale.pubkey(),
+                lamports,
+                TokenSale::LEN as u64,
+                &self.program_id,
+            ),
+            self.init_instruction(params),
+        ];
+        let owner = self.owner();
+        let token_sale = Keypair::from_bytes(&self.token_sale.to_bytes()).unwrap();
+        self.process(&instructions, &[&owner, &token_sale]).await
+    }
+
+    pub async fn init(&mut self, params: &SaleParams) -> Result<(), TransactionError> {
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+        self.init_with_lamports(params, rent.minimum_balance(TokenSale::LEN)).await
+    }
+
+    /// Initializes and funds a sale with `params`
+    pub async fn init_and_fund(&mut self, params: &SaleParams) {
+        self.init(params).await.unwrap();
+        self.fund(params.token_sale_amount).await.unwrap();
+    }
+
+    pub async fn fund(&mut self, amount: u64) -> Result<(), TransactionError> {
+        let instruction = instruction::fund_token_sale(
+            &self.program_id,
+            &self.owner.pubkey(),
+            &self.token_sale.pubkey(),
+            &self.owner_token_account,
+            &self.sale_token_account,
+            amount,
+        );
+        let owner = self.owner();
+        self.process(&[instruction], &[&owner]).await
+    }
+
+    pub fn execute_instruction(&self, buyer: &Buyer, usd_amount: u64, max_price: u64) -> Instruction {
+        instruction::execute_token_sale(
+            &self.program_id,
+            &buyer.pubkey(),
+            &self.token_sale.pubkey(),
+            &self.sale_token_account,
+            &buyer.token_account,
+            &buyer.usd_account,
+            &self.pool_usd_account,
+            &self.whitelist_map,
+            &self.whitelist_account,
+            &self.whitelist_program_id,
+            usd_amount,
+            max_price,
+            None,
+        )
+    }
+
+    pub async fn execute(&mut self, buyer: &Buyer, usd_amount: u64) -> Result<(), TransactionError> {
+        self.execute_with_max_price(buyer, usd_amount, u64::MAX).await
+    }
+
+    pub async fn execute_with_max_price(
+        &mut self,
+        buyer: &Buyer,
+        usd_amount: u64,
+        max_price: u64,
+    ) -> Result<(), TransactionError> {
+        let instruction = self.execute_instruction(buyer, usd_amount, max_price);
+        self.process(&[instruction], &[&buyer.keypair]).await
+    }
+
+    pub async fn pause(&mut self) -> Result<(), TransactionError> {
+        let instruction =
+            instruction::pause_token_sale(&self.program_id, &self.owner.pubkey(), &self.token_sale.pubkey());
+        let owner = self.owner();
+        self.process(&[instruction], &[&owner]).await
+    }
+
+    pub async fn resume(&mut self) -> Result<(), TransactionError> {
+        let instruction =
+            instruction::resume_token_sale(&self.program_id, &self.owner.pubkey(), &self.token_sale.pubkey());
+        let owner = self.owner();
+        self.process(&[instruction], &[&owner]).await
+    }
+
+    pub async fn end(&mut self) -> Result<(), TransactionError> {
+        let instruction =
+            instruction::end_token_sale(&self.program_id, &self.owner.pubkey(), &self.token_sale.pubkey());
+        let owner = self.owner();
+        self.process(&[instruction], &[&owner]).await
+    }
+
+    pub async fn update_parameters(
+        &mut self,
+        usd_min_amount: u64,
+        usd_max_amount: u64,
+        token_sale_price: u64,
+        token_sale_time: u64,
+    ) -> Result<(), TransactionError> {
+        let instruction = instruction::update_sale_parameters(
+            &self.program_id,
+            &self.owner.pubkey(),
+            &self.token_sale.pubkey(),
+            usd_min_amount,
+            usd_max_amount,
+            token_sale_price,
+            token_sale_time,
+        );
+        let owner = self.owner();
+        self.process(&[instruction], &[&owner]).await
+    }
+
+    pub async fn propose_admin(&mut self, new_admin_pubkey: &Pubkey) -> Result<(), TransactionError> {
+        let instruction = instruction::propose_admin(
+            &self.program_id,
+            &self.owner.pubkey(),
+            &self.token_sale.pubkey(),
+            new_admin_pubkey,
+        );
+        let owner = self.owner();
+        self.process(&[instruction], &[&owner]).await
+    }
+
+    pub async fn accept_admin(&mut self, new_admin: &Keypair) -> Result<(), TransactionError> {
+        let instruction =
+            instruction::accept_admin(&self.program_id, &new_admin.pubkey(), &self.token_sale.pubkey());
+        self.process(&[instruction], &[new_admin]).await
+    }
+
+    pub async fn set_role(&mut self, role: SaleRole, role_pubkey: &Pubkey) -> Result<(), TransactionError> {
+        let instruction = instruction::set_sale_role(
+            &self.program_id,
+            &self.owner.pubkey(),
+            &self.token_sale.pubkey(),
+            role,
+            role_pubkey,
+        );
+        let owner = self.owner();
+        self.process(&[instruction], &[&owner]).await
+    }
+
+    pub async fn set_payment_mint(
+        &mut self,
+        payment_mint: &Pubkey,
+        pool_account: &Pubkey,
+        price: u64,
+    ) -> Result<(), TransactionError> {
+        let instruction = instruction::set_payment_mint(
+            &self.program_id,
+            &self.owner.pubkey(),
+            &self.token_sale.pubkey(),
+            payment_mint,
+            pool_account,
+            price,
+        );
+        let owner = self.owner();
+        self.process(&[instruction], &[&owner]).await
+    }
+
+    /// Purchases with `amount` from `payment_account` paid into `pool_account`
+    pub async fn execute_with_payment(
+        &mut self,
+        buyer: &Buyer,
+        payment_account: &Pubkey,
+        pool_account: &Pubkey,
+        amount: u64,
+    ) -> Result<(), TransactionError> {
+        let instruction = instruction::execute_token_sale(
+            &self.program_id,
+            &buyer.pubkey(),
+            &self.token_sale.pubkey(),
+            &self.sale_token_account,
+            &buyer.token_account,
+            payment_account,
+            pool_account,
+            &self.whitelist_map,
+            &self.whitelist_account,
+            &self.whitelist_program_id,
+            amount,
+            u64::MAX,
+            None,
+        );
+        self.process(&[instruction], &[&buyer.keypair]).await
+    }
+
+    /// Purchases with `lamports` of native SOL from the buyer wallet paid into `pool_account`
+    pub async fn execute_with_sol(
+        &mut self,
+        buyer: &Buyer,
+        pool_account: &Pubkey,
+        lamports: u64,
+    ) -> Result<(), TransactionError> {
+        self.execute_with_sol_and_oracle(buyer, pool_account, None, lamports).await
+    }
+
+    /// Purchases with `lamports` of native SOL paid into `pool_account` at the price of `oracle`
+    pub async fn execute_with_oracle(
+        &mut self,
+        buyer: &Buyer,
+        pool_account: &Pubkey,
+        oracle: &Pubkey,
+        lamports: u64,
+    ) -> Result<(), TransactionError> {
+        self.execute_with_sol_and_oracle(buyer, pool_account, Some(oracle), lamports).await
+    }
+
+    async fn execute_with_sol_and_oracle(
+        &mut self,
+        buyer: &Buyer,
+        pool_account: &Pubkey,
+        oracle: Option<&Pubkey>,
+        lamports: u64,
+    ) -> Result<(), TransactionError> {
+        let instruction = instruction::execute_token_sale_with_sol(
+            &self.program_id,
+            &buyer.pubkey(),
+            &self.token_sale.pubkey(),
+            &self.sale_token_account,
+            &buyer.token_account,
+            pool_account,
+            &self.whitelist_map,
+            &self.whitelist_account,
+            &self.whitelist_program_id,
+            lamports,
+            u64::MAX,
+            None,
+            oracle,
+        );
+        self.process(&[instruction], &[&buyer.keypair]).await
+    }
+
+    pub async fn set_oracle(
+        &mut self,
+        oracle: &Pubkey,
+        max_age: u64,
+        max_confidence_bps: u16,
+    ) -> Result<(), TransactionError> {
+        let instruction = instruction::set_oracle(
+            &self.program_id,
+            &self.owner.pubkey(),
+            &self.token_sale.pubkey(),
+            oracle,
+            max_age,
+            max_confidence_bps,
+        );
+        let owner = self.owner();
+        self.process(&[instruction], &[&owner]).await
+    }
+
+    pub async fn set_voucher_signer(&mut self, voucher_signer: &Pubkey) -> Result<(), TransactionError> {
+        let instruction = instruction::set_voucher_signer(
+            &self.program_id,
+            &self.owner.pubkey(),
+            &self.token_sale.pubkey(),
+            voucher_signer,
+        );
+        let owner = self.owner();
+        self.process(&[instruction], &[&owner]).await
+    }
+
+    /// Creates an Ed25519 program instruction verifying a voucher of `allocation` for
+    /// `buyer` signed by `voucher_signer`
+    pub fn voucher_instruction(
+        &self,
+        voucher_signer: &ed25519_dalek::Keypair,
+        buyer: &Buyer,
+        allocation: u64,
+        expiry_time: u64,
+    ) -> Instruction {
+        let voucher = Voucher {
+            token_sale_pubkey: self.token_sale.pubkey(),
+            user_pubkey: buyer.pubkey(),
+            allocation_amount: allocation,
+            expiry_time,
+        };
+        let mut message = vec![0; Voucher::LEN];
+        Voucher::pack(voucher, &mut message).unwrap();
+        ed25519_instruction::new_ed25519_instruction(voucher_signer, &message)
+    }
+
+    /// Creates a purchase of `usd_amount` reading the allocation from a voucher
+    pub fn voucher_execute_instruction(&self, buyer: &Buyer, usd_amount: u64) -> Instruction {
+        instruction::execute_token_sale_with_voucher(
+            &self.program_id,
+            &buyer.pubkey(),
+            &self.token_sale.pubkey(),
+            &self.sale_token_account,
+            &buyer.token_account,
+            &buyer.usd_account,
+            &self.pool_usd_account,
+            &self.whitelist_map,
+            &self.whitelist_program_id,
+            usd_amount,
+            u64::MAX,
+        )
+    }
+
+    /// Purchases with `usd_amount` against the voucher verified by `voucher_instruction`
+    pub async fn execute_with_voucher(
+        &mut self,
+        buyer: &Buyer,
+        voucher_instruction: Instruction,
+        usd_amount: u64,
+    ) -> Result<(), TransactionError> {
+        let instruction = self.voucher_execute_instruction(buyer, usd_amount);
+        self.process(&[voucher_instruction, instruction], &[&buyer.keypair]).await
+    }
+
+    pub async fn set_merkle_root(&mut self, merkle_root: [u8; 32]) -> Result<(), TransactionError> {
+        let instruction = instruction::set_merkle_root(
+            &self.program_id,
+            &self.owner.pubkey(),
+            &self.token_sale.pubkey(),
+            merkle_root,
+        );
+        let owner = self.owner();
+        self.process(&[instruction], &[&owner]).await
+    }
+
+    pub async fn execute_with_merkle_proof(
+        &mut self,
+        buyer: &Buyer,
+        merkle_proof: MerkleProof,
+        usd_amount: u64,
+    ) -> Result<(), TransactionError> {
+        let mut instruction = self.execute_instruction(buyer, usd_amount, u64::MAX);
+        instruction.data = TokenSaleInstruction::ExecuteTokenSale {
+            usd_amount,
+            max_price: u64::MAX,
+            merkle_proof: Some(merkle_proof),
+        }
+        .pack();
+        self.process(&[instruction], &[&buyer.keypair]).await
+    }
+
+    pub async fn withdraw_unsold(&mut self, destination: &Pubkey) -> Result<(), TransactionError> {
+        let instruction = instruction::withdraw_unsold_tokens(
+            &self.program_id,
+            &self.owner.pubkey(),
+            &self.token_sale.pubkey(),
+            &self.sale_token_account,
+            destination,
+        );
+        let owner = self.owner();
+        self.process(&[instruction], &[&owner]).await
+    }
+
+    pub async fn cancel(&mut self, destination: &Pubkey) -> Result<(), TransactionError> {
+        let instruction = instruction::cancel_token_sale(
+            &self.program_id,
+            &self.owner.pubkey(),
+            &self.token_sale.pubkey(),
+            &self.sale_token_account,
+            destination,
+        );
+        let owner = self.owner();
+        self.process(&[instruction], &[&owner]).await
+    }
+
+    pub async fn close(&mut self) -> Result<(), TransactionError> {
+        let instruction = instruction::close_token_sale(
+            &self.program_id,
+            &self.owner.pubkey(),
+            &self.token_sale.pubkey(),
+            &self.sale_token_account,
+            &self.pool_usd_account,
+        );
+        let owner = self.owner();
+        self.process(&[instruction], &[&owner]).await
+    }
+
+    pub async fn claim(&mut self, buyer: &Buyer) -> Result<(), TransactionError> {
+        let instruction = instruction::claim_vested_tokens(
+            &self.program_id,
+            &buyer.pubkey(),
+            &self.token_sale.pubkey(),
+            &self.sale_token_account,
+            &buyer.token_account,
+        );
+        self.process(&[instruction], &[&buyer.keypair]).await
+    }
+
+    pub async fn refund(&mut self, buyer: &Buyer) -> Result<(), TransactionError> {
+        let instruction = instruction::refund_purchase(
+            &self.program_id,
+            &buyer.pubkey(),
+            &self.token_sale.pubkey(),
+            &self.pool_usd_account,
+            &buyer.usd_account,
+            &self.sale_token_account,
+            &buyer.token_account,
+        );
+        self.process(&[instruction], &[&buyer.keypair]).await
+    }
+
+    pub async fn withdraw_proceeds(&mut self, destination: &Pubkey) -> Result<(), TransactionError> {
+        let instruction = instruction::withdraw_sale_proceeds(
+            &self.program_id,
+            &self.owner.pubkey(),
+            &self.token_sale.pubkey(),
+            &self.pool_usd_account,
+            destination,
+        );
+        let owner = self.owner();
+        self.process(&[instruction], &[&owner]).await
+    }
+
+    pub async fn commit(&mut self, buyer: &Buyer, usd_amount: u64) -> Result<(), TransactionError> {
+        let instruction = instruction::commit_to_sale(
+            &self.program_id,
+            &buyer.pubkey(),
+            &self.token_sale.pubkey(),
+            &buyer.usd_account,
+            &self.pool_usd_account,
+            &self.whitelist_map,
+            &self.whitelist_account,
+            &self.whitelist_program_id,
+            usd_amount,
+            None,
+        );
+        self.process(&[instruction], &[&buyer.keypair]).await
+    }
+
+    pub async fn settle(&mut self, buyer: &Buyer) -> Result<(), TransactionError> {
+        let instruction = instruction::settle_commitment(
+            &self.program_id,
+            &buyer.pubkey(),
+            &self.token_sale.pubkey(),
+            &self.pool_usd_account,
+            &buyer.usd_account,
+            &self.sale_token_account,
+            &buyer.token_account,
+        );
+        self.process(&[instruction], &[&buyer.keypair]).await
+    }
+
+    /// Stores a sale of 1000 tokens at `token_sale_price` tokens per usd in the version 1
+    /// layout, with a funded sale token account held by the version 1 authority
+    pub async fn add_v1_token_sale(&mut self, token_sale_price: u64) {
+        self.add_v1_token_sale_with_balance(token_sale_price, 1000 * ONE_TOKEN).await
+    }
+
+    /// Stores a version 1 sale like [TokenSaleTest::add_v1_token_sale] with `balance`
+    /// in its sale token account
+    pub async fn add_v1_token_sale_with_balance(&mut self, token_sale_price: u64, balance: u64) {
+        let (authority_v1, _) = Processor::find_authority_v1_address(&self.program_id);
+        self.sale_token_account = self.create_token_account(&self.sale_mint.clone(), &authority_v1).await;
+        if balance > 0 {
+            self.mint_to(&self.sale_mint.clone(), &self.sale_token_account.clone(), balance).await;
+        }
+
+        let token_sale_v1 = TokenSaleV1 {
+            is_initialized: true,
+            init_pubkey: self.owner.pubkey(),
+            sale_token_account_pubkey: self.sale_token_account,
+            pool_token_account_pubkey: self.pool_usd_account,
+            whitelist_map_pubkey: self.whitelist_map,
+            whitelist_program_pubkey: self.whitelist_program_id,
+            token_sale_amount: 1000 * ONE_TOKEN,
+            usd_min_amount: ONE_USD,
+            usd_max_amount: 100 * ONE_USD,
+            token_sale_price,
+            token_sale_time: SALE_TIME,
+            token_sale_paused: false,
+            token_sale_ended: false,
+        };
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+        let mut data = vec![0; TokenSaleV1::LEN];
+        TokenSaleV1::pack(token_sale_v1, &mut data).unwrap();
+        let account = Account {
+            lamports: rent.minimum_balance(TokenSaleV1::LEN),
+            data,
+            owner: self.program_id,
+            ..Account::default()
+        };
+        self.context.set_account(&self.token_sale.pubkey(), &AccountSharedData::from(account));
+    }
+
+    /// Stores `price_account` in the Pyth layout at `oracle`
+    pub async fn set_price_account(&mut self, oracle: &Pubkey, price_account: PriceAccount) {
+        self.set_price_account_with_owner(oracle, price_account, &PYTH_PROGRAM_ID).await
+    }
+
+    /// Stores `price_account` in the Pyth layout at `oracle`, owned by `owner`
+    pub async fn set_price_account_with_owner(&mut self, oracle: &Pubkey, price_account: PriceAccount, owner: &Pubkey) {
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+        let mut data = vec![0; PriceAccount::LEN];
+        PriceAccount::pack(price_account, &mut data).unwrap();
+        let account = Account {
+            lamports: rent.minimum_balance(PriceAccount::LEN),
+            data,
+            owner: *owner,
+            ..Account::default()
+        };
+        self.context.set_account(oracle, &AccountSharedData::from(account));
+    }
+
+    pub async fn migrate(&mut self) -> Result<(), TransactionError> {
+        let instruction = instruction::migrate_token_sale(
+            &self.program_id,
+            &self.owner.pubkey(),
+            &self.token_sale.pubkey(),
+            &self.sale_token_account,
+            &self.pool_usd_account,
+            &self.sale_mint,
+            &self.usd_mint,
+        );
+        let owner = self.owner();
+        self.process(&[instruction], &[&owner]).await
+    }
+
+    pub async fn token_sale_state(&mut self) -> TokenSale {
+        let account = self.get_account(&self.token_sale.pubkey()).await;
+        TokenSale::unpack(&account.data).unwrap()
+    }
+
+    pub async fn purchase_record(&mut self, buyer: &Buyer) -> PurchaseRecord {
+        let (address, _) = Processor::find_purchase_record_address(
+            &self.program_id,
+            &self.token_sale.pubkey(),
+            &buyer.pubkey(),
+        );
+        let account = self.get_account(&address).await;
+        PurchaseRecord::unpack(&account.data).unwrap()
+    }
+
+    pub async fn token_balance(&mut self, account: &Pubkey) -> u64 {
+        let account = self.get_account(account).await;
+        spl_token::state::Account::unpack(&account.data).unwrap().amount
+    }
+
+    pub async fn get_account(&mut self, pubkey: &Pubkey) -> Account {
+        self.context.banks_client.get_account(*pubkey).await.unwrap().unwrap()
+    }
+
+    pub async fn account_exists(&mut self, pubkey: &Pubkey) -> bool {
+        self.context.banks_client.get_account(*pubkey).await.unwrap().is_some()
+    }
+
+    /// Copy of the owner keypair, so it can sign alongside mutable borrows of the test
+    pub fn owner(&self) -> Keypair {
+        Keypair::from_bytes(&self.owner.to_bytes()).unwrap()
+    }
+}
+
+pub fn assert_sale_error(result: Result<(), TransactionError>, error: TokenSaleError) {
+    assert_instruction_error(result, InstructionError::Custom(error as u32));
+}
+
+/// Asserts the transaction failed with instruction error `error`
+pub fn assert_instruction_error(result: Result<(), TransactionError>, error: InstructionError) {
+    match result {
+        Err(TransactionError::InstructionError(_, instruction_error)) => {
+            assert_eq!(instruction_error, error)
+        }
+        other => panic!("expected {:?}, got {:?}", error, other),
+    }
+}
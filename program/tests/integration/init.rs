@@ -0,0 +1,174 @@
+use crate::helpers::*;
+use solana_program::{instruction::Instruction, program_pack::Pack};
+use solana_sdk::signature::Signer;
+use solr_token_sale::{
+    error::TokenSaleError,
+    processor::Processor,
+    state::{PricingMode, SaleMode, PRICE_MULTIPLIER},
+};
+
+#[tokio::test]
+async fn test_init_token_sale() {
+    let mut test = TokenSaleTest::start_with_decimals(9, 6).await;
+    test.init(&SaleParams::default()).await.unwrap();
+
+    let token_sale_state = test.token_sale_state().await;
+    let (authority, bump_seed) = Processor::find_authority_address(&test.program_id, &test.token_sale.pubkey());
+    assert!(token_sale_state.is_initialized);
+    assert_eq!(token_sale_state.init_pubkey, test.owner.pubkey());
+    assert_eq!(token_sale_state.guardian_pubkey, test.owner.pubkey());
+    assert_eq!(token_sale_state.operator_pubkey, test.owner.pubkey());
+    assert_eq!(token_sale_state.sale_token_account_pubkey, test.sale_token_account);
+    assert_eq!(token_sale_state.pool_token_account_pubkey, test.pool_usd_account);
+    assert_eq!(token_sale_state.whitelist_map_pubkey, test.whitelist_map);
+    assert_eq!(token_sale_state.whitelist_program_pubkey, test.whitelist_program_id);
+    assert_eq!(token_sale_state.token_sale_price, PRICE_MULTIPLIER / 10);
+    assert_eq!(token_sale_state.bump_seed, bump_seed);
+    assert_eq!(token_sale_state.sale_mint_decimals, 9);
+    assert_eq!(token_sale_state.usd_mint_decimals, 6);
+
+    let sale_token_account = test.get_account(&test.sale_token_account.clone()).await;
+    assert_eq!(spl_token::state::Account::unpack(&sale_token_account.data).unwrap().owner, authority);
+    // without escrow the proceeds go straight to the pool account
+    let pool_usd_account = test.get_account(&test.pool_usd_account.clone()).await;
+    assert_eq!(spl_token::state::Account::unpack(&pool_usd_account.data).unwrap().owner, test.owner.pubkey());
+}
+
+#[tokio::test]
+async fn test_init_token_sale_with_escrow() {
+    let mut test = TokenSaleTest::start().await;
+    let params = SaleParams {
+        soft_cap_amount: 50 * ONE_USD,
+        ..SaleParams::default()
+    };
+    test.init(&params).await.unwrap();
+
+    let (authority, _) = Processor::find_authority_address(&test.program_id, &test.token_sale.pubkey());
+    let pool_usd_account = test.get_account(&test.pool_usd_account.clone()).await;
+    assert_eq!(spl_token::state::Account::unpack(&pool_usd_account.data).unwrap().owner, authority);
+}
+
+#[tokio::test]
+async fn test_init_token_sale_not_rent_exempt() {
+    let mut test = TokenSaleTest::start().await;
+    let result = test.init_with_lamports(&SaleParams::default(), 1).await;
+    assert_sale_error(result, TokenSaleError::NotRentExempt);
+}
+
+#[tokio::test]
+async fn test_init_token_sale_invalid_vesting_schedule() {
+    let mut test = TokenSaleTest::start().await;
+    let params = SaleParams {
+        vesting_tge_percent: 20,
+        vesting_start_time: SALE_END_TIME + 1000,
+        vesting_cliff_time: SALE_END_TIME,
+        vesting_end_time: SALE_END_TIME + 5000,
+        ..SaleParams::default()
+    };
+    let result = test.init(&params).await;
+    assert_sale_error(result, TokenSaleError::InvalidVestingSchedule);
+}
+
+#[tokio::test]
+async fn test_init_token_sale_invalid_auction_schedule() {
+    let mut test = TokenSaleTest::start().await;
+    let params = SaleParams {
+        pricing_mode: PricingMode::DutchAuction,
+        auction_start_price: PRICE_MULTIPLIER,
+        auction_floor_price: 0,
+        auction_decay_amount: PRICE_MULTIPLIER / 10,
+        auction_decay_interval: 60,
+        ..SaleParams::default()
+    };
+    let result = test.init(&params).await;
+    assert_sale_error(result, TokenSaleError::InvalidAuctionSchedule);
+}
+
+#[tokio::test]
+async fn test_init_pro_rata_dutch_auction() {
+    let mut test = TokenSaleTest::start().await;
+    let params = SaleParams {
+        sale_mode: SaleMode::ProRata,
+        pricing_mode: PricingMode::DutchAuction,
+        auction_start_price: PRICE_MULTIPLIER,
+        auction_floor_price: PRICE_MULTIPLIER / 10,
+        auction_decay_amount: PRICE_MULTIPLIER / 10,
+        auction_decay_interval: 60,
+        ..SaleParams::default()
+    };
+    let result = test.init(&params).await;
+    assert_sale_error(result, TokenSaleError::InvalidSaleMode);
+}
+
+#[tokio::test]
+async fn test_init_token_sale_zero_amount() {
+    let mut test = TokenSaleTest::start().await;
+    let params = SaleParams {
+        token_sale_amount: 0,
+        ..SaleParams::default()
+    };
+    let result = test.init(&params).await;
+    assert_sale_error(result, TokenSaleError::InvalidTokenSaleAmount);
+}
+
+#[tokio::test]
+async fn test_init_token_sale_invalid_purchase_limits() {
+    let mut test = TokenSaleTest::start().await;
+    let params = SaleParams {
+        usd_min_amount: 100 * ONE_USD,
+        usd_max_amount: 10 * ONE_USD,
+        ..SaleParams::default()
+    };
+    let result = test.init(&params).await;
+    assert_sale_error(result, TokenSaleError::InvalidPurchaseLimits);
+}
+
+#[tokio::test]
+async fn test_init_token_sale_zero_price() {
+    let mut test = TokenSaleTest::start().await;
+    let params = SaleParams {
+        token_sale_price: 0,
+        ..SaleParams::default()
+    };
+    let result = test.init(&params).await;
+    assert_sale_error(result, TokenSaleError::InvalidTokenSalePrice);
+}
+
+#[tokio::test]
+async fn test_init_token_sale_invalid_time() {
+    let mut test = TokenSaleTest::start().await;
+    let params = SaleParams {
+        token_sale_time: SALE_TIME - 10,
+        ..SaleParams::default()
+    };
+    let result = test.init(&params).await;
+    assert_sale_error(result, TokenSaleError::InvalidTokenSaleTime);
+
+    let params = SaleParams {
+        token_sale_end_time: SALE_TIME,
+        ..SaleParams::default()
+    };
+    let result = test.init(&params).await;
+    assert_sale_error(result, TokenSaleError::InvalidTokenSaleTime);
+}
+
+#[tokio::test]
+async fn test_init_token_sale_sale_token_account_not_empty() {
+    let mut test = TokenSaleTest::start().await;
+    test.mint_to(&test.sale_mint.clone(), &test.sale_token_account.clone(), ONE_TOKEN).await;
+
+    let result = test.init(&SaleParams::default()).await;
+    assert_sale_error(result, TokenSaleError::InvalidSaleTokenBalance);
+}
+
+#[tokio::test]
+async fn test_invalid_instruction() {
+    let mut test = TokenSaleTest::start().await;
+    let instruction = Instruction {
+        program_id: test.program_id,
+        accounts: vec![],
+        data: vec![99],
+    };
+    let result = test.process(&[instruction], &[]).await;
+    assert_sale_error(result, TokenSaleError::InvalidInstruction);
+}
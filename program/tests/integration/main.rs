@@ -0,0 +1,20 @@
+//! Processor tests running the token sale and a mock whitelist program in solana-program-test
+
+mod admin;
+mod auction;
+mod cancel;
+mod close;
+mod fund;
+mod helpers;
+mod init;
+mod merkle;
+mod migrate;
+mod mock_whitelist;
+mod oracle;
+mod payment;
+mod pro_rata;
+mod purchase;
+mod soft_cap;
+mod validation;
+mod voucher;
+mod vesting;
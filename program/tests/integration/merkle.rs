@@ -0,0 +1,119 @@
+use crate::helpers::*;
+use solana_program::pubkey::Pubkey;
+use solana_sdk::signature::Signer;
+use solr_token_sale::{
+    error::TokenSaleError,
+    instruction,
+    merkle::{MerkleProof, MerkleTree},
+    state::SaleMode,
+};
+
+/// Merkle whitelist of the buyers with their allocations among other users
+fn merkle_tree(allocations: &[(&Buyer, u64)]) -> MerkleTree {
+    let mut leaves: Vec<(Pubkey, u64)> = (0..5).map(|_| (Pubkey::new_unique(), 100 * ONE_USD)).collect();
+    leaves.extend(allocations.iter().map(|(buyer, allocation)| (buyer.pubkey(), *allocation)));
+    MerkleTree::new(leaves).unwrap()
+}
+
+#[tokio::test]
+async fn test_execute_token_sale_with_merkle_proof() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let buyer = test.add_unlisted_buyer(100 * ONE_USD).await;
+    let tree = merkle_tree(&[(&buyer, 50 * ONE_USD)]);
+    test.set_merkle_root(tree.root()).await.unwrap();
+    test.set_time(SALE_TIME).await;
+
+    let merkle_proof = tree.proof(&buyer.pubkey()).unwrap();
+    test.execute_with_merkle_proof(&buyer, merkle_proof.clone(), 10 * ONE_USD).await.unwrap();
+    test.execute_with_merkle_proof(&buyer, merkle_proof.clone(), 40 * ONE_USD).await.unwrap();
+    assert_eq!(test.token_balance(&buyer.token_account).await, 500 * ONE_TOKEN);
+    assert_eq!(test.purchase_record(&buyer).await.usd_amount, 50 * ONE_USD);
+
+    // the proven allocation is spent across purchases
+    let result = test.execute_with_merkle_proof(&buyer, merkle_proof, ONE_USD).await;
+    assert_sale_error(result, TokenSaleError::ExceedsAllocation);
+}
+
+#[tokio::test]
+async fn test_execute_token_sale_invalid_merkle_proof() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let buyer = test.add_unlisted_buyer(100 * ONE_USD).await;
+    let other_buyer = test.add_unlisted_buyer(100 * ONE_USD).await;
+    let tree = merkle_tree(&[(&buyer, 50 * ONE_USD), (&other_buyer, 80 * ONE_USD)]);
+    test.set_merkle_root(tree.root()).await.unwrap();
+    test.set_time(SALE_TIME).await;
+
+    // raising the allocation of the leaf
+    let merkle_proof = MerkleProof {
+        allocation_amount: 80 * ONE_USD,
+        ..tree.proof(&buyer.pubkey()).unwrap()
+    };
+    let result = test.execute_with_merkle_proof(&buyer, merkle_proof, 60 * ONE_USD).await;
+    assert_sale_error(result, TokenSaleError::InvalidMerkleProof);
+
+    // proving the leaf of another buyer
+    let merkle_proof = tree.proof(&other_buyer.pubkey()).unwrap();
+    let result = test.execute_with_merkle_proof(&buyer, merkle_proof, 60 * ONE_USD).await;
+    assert_sale_error(result, TokenSaleError::InvalidMerkleProof);
+
+    // allocations are no longer read from the whitelist
+    test.set_allocation(&buyer.pubkey(), 50 * ONE_USD).await;
+    let result = test.execute(&buyer, 10 * ONE_USD).await;
+    assert_sale_error(result, TokenSaleError::InvalidMerkleProof);
+    assert_eq!(test.token_balance(&buyer.token_account).await, 0);
+}
+
+#[tokio::test]
+async fn test_commit_to_sale_with_merkle_proof() {
+    let mut test = TokenSaleTest::start().await;
+    let params = SaleParams {
+        sale_mode: SaleMode::ProRata,
+        ..SaleParams::default()
+    };
+    test.init_and_fund(&params).await;
+    let buyer = test.add_unlisted_buyer(100 * ONE_USD).await;
+    let tree = merkle_tree(&[(&buyer, 50 * ONE_USD)]);
+    test.set_merkle_root(tree.root()).await.unwrap();
+    test.set_time(SALE_TIME).await;
+
+    let commit_instruction = |usd_amount| {
+        instruction::commit_to_sale(
+            &test.program_id,
+            &buyer.pubkey(),
+            &test.token_sale.pubkey(),
+            &buyer.usd_account,
+            &test.pool_usd_account,
+            &test.whitelist_map,
+            &test.whitelist_account,
+            &test.whitelist_program_id,
+            usd_amount,
+            tree.proof(&buyer.pubkey()),
+        )
+    };
+    let instructions = [commit_instruction(30 * ONE_USD)];
+    test.process(&instructions, &[&buyer.keypair]).await.unwrap();
+    assert_eq!(test.purchase_record(&buyer).await.usd_amount, 30 * ONE_USD);
+
+    let result = test.process(&instructions, &[&buyer.keypair]).await;
+    assert_sale_error(result, TokenSaleError::ExceedsAllocation);
+}
+
+#[tokio::test]
+async fn test_set_merkle_root() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let buyer = test.add_buyer(50 * ONE_USD, 100 * ONE_USD).await;
+    test.set_merkle_root(merkle_tree(&[]).root()).await.unwrap();
+    assert!(test.token_sale_state().await.has_merkle_root());
+
+    // a root of all zeros reads allocations from the whitelist again
+    test.set_merkle_root([0; 32]).await.unwrap();
+    assert!(!test.token_sale_state().await.has_merkle_root());
+    test.set_time(SALE_TIME).await;
+    test.execute(&buyer, 10 * ONE_USD).await.unwrap();
+
+    let result = test.set_merkle_root(merkle_tree(&[(&buyer, 50 * ONE_USD)]).root()).await;
+    assert_sale_error(result, TokenSaleError::TokenSaleStarted);
+}
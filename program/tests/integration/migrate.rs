@@ -0,0 +1,86 @@
+use crate::helpers::*;
+use solana_program::program_pack::Pack;
+use solana_sdk::signature::Signer;
+use solr_token_sale::{
+    error::TokenSaleError,
+    processor::Processor,
+    state::{SaleStatus, TokenSale, PRICE_MULTIPLIER, TOKEN_SALE_VERSION},
+};
+
+#[tokio::test]
+async fn test_migrate_token_sale_v1() {
+    let mut test = TokenSaleTest::start().await;
+    // 10 token base units per usd base unit, 0.1 usd per token
+    test.add_v1_token_sale(10).await;
+    let buyer = test.add_buyer(50 * ONE_USD, 100 * ONE_USD).await;
+    test.set_time(SALE_TIME).await;
+
+    let result = test.execute(&buyer, 10 * ONE_USD).await;
+    assert_sale_error(result, TokenSaleError::InvalidAccountVersion);
+
+    test.migrate().await.unwrap();
+
+    let account = test.get_account(&test.token_sale.pubkey()).await;
+    assert_eq!(account.data.len(), TokenSale::LEN);
+    assert_eq!(TokenSale::version(&account.data), TOKEN_SALE_VERSION);
+    let rent = test.context.banks_client.get_rent().await.unwrap();
+    assert!(rent.is_exempt(account.lamports, account.data.len()));
+
+    let token_sale_state = test.token_sale_state().await;
+    assert_eq!(token_sale_state.init_pubkey, test.owner.pubkey());
+    assert_eq!(token_sale_state.sale_token_account_pubkey, test.sale_token_account);
+    assert_eq!(token_sale_state.pool_token_account_pubkey, test.pool_usd_account);
+    assert_eq!(token_sale_state.whitelist_map_pubkey, test.whitelist_map);
+    assert_eq!(token_sale_state.whitelist_program_pubkey, test.whitelist_program_id);
+    assert_eq!(token_sale_state.token_sale_amount, 1000 * ONE_TOKEN);
+    assert_eq!(token_sale_state.usd_min_amount, ONE_USD);
+    assert_eq!(token_sale_state.usd_max_amount, 100 * ONE_USD);
+    assert_eq!(token_sale_state.token_sale_price, PRICE_MULTIPLIER / 10);
+    assert_eq!(token_sale_state.token_sale_time, SALE_TIME);
+    assert_eq!(token_sale_state.status, SaleStatus::Funded);
+
+    let sale_token_account = test.get_account(&test.sale_token_account.clone()).await;
+    let (authority, _) = Processor::find_authority_address(&test.program_id, &test.token_sale.pubkey());
+    assert_eq!(spl_token::state::Account::unpack(&sale_token_account.data).unwrap().owner, authority);
+
+    test.execute(&buyer, 10 * ONE_USD).await.unwrap();
+    assert_eq!(test.token_balance(&buyer.token_account).await, 100 * ONE_TOKEN);
+}
+
+#[tokio::test]
+async fn test_migrate_unfunded_token_sale_v1() {
+    let mut test = TokenSaleTest::start().await;
+    test.add_v1_token_sale_with_balance(10, 0).await;
+    let buyer = test.add_buyer(50 * ONE_USD, 100 * ONE_USD).await;
+    test.set_time(SALE_TIME).await;
+
+    test.migrate().await.unwrap();
+    assert_eq!(test.token_sale_state().await.status, SaleStatus::Initialized);
+    let result = test.execute(&buyer, 10 * ONE_USD).await;
+    assert_sale_error(result, TokenSaleError::TokenSaleNotFunded);
+
+    test.fund(1000 * ONE_TOKEN).await.unwrap();
+    assert_eq!(test.token_sale_state().await.status, SaleStatus::Funded);
+    assert_eq!(test.token_balance(&test.sale_token_account.clone()).await, 1000 * ONE_TOKEN);
+    test.execute(&buyer, 10 * ONE_USD).await.unwrap();
+    assert_eq!(test.token_balance(&buyer.token_account).await, 100 * ONE_TOKEN);
+}
+
+#[tokio::test]
+async fn test_migrate_token_sale_current_version() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+
+    let result = test.migrate().await;
+    assert_sale_error(result, TokenSaleError::InvalidAccountVersion);
+}
+
+#[tokio::test]
+async fn test_migrate_token_sale_wrong_mint() {
+    let mut test = TokenSaleTest::start().await;
+    test.add_v1_token_sale(10).await;
+    test.usd_mint = test.sale_mint;
+
+    let result = test.migrate().await;
+    assert_sale_error(result, TokenSaleError::MintMismatch);
+}
@@ -0,0 +1,82 @@
+//! Minimal stand-in for the solr-token-whitelist program so the token sale can be
+//! tested offline. It only knows how to seed whitelist accounts and set allocations,
+//! which is all the token sale reads.
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use solr_token_whitelist::state::TokenWhitelist;
+use std::collections::BTreeMap;
+use std::convert::TryInto;
+
+/// Size of a whitelist map or whitelist account
+pub const WHITELIST_ACCOUNT_LEN: usize = 5161;
+
+/// Whitelist account data holding `entries`
+pub fn whitelist_data(init_pubkey: &Pubkey, entries: &[(Pubkey, u64)]) -> Vec<u8> {
+    let mut whitelist_map = BTreeMap::new();
+    for (key, value) in entries {
+        whitelist_map.insert(key.to_string(), *value);
+    }
+    let token_whitelist = TokenWhitelist {
+        is_initialized: true,
+        init_pubkey: *init_pubkey,
+        max_whitelist_size: 100,
+        whitelist_map,
+    };
+    let mut data = vec![0; WHITELIST_ACCOUNT_LEN];
+    token_whitelist.pack_into_slice(&mut data);
+    data
+}
+
+/// Sets the allocation of `user_pubkey` in a whitelist account
+pub fn set_allocation(
+    program_id: &Pubkey,
+    whitelist_account_pubkey: &Pubkey,
+    user_pubkey: &Pubkey,
+    allocation: u64,
+) -> Instruction {
+    let mut data = vec![0];
+    data.extend_from_slice(&allocation.to_le_bytes());
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*whitelist_account_pubkey, false),
+            AccountMeta::new_readonly(*user_pubkey, false),
+        ],
+        data,
+    }
+}
+
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let (&tag, rest) = instruction_data.split_first().ok_or(ProgramError::InvalidInstructionData)?;
+    if tag != 0 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let allocation = rest
+        .get(..8)
+        .and_then(|slice| slice.try_into().ok())
+        .map(u64::from_le_bytes)
+        .ok_or(ProgramError::InvalidInstructionData)?;
+
+    let account_info_iter = &mut accounts.iter();
+    let whitelist_account = next_account_info(account_info_iter)?;
+    let user_account = next_account_info(account_info_iter)?;
+    if whitelist_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut token_whitelist = TokenWhitelist::unpack_from_slice(&whitelist_account.data.borrow())?;
+    token_whitelist.add_keypair(&user_account.key.to_string(), &allocation);
+    token_whitelist.pack_into_slice(&mut whitelist_account.data.borrow_mut());
+
+    Ok(())
+}
@@ -0,0 +1,127 @@
+use crate::helpers::*;
+use solana_program::pubkey::Pubkey;
+use solana_sdk::signature::Signer;
+use solr_token_sale::{
+    error::TokenSaleError,
+    oracle::{PriceAccount, PYTH_PRICE_STATUS_TRADING},
+    state::PRICE_MULTIPLIER,
+};
+
+const ONE_SOL: u64 = 1_000_000_000;
+
+/// 125 usd per sol with a confidence interval of 0.1 usd
+fn sol_price() -> PriceAccount {
+    PriceAccount {
+        expo: -8,
+        timestamp: SALE_TIME as i64,
+        price: 12_500_000_000,
+        conf: 10_000_000,
+        status: PYTH_PRICE_STATUS_TRADING,
+    }
+}
+
+/// Starts a sale accepting native SOL at the oracle price, returning a buyer, the
+/// wrapped SOL pool account and the oracle
+async fn start_oracle_sale() -> (TokenSaleTest, Buyer, Pubkey, Pubkey) {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let buyer = test.add_buyer(200 * ONE_USD, 0).await;
+    let pool_account = test.create_token_account(&spl_token::native_mint::id(), &test.owner.pubkey()).await;
+    test.set_payment_mint(&spl_token::native_mint::id(), &pool_account, PRICE_MULTIPLIER / 1000).await.unwrap();
+    let oracle = Pubkey::new_unique();
+    test.set_price_account(&oracle, sol_price()).await;
+    test.set_oracle(&oracle, 60, 100).await.unwrap();
+    (test, buyer, pool_account, oracle)
+}
+
+#[tokio::test]
+async fn test_execute_token_sale_with_oracle_price() {
+    let (mut test, buyer, pool_account, oracle) = start_oracle_sale().await;
+    test.set_time(SALE_TIME + 60).await;
+
+    // 0.08 sol at 125 usd is worth 10 usd and buys 100 tokens
+    test.execute_with_oracle(&buyer, &pool_account, &oracle, ONE_SOL * 8 / 100).await.unwrap();
+
+    assert_eq!(test.token_balance(&pool_account).await, ONE_SOL * 8 / 100);
+    assert_eq!(test.token_balance(&buyer.token_account).await, 100 * ONE_TOKEN);
+    let purchase_record = test.purchase_record(&buyer).await;
+    assert_eq!(purchase_record.usd_amount, 10 * ONE_USD);
+    assert_eq!(test.token_sale_state().await.usd_raised_amount, 10 * ONE_USD);
+
+    // the usd value is checked against the purchase limits
+    let result = test.execute_with_oracle(&buyer, &pool_account, &oracle, ONE_SOL * 8 / 10).await;
+    assert_sale_error(result, TokenSaleError::AmountMaximum);
+}
+
+#[tokio::test]
+async fn test_execute_token_sale_stale_oracle_price() {
+    let (mut test, buyer, pool_account, oracle) = start_oracle_sale().await;
+    test.set_time(SALE_TIME + 61).await;
+
+    let result = test.execute_with_oracle(&buyer, &pool_account, &oracle, ONE_SOL / 10).await;
+    assert_sale_error(result, TokenSaleError::StaleOraclePrice);
+}
+
+#[tokio::test]
+async fn test_execute_token_sale_oracle_confidence_too_wide() {
+    let (mut test, buyer, pool_account, oracle) = start_oracle_sale().await;
+    test.set_time(SALE_TIME).await;
+    // a confidence interval of 2 usd is 1.6% of the price
+    let price_account = PriceAccount {
+        conf: 200_000_000,
+        ..sol_price()
+    };
+    test.set_price_account(&oracle, price_account).await;
+
+    let result = test.execute_with_oracle(&buyer, &pool_account, &oracle, ONE_SOL / 10).await;
+    assert_sale_error(result, TokenSaleError::OracleConfidenceTooWide);
+}
+
+#[tokio::test]
+async fn test_execute_token_sale_invalid_oracle() {
+    let (mut test, buyer, pool_account, oracle) = start_oracle_sale().await;
+    test.set_time(SALE_TIME).await;
+    let other_oracle = Pubkey::new_unique();
+    test.set_price_account(&other_oracle, sol_price()).await;
+
+    let result = test.execute_with_oracle(&buyer, &pool_account, &other_oracle, ONE_SOL / 10).await;
+    assert_sale_error(result, TokenSaleError::InvalidOracle);
+
+    let price_account = PriceAccount {
+        status: 0,
+        ..sol_price()
+    };
+    test.set_price_account(&oracle, price_account).await;
+    let result = test.execute_with_oracle(&buyer, &pool_account, &oracle, ONE_SOL / 10).await;
+    assert_sale_error(result, TokenSaleError::InvalidOracle);
+
+    // the configured oracle is rejected once it is no longer owned by the Pyth program
+    test.set_price_account_with_owner(&oracle, sol_price(), &Pubkey::new_unique()).await;
+    let result = test.execute_with_oracle(&buyer, &pool_account, &oracle, ONE_SOL / 10).await;
+    assert_sale_error(result, TokenSaleError::InvalidOracle);
+}
+
+#[tokio::test]
+async fn test_set_oracle() {
+    let (mut test, buyer, pool_account, _oracle) = start_oracle_sale().await;
+    let result = test.set_oracle(&pool_account, 60, 100).await;
+    assert_sale_error(result, TokenSaleError::InvalidOracle);
+    // a price account in the Pyth layout owned by any other program is rejected
+    let fake_oracle = Pubkey::new_unique();
+    test.set_price_account_with_owner(&fake_oracle, sol_price(), &Pubkey::new_unique()).await;
+    let result = test.set_oracle(&fake_oracle, 60, 100).await;
+    assert_sale_error(result, TokenSaleError::InvalidOracle);
+
+    // without an oracle native SOL is paid at its fixed price of 0.001 sol
+    test.set_oracle(&Pubkey::default(), 0, 0).await.unwrap();
+    let token_sale_state = test.token_sale_state().await;
+    assert!(!token_sale_state.has_oracle());
+    test.set_time(SALE_TIME).await;
+    test.execute_with_sol(&buyer, &pool_account, ONE_SOL / 10).await.unwrap();
+    assert_eq!(test.token_balance(&buyer.token_account).await, 100 * ONE_TOKEN);
+
+    let oracle = Pubkey::new_unique();
+    test.set_price_account(&oracle, sol_price()).await;
+    let result = test.set_oracle(&oracle, 60, 100).await;
+    assert_sale_error(result, TokenSaleError::TokenSaleStarted);
+}
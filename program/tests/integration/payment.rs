@@ -0,0 +1,190 @@
+use crate::helpers::*;
+use solana_program::pubkey::Pubkey;
+use solana_sdk::signature::Signer;
+use solr_token_sale::{error::TokenSaleError, processor::Processor, state::PRICE_MULTIPLIER};
+
+const PAYMENT_PRICE: u64 = PRICE_MULTIPLIER * 12 / 100;
+const SOL_PRICE: u64 = PRICE_MULTIPLIER / 1000;
+const ONE_SOL: u64 = 1_000_000_000;
+
+#[tokio::test]
+async fn test_execute_token_sale_with_payment_mint() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let buyer = test.add_buyer(50 * ONE_USD, 100 * ONE_USD).await;
+    let payment_mint = test.create_mint(DECIMALS).await;
+    let pool_account = test.create_token_account(&payment_mint, &test.owner.pubkey()).await;
+    let payment_account = test.create_token_account(&payment_mint, &buyer.pubkey()).await;
+    test.mint_to(&payment_mint, &payment_account, 100 * ONE_USD).await;
+    test.set_payment_mint(&payment_mint, &pool_account, PAYMENT_PRICE).await.unwrap();
+    test.set_time(SALE_TIME).await;
+
+    // 12 units at 0.12 buy 100 tokens, worth 10 usd at the sale price
+    test.execute_with_payment(&buyer, &payment_account, &pool_account, 12 * ONE_USD).await.unwrap();
+    test.execute(&buyer, 10 * ONE_USD).await.unwrap();
+
+    assert_eq!(test.token_balance(&payment_account).await, 88 * ONE_USD);
+    assert_eq!(test.token_balance(&pool_account).await, 12 * ONE_USD);
+    assert_eq!(test.token_balance(&test.pool_usd_account.clone()).await, 10 * ONE_USD);
+    assert_eq!(test.token_balance(&buyer.token_account).await, 200 * ONE_TOKEN);
+    let purchase_record = test.purchase_record(&buyer).await;
+    assert_eq!(purchase_record.usd_amount, 20 * ONE_USD);
+    assert_eq!(purchase_record.token_amount, 200 * ONE_TOKEN);
+    assert_eq!(test.token_sale_state().await.usd_raised_amount, 20 * ONE_USD);
+
+    // the usd value counts towards the allocation
+    let result = test.execute_with_payment(&buyer, &payment_account, &pool_account, 37 * ONE_USD).await;
+    assert_sale_error(result, TokenSaleError::ExceedsAllocation);
+}
+
+#[tokio::test]
+async fn test_execute_token_sale_payment_mint_not_accepted() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let buyer = test.add_buyer(50 * ONE_USD, 100 * ONE_USD).await;
+    let payment_mint = test.create_mint(DECIMALS).await;
+    let other_mint = test.create_mint(DECIMALS).await;
+    let pool_account = test.create_token_account(&payment_mint, &test.owner.pubkey()).await;
+    let other_pool_account = test.create_token_account(&other_mint, &test.owner.pubkey()).await;
+    let payment_account = test.create_token_account(&other_mint, &buyer.pubkey()).await;
+    test.mint_to(&other_mint, &payment_account, 100 * ONE_USD).await;
+    test.set_payment_mint(&payment_mint, &pool_account, PAYMENT_PRICE).await.unwrap();
+    test.set_time(SALE_TIME).await;
+
+    let result = test.execute_with_payment(&buyer, &payment_account, &other_pool_account, 12 * ONE_USD).await;
+    assert_sale_error(result, TokenSaleError::PaymentMintNotAccepted);
+}
+
+#[tokio::test]
+async fn test_execute_token_sale_payment_mint_wrong_pool() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let buyer = test.add_buyer(50 * ONE_USD, 100 * ONE_USD).await;
+    let payment_mint = test.create_mint(DECIMALS).await;
+    let pool_account = test.create_token_account(&payment_mint, &test.owner.pubkey()).await;
+    let other_pool_account = test.create_token_account(&payment_mint, &Pubkey::new_unique()).await;
+    let payment_account = test.create_token_account(&payment_mint, &buyer.pubkey()).await;
+    test.mint_to(&payment_mint, &payment_account, 100 * ONE_USD).await;
+    test.set_payment_mint(&payment_mint, &pool_account, PAYMENT_PRICE).await.unwrap();
+    test.set_time(SALE_TIME).await;
+
+    let result = test.execute_with_payment(&buyer, &payment_account, &other_pool_account, 12 * ONE_USD).await;
+    assert_sale_error(result, TokenSaleError::PoolTokenAccountMismatch);
+}
+
+#[tokio::test]
+async fn test_remove_payment_mint() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let buyer = test.add_buyer(50 * ONE_USD, 100 * ONE_USD).await;
+    let payment_mint = test.create_mint(DECIMALS).await;
+    let pool_account = test.create_token_account(&payment_mint, &test.owner.pubkey()).await;
+    let payment_account = test.create_token_account(&payment_mint, &buyer.pubkey()).await;
+    test.mint_to(&payment_mint, &payment_account, 100 * ONE_USD).await;
+    test.set_payment_mint(&payment_mint, &pool_account, PAYMENT_PRICE).await.unwrap();
+    test.set_payment_mint(&payment_mint, &pool_account, 0).await.unwrap();
+    test.set_time(SALE_TIME).await;
+
+    let result = test.execute_with_payment(&buyer, &payment_account, &pool_account, 12 * ONE_USD).await;
+    assert_sale_error(result, TokenSaleError::PaymentMintNotAccepted);
+}
+
+#[tokio::test]
+async fn test_set_payment_mint_limits() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    for _ in 0..4 {
+        let payment_mint = test.create_mint(DECIMALS).await;
+        let pool_account = test.create_token_account(&payment_mint, &test.owner.pubkey()).await;
+        test.set_payment_mint(&payment_mint, &pool_account, PAYMENT_PRICE).await.unwrap();
+    }
+    let payment_mint = test.create_mint(DECIMALS).await;
+    let pool_account = test.create_token_account(&payment_mint, &test.owner.pubkey()).await;
+    let result = test.set_payment_mint(&payment_mint, &pool_account, PAYMENT_PRICE).await;
+    assert_sale_error(result, TokenSaleError::TooManyPaymentMints);
+
+    let buyer = test.add_buyer(50 * ONE_USD, 100 * ONE_USD).await;
+    test.set_time(SALE_TIME).await;
+    test.execute(&buyer, 10 * ONE_USD).await.unwrap();
+    let result = test.set_payment_mint(&payment_mint, &pool_account, 0).await;
+    assert_sale_error(result, TokenSaleError::TokenSaleStarted);
+}
+
+#[tokio::test]
+async fn test_set_payment_mint_with_escrow() {
+    let mut test = TokenSaleTest::start().await;
+    let params = SaleParams {
+        soft_cap_amount: 50 * ONE_USD,
+        ..SaleParams::default()
+    };
+    test.init_and_fund(&params).await;
+    let payment_mint = test.create_mint(DECIMALS).await;
+    let pool_account = test.create_token_account(&payment_mint, &test.owner.pubkey()).await;
+
+    let result = test.set_payment_mint(&payment_mint, &pool_account, PAYMENT_PRICE).await;
+    assert_sale_error(result, TokenSaleError::InvalidSaleMode);
+}
+
+#[tokio::test]
+async fn test_close_token_sale_with_payment_mint() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let payment_mint = test.create_mint(DECIMALS).await;
+    let pool_account = test.create_token_account(&payment_mint, &test.owner.pubkey()).await;
+    let destination = test.create_token_account(&test.sale_mint.clone(), &test.owner.pubkey()).await;
+    test.set_payment_mint(&payment_mint, &pool_account, PAYMENT_PRICE).await.unwrap();
+    let (payment_config, _) = Processor::find_payment_config_address(&test.program_id, &test.token_sale.pubkey());
+    assert!(test.account_exists(&payment_config).await);
+
+    test.cancel(&destination).await.unwrap();
+    test.close().await.unwrap();
+    assert!(!test.account_exists(&test.token_sale.pubkey()).await);
+    assert!(!test.account_exists(&payment_config).await);
+}
+
+#[tokio::test]
+async fn test_execute_token_sale_with_native_sol() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let buyer = test.add_buyer(200 * ONE_USD, 0).await;
+    let pool_account = test.create_token_account(&spl_token::native_mint::id(), &test.owner.pubkey()).await;
+    test.set_payment_mint(&spl_token::native_mint::id(), &pool_account, SOL_PRICE).await.unwrap();
+    test.set_time(SALE_TIME).await;
+
+    // 0.1 sol at 0.001 sol buys 100 tokens, worth 10 usd at the sale price
+    test.execute_with_sol(&buyer, &pool_account, ONE_SOL / 10).await.unwrap();
+
+    assert_eq!(test.token_balance(&pool_account).await, ONE_SOL / 10);
+    assert_eq!(test.token_balance(&buyer.token_account).await, 100 * ONE_TOKEN);
+    let purchase_record = test.purchase_record(&buyer).await;
+    assert_eq!(purchase_record.usd_amount, 10 * ONE_USD);
+    assert_eq!(test.token_sale_state().await.usd_raised_amount, 10 * ONE_USD);
+}
+
+#[tokio::test]
+async fn test_execute_token_sale_with_native_sol_amount_limits() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let buyer = test.add_buyer(200 * ONE_USD, 0).await;
+    let pool_account = test.create_token_account(&spl_token::native_mint::id(), &test.owner.pubkey()).await;
+    test.set_payment_mint(&spl_token::native_mint::id(), &pool_account, SOL_PRICE).await.unwrap();
+    test.set_time(SALE_TIME).await;
+
+    let result = test.execute_with_sol(&buyer, &pool_account, ONE_SOL / 200).await;
+    assert_sale_error(result, TokenSaleError::AmountMinimum);
+    test.execute_with_sol(&buyer, &pool_account, ONE_SOL / 2).await.unwrap();
+    let result = test.execute_with_sol(&buyer, &pool_account, ONE_SOL * 6 / 10).await;
+    assert_sale_error(result, TokenSaleError::AmountMaximum);
+}
+
+#[tokio::test]
+async fn test_execute_token_sale_native_sol_not_accepted() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let buyer = test.add_buyer(200 * ONE_USD, 0).await;
+    let pool_account = test.create_token_account(&spl_token::native_mint::id(), &test.owner.pubkey()).await;
+    test.set_time(SALE_TIME).await;
+
+    let result = test.execute_with_sol(&buyer, &pool_account, ONE_SOL / 10).await;
+    assert_sale_error(result, TokenSaleError::PoolTokenAccountMismatch);
+}
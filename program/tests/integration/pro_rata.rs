@@ -0,0 +1,109 @@
+use crate::helpers::*;
+use solana_sdk::signature::Signer;
+use solr_token_sale::{error::TokenSaleError, state::SaleMode};
+
+fn pro_rata_params() -> SaleParams {
+    SaleParams {
+        sale_mode: SaleMode::ProRata,
+        ..SaleParams::default()
+    }
+}
+
+#[tokio::test]
+async fn test_commit_and_settle_oversubscribed() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&pro_rata_params()).await;
+    let first_buyer = test.add_buyer(100 * ONE_USD, 100 * ONE_USD).await;
+    let second_buyer = test.add_buyer(100 * ONE_USD, 100 * ONE_USD).await;
+    let owner_pubkey = test.owner.pubkey();
+    let destination = test.create_token_account(&test.usd_mint.clone(), &owner_pubkey).await;
+    test.set_time(SALE_TIME).await;
+
+    test.commit(&first_buyer, 40 * ONE_USD).await.unwrap();
+    test.commit(&first_buyer, 60 * ONE_USD).await.unwrap();
+    test.commit(&second_buyer, 100 * ONE_USD).await.unwrap();
+    assert_eq!(test.purchase_record(&first_buyer).await.usd_amount, 100 * ONE_USD);
+    assert_eq!(test.token_sale_state().await.usd_raised_amount, 200 * ONE_USD);
+
+    let result = test.settle(&first_buyer).await;
+    assert_sale_error(result, TokenSaleError::TokenSaleNotEnded);
+
+    // 1000 tokens at 0.1 usd fill 100 usd of the 200 usd committed
+    test.set_time(SALE_END_TIME).await;
+    test.settle(&first_buyer).await.unwrap();
+    assert_eq!(test.token_balance(&first_buyer.usd_account).await, 50 * ONE_USD);
+    assert_eq!(test.token_balance(&first_buyer.token_account).await, 500 * ONE_TOKEN);
+    assert!(test.purchase_record(&first_buyer).await.is_settled);
+
+    let result = test.settle(&first_buyer).await;
+    assert_sale_error(result, TokenSaleError::CommitmentSettled);
+
+    test.withdraw_proceeds(&destination).await.unwrap();
+    assert_eq!(test.token_balance(&destination).await, 50 * ONE_USD);
+
+    test.settle(&second_buyer).await.unwrap();
+    assert_eq!(test.token_balance(&second_buyer.usd_account).await, 50 * ONE_USD);
+    assert_eq!(test.token_balance(&second_buyer.token_account).await, 500 * ONE_TOKEN);
+
+    test.withdraw_proceeds(&destination).await.unwrap();
+    assert_eq!(test.token_balance(&destination).await, 100 * ONE_USD);
+    assert_eq!(test.token_balance(&test.pool_usd_account.clone()).await, 0);
+}
+
+#[tokio::test]
+async fn test_settle_tied_commitments_with_rounding() {
+    let mut test = TokenSaleTest::start().await;
+    // 20 tokens at 0.1 usd cap the sale at 2 usd
+    test.init_and_fund(&SaleParams {
+        token_sale_amount: 20 * ONE_TOKEN,
+        ..pro_rata_params()
+    }).await;
+    let buyers = [
+        test.add_buyer(ONE_USD, ONE_USD).await,
+        test.add_buyer(ONE_USD, ONE_USD).await,
+        test.add_buyer(ONE_USD, ONE_USD).await,
+    ];
+    let owner_pubkey = test.owner.pubkey();
+    let destination = test.create_token_account(&test.usd_mint.clone(), &owner_pubkey).await;
+    test.set_time(SALE_TIME).await;
+    for buyer in &buyers {
+        test.commit(buyer, ONE_USD).await.unwrap();
+    }
+
+    // nothing is earned before the commitments settle
+    test.set_time(SALE_END_TIME).await;
+    test.withdraw_proceeds(&destination).await.unwrap();
+    assert_eq!(test.token_balance(&destination).await, 0);
+
+    // each commitment fills 2/3 usd rounded down
+    for buyer in &buyers {
+        test.settle(buyer).await.unwrap();
+        assert_eq!(test.token_balance(&buyer.usd_account).await, 333_334);
+    }
+    test.withdraw_proceeds(&destination).await.unwrap();
+    assert_eq!(test.token_balance(&destination).await, 3 * 666_666);
+    assert_eq!(test.token_balance(&test.pool_usd_account.clone()).await, 0);
+}
+
+#[tokio::test]
+async fn test_commit_to_sale_exceeds_allocation() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&pro_rata_params()).await;
+    let buyer = test.add_buyer(50 * ONE_USD, 100 * ONE_USD).await;
+    test.set_time(SALE_TIME).await;
+
+    test.commit(&buyer, 30 * ONE_USD).await.unwrap();
+    let result = test.commit(&buyer, 30 * ONE_USD).await;
+    assert_sale_error(result, TokenSaleError::ExceedsAllocation);
+}
+
+#[tokio::test]
+async fn test_commit_to_first_come_first_served_sale() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let buyer = test.add_buyer(50 * ONE_USD, 100 * ONE_USD).await;
+    test.set_time(SALE_TIME).await;
+
+    let result = test.commit(&buyer, 10 * ONE_USD).await;
+    assert_sale_error(result, TokenSaleError::InvalidSaleMode);
+}
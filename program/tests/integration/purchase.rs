@@ -0,0 +1,187 @@
+use crate::helpers::*;
+use solana_program::pubkey::Pubkey;
+use solr_token_sale::{error::TokenSaleError, state::SaleMode};
+
+#[tokio::test]
+async fn test_execute_token_sale() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let buyer = test.add_buyer(50 * ONE_USD, 100 * ONE_USD).await;
+    test.set_time(SALE_TIME).await;
+
+    test.execute(&buyer, 10 * ONE_USD).await.unwrap();
+
+    assert_eq!(test.token_balance(&buyer.usd_account).await, 90 * ONE_USD);
+    assert_eq!(test.token_balance(&buyer.token_account).await, 100 * ONE_TOKEN);
+    assert_eq!(test.token_balance(&test.pool_usd_account.clone()).await, 10 * ONE_USD);
+    let purchase_record = test.purchase_record(&buyer).await;
+    assert_eq!(purchase_record.usd_amount, 10 * ONE_USD);
+    assert_eq!(purchase_record.token_amount, 100 * ONE_TOKEN);
+    assert_eq!(purchase_record.claimed_amount, 100 * ONE_TOKEN);
+    assert_eq!(test.token_sale_state().await.usd_raised_amount, 10 * ONE_USD);
+}
+
+#[tokio::test]
+async fn test_execute_token_sale_with_decimals() {
+    let mut test = TokenSaleTest::start_with_decimals(9, 6).await;
+    let params = SaleParams {
+        token_sale_amount: 1000 * 1_000_000_000,
+        ..SaleParams::default()
+    };
+    test.init_and_fund(&params).await;
+    let buyer = test.add_buyer(50 * ONE_USD, 100 * ONE_USD).await;
+    test.set_time(SALE_TIME).await;
+
+    test.execute(&buyer, 3 * ONE_USD + 1).await.unwrap();
+
+    // 3.000001 usd at 0.1 usd buys 30.00001 tokens
+    assert_eq!(test.token_balance(&buyer.token_account).await, 30_000_010_000);
+}
+
+#[tokio::test]
+async fn test_repeat_purchases() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let buyer = test.add_buyer(50 * ONE_USD, 100 * ONE_USD).await;
+    test.set_time(SALE_TIME).await;
+
+    test.execute(&buyer, 20 * ONE_USD).await.unwrap();
+    test.execute(&buyer, 30 * ONE_USD).await.unwrap();
+
+    assert_eq!(test.token_balance(&buyer.token_account).await, 500 * ONE_TOKEN);
+    let purchase_record = test.purchase_record(&buyer).await;
+    assert_eq!(purchase_record.usd_amount, 50 * ONE_USD);
+    assert_eq!(purchase_record.token_amount, 500 * ONE_TOKEN);
+
+    let result = test.execute(&buyer, ONE_USD).await;
+    assert_sale_error(result, TokenSaleError::ExceedsAllocation);
+}
+
+#[tokio::test]
+async fn test_execute_token_sale_not_whitelisted() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let buyer = test.add_unlisted_buyer(100 * ONE_USD).await;
+    test.set_time(SALE_TIME).await;
+
+    let result = test.execute(&buyer, 10 * ONE_USD).await;
+    assert_sale_error(result, TokenSaleError::UserNotWhitelisted);
+}
+
+#[tokio::test]
+async fn test_execute_token_sale_not_started() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let buyer = test.add_buyer(50 * ONE_USD, 100 * ONE_USD).await;
+
+    let result = test.execute(&buyer, 10 * ONE_USD).await;
+    assert_sale_error(result, TokenSaleError::TokenSaleNotStarted);
+}
+
+#[tokio::test]
+async fn test_execute_token_sale_closed() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let buyer = test.add_buyer(50 * ONE_USD, 100 * ONE_USD).await;
+    test.set_time(SALE_END_TIME).await;
+
+    let result = test.execute(&buyer, 10 * ONE_USD).await;
+    assert_sale_error(result, TokenSaleError::TokenSaleClosed);
+}
+
+#[tokio::test]
+async fn test_execute_token_sale_not_funded() {
+    let mut test = TokenSaleTest::start().await;
+    test.init(&SaleParams::default()).await.unwrap();
+    let buyer = test.add_buyer(50 * ONE_USD, 100 * ONE_USD).await;
+    test.set_time(SALE_TIME).await;
+
+    let result = test.execute(&buyer, 10 * ONE_USD).await;
+    assert_sale_error(result, TokenSaleError::TokenSaleNotFunded);
+}
+
+#[tokio::test]
+async fn test_execute_token_sale_amount_limits() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let buyer = test.add_buyer(500 * ONE_USD, 500 * ONE_USD).await;
+    test.set_time(SALE_TIME).await;
+
+    let result = test.execute(&buyer, ONE_USD - 1).await;
+    assert_sale_error(result, TokenSaleError::AmountMinimum);
+
+    let result = test.execute(&buyer, 100 * ONE_USD + 1).await;
+    assert_sale_error(result, TokenSaleError::AmountMaximum);
+}
+
+#[tokio::test]
+async fn test_execute_token_sale_exceeds_tokens_available() {
+    let mut test = TokenSaleTest::start().await;
+    let params = SaleParams {
+        token_sale_amount: 100 * ONE_TOKEN,
+        ..SaleParams::default()
+    };
+    test.init_and_fund(&params).await;
+    let buyer = test.add_buyer(50 * ONE_USD, 100 * ONE_USD).await;
+    test.set_time(SALE_TIME).await;
+
+    let result = test.execute(&buyer, 20 * ONE_USD).await;
+    assert_sale_error(result, TokenSaleError::AmountExceeds);
+}
+
+#[tokio::test]
+async fn test_execute_token_sale_invalid_program_address() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let buyer = test.add_buyer(50 * ONE_USD, 100 * ONE_USD).await;
+    test.set_time(SALE_TIME).await;
+
+    let mut instruction = test.execute_instruction(&buyer, 10 * ONE_USD, u64::MAX);
+    instruction.accounts[6].pubkey = Pubkey::new_unique();
+    let result = test.process(&[instruction], &[&buyer.keypair]).await;
+    assert_sale_error(result, TokenSaleError::InvalidProgramAddress);
+}
+
+#[tokio::test]
+async fn test_execute_token_sale_pro_rata() {
+    let mut test = TokenSaleTest::start().await;
+    let params = SaleParams {
+        sale_mode: SaleMode::ProRata,
+        ..SaleParams::default()
+    };
+    test.init_and_fund(&params).await;
+    let buyer = test.add_buyer(50 * ONE_USD, 100 * ONE_USD).await;
+    test.set_time(SALE_TIME).await;
+
+    let result = test.execute(&buyer, 10 * ONE_USD).await;
+    assert_sale_error(result, TokenSaleError::InvalidSaleMode);
+}
+
+#[tokio::test]
+async fn test_execute_token_sale_overflow() {
+    // 10^30 base units per token overflow the token amount of any usd amount
+    let mut test = TokenSaleTest::start_with_decimals(30, 6).await;
+    let params = SaleParams {
+        usd_min_amount: 0,
+        ..SaleParams::default()
+    };
+    test.init_and_fund(&params).await;
+    let buyer = test.add_buyer(50 * ONE_USD, 100 * ONE_USD).await;
+    test.set_time(SALE_TIME).await;
+
+    let result = test.execute(&buyer, 1000).await;
+    assert_sale_error(result, TokenSaleError::MathOverflow);
+}
+
+#[tokio::test]
+async fn test_execute_token_sale_complete() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let first_buyer = test.add_buyer(100 * ONE_USD, 100 * ONE_USD).await;
+    let second_buyer = test.add_buyer(50 * ONE_USD, 100 * ONE_USD).await;
+    test.set_time(SALE_TIME).await;
+
+    test.execute(&first_buyer, 100 * ONE_USD).await.unwrap();
+    let result = test.execute(&second_buyer, 10 * ONE_USD).await;
+    assert_sale_error(result, TokenSaleError::TokenSaleComplete);
+}
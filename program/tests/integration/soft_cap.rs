@@ -0,0 +1,120 @@
+use crate::helpers::*;
+use solana_sdk::signature::Signer;
+use solr_token_sale::error::TokenSaleError;
+
+fn soft_cap_params() -> SaleParams {
+    SaleParams {
+        soft_cap_amount: 50 * ONE_USD,
+        ..SaleParams::default()
+    }
+}
+
+#[tokio::test]
+async fn test_refund_purchase() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&soft_cap_params()).await;
+    let buyer = test.add_buyer(50 * ONE_USD, 100 * ONE_USD).await;
+    test.set_time(SALE_TIME).await;
+    test.execute(&buyer, 10 * ONE_USD).await.unwrap();
+
+    let result = test.refund(&buyer).await;
+    assert_sale_error(result, TokenSaleError::TokenSaleNotEnded);
+
+    test.set_time(SALE_END_TIME).await;
+    test.refund(&buyer).await.unwrap();
+    assert_eq!(test.token_balance(&buyer.usd_account).await, 100 * ONE_USD);
+    assert_eq!(test.token_balance(&buyer.token_account).await, 0);
+    assert_eq!(test.token_balance(&test.pool_usd_account.clone()).await, 0);
+    assert!(test.purchase_record(&buyer).await.is_refunded);
+
+    let result = test.refund(&buyer).await;
+    assert_sale_error(result, TokenSaleError::PurchaseRefunded);
+}
+
+#[tokio::test]
+async fn test_refund_purchase_after_withdraw_unsold() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&soft_cap_params()).await;
+    let buyer = test.add_buyer(50 * ONE_USD, 100 * ONE_USD).await;
+    let owner_pubkey = test.owner.pubkey();
+    let destination = test.create_token_account(&test.sale_mint.clone(), &owner_pubkey).await;
+    test.set_time(SALE_TIME).await;
+    test.execute(&buyer, 10 * ONE_USD).await.unwrap();
+
+    test.set_time(SALE_END_TIME).await;
+    test.withdraw_unsold(&destination).await.unwrap();
+    assert_eq!(test.token_balance(&destination).await, 900 * ONE_TOKEN);
+
+    // the refund returns the delivered tokens, which are withdrawn again
+    test.refund(&buyer).await.unwrap();
+    assert_eq!(test.token_balance(&test.sale_token_account.clone()).await, 100 * ONE_TOKEN);
+    test.withdraw_unsold(&destination).await.unwrap();
+    assert_eq!(test.token_balance(&destination).await, 1000 * ONE_TOKEN);
+
+    let result = test.withdraw_unsold(&destination).await;
+    assert_sale_error(result, TokenSaleError::TokenSaleWithdrawn);
+    test.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_refund_purchase_soft_cap_reached() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&soft_cap_params()).await;
+    let buyer = test.add_buyer(60 * ONE_USD, 100 * ONE_USD).await;
+    test.set_time(SALE_TIME).await;
+    test.execute(&buyer, 60 * ONE_USD).await.unwrap();
+
+    test.set_time(SALE_END_TIME).await;
+    let result = test.refund(&buyer).await;
+    assert_sale_error(result, TokenSaleError::SoftCapReached);
+}
+
+#[tokio::test]
+async fn test_withdraw_sale_proceeds() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&soft_cap_params()).await;
+    let buyer = test.add_buyer(60 * ONE_USD, 100 * ONE_USD).await;
+    let owner_pubkey = test.owner.pubkey();
+    let destination = test.create_token_account(&test.usd_mint.clone(), &owner_pubkey).await;
+    test.set_time(SALE_TIME).await;
+
+    test.execute(&buyer, 40 * ONE_USD).await.unwrap();
+    let result = test.withdraw_proceeds(&destination).await;
+    assert_sale_error(result, TokenSaleError::SoftCapNotReached);
+
+    test.execute(&buyer, 20 * ONE_USD).await.unwrap();
+    test.withdraw_proceeds(&destination).await.unwrap();
+    assert_eq!(test.token_balance(&destination).await, 60 * ONE_USD);
+    assert_eq!(test.token_sale_state().await.usd_withdrawn_amount, 60 * ONE_USD);
+}
+
+#[tokio::test]
+async fn test_withdraw_sale_proceeds_without_escrow() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let owner_pubkey = test.owner.pubkey();
+    let destination = test.create_token_account(&test.usd_mint.clone(), &owner_pubkey).await;
+
+    let result = test.withdraw_proceeds(&destination).await;
+    assert_sale_error(result, TokenSaleError::ProceedsNotEscrowed);
+}
+
+#[tokio::test]
+async fn test_claim_vested_tokens_soft_cap_not_reached() {
+    let mut test = TokenSaleTest::start().await;
+    let params = SaleParams {
+        vesting_tge_percent: 20,
+        vesting_start_time: SALE_END_TIME,
+        vesting_cliff_time: SALE_END_TIME,
+        vesting_end_time: SALE_END_TIME,
+        ..soft_cap_params()
+    };
+    test.init_and_fund(&params).await;
+    let buyer = test.add_buyer(50 * ONE_USD, 100 * ONE_USD).await;
+    test.set_time(SALE_TIME).await;
+    test.execute(&buyer, 10 * ONE_USD).await.unwrap();
+
+    test.set_time(SALE_END_TIME).await;
+    let result = test.claim(&buyer).await;
+    assert_sale_error(result, TokenSaleError::SoftCapNotReached);
+}
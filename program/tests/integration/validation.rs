@@ -0,0 +1,177 @@
+use crate::helpers::*;
+use solana_program::pubkey::Pubkey;
+use solana_sdk::{account::AccountSharedData, signature::Signer};
+use solr_token_sale::{error::TokenSaleError, instruction};
+
+#[tokio::test]
+async fn test_fund_token_sale_wrong_destination() {
+    let mut test = TokenSaleTest::start().await;
+    let params = SaleParams::default();
+    test.init(&params).await.unwrap();
+    let destination = test.create_token_account(&test.sale_mint.clone(), &Pubkey::new_unique()).await;
+
+    let instruction = instruction::fund_token_sale(
+        &test.program_id,
+        &test.owner.pubkey(),
+        &test.token_sale.pubkey(),
+        &test.owner_token_account,
+        &destination,
+        params.token_sale_amount,
+    );
+    let owner = test.owner();
+    let result = test.process(&[instruction], &[&owner]).await;
+    assert_sale_error(result, TokenSaleError::SaleTokenAccountMismatch);
+}
+
+#[tokio::test]
+async fn test_execute_token_sale_wrong_usd_mint() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let buyer = test.add_buyer(50 * ONE_USD, 100 * ONE_USD).await;
+    test.set_time(SALE_TIME).await;
+    let other_token_account = test.create_token_account(&test.sale_mint.clone(), &buyer.pubkey()).await;
+
+    let mut instruction = test.execute_instruction(&buyer, 10 * ONE_USD, u64::MAX);
+    instruction.accounts[4].pubkey = other_token_account;
+    let result = test.process(&[instruction], &[&buyer.keypair]).await;
+    assert_sale_error(result, TokenSaleError::MintMismatch);
+}
+
+#[tokio::test]
+async fn test_execute_token_sale_wrong_sale_mint() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let buyer = test.add_buyer(50 * ONE_USD, 100 * ONE_USD).await;
+    test.set_time(SALE_TIME).await;
+    let other_usd_account = test.create_token_account(&test.usd_mint.clone(), &buyer.pubkey()).await;
+
+    let mut instruction = test.execute_instruction(&buyer, 10 * ONE_USD, u64::MAX);
+    instruction.accounts[3].pubkey = other_usd_account;
+    let result = test.process(&[instruction], &[&buyer.keypair]).await;
+    assert_sale_error(result, TokenSaleError::MintMismatch);
+}
+
+#[tokio::test]
+async fn test_execute_token_sale_duplicate_account() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let buyer = test.add_buyer(50 * ONE_USD, 100 * ONE_USD).await;
+    test.set_time(SALE_TIME).await;
+
+    let mut instruction = test.execute_instruction(&buyer, 10 * ONE_USD, u64::MAX);
+    instruction.accounts[3].pubkey = test.sale_token_account;
+    let result = test.process(&[instruction], &[&buyer.keypair]).await;
+    assert_sale_error(result, TokenSaleError::DuplicateAccount);
+}
+
+#[tokio::test]
+async fn test_execute_token_sale_wrong_pool_account() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let buyer = test.add_buyer(50 * ONE_USD, 100 * ONE_USD).await;
+    test.set_time(SALE_TIME).await;
+    let other_usd_account = test.create_token_account(&test.usd_mint.clone(), &buyer.pubkey()).await;
+
+    let mut instruction = test.execute_instruction(&buyer, 10 * ONE_USD, u64::MAX);
+    instruction.accounts[5].pubkey = other_usd_account;
+    let result = test.process(&[instruction], &[&buyer.keypair]).await;
+    assert_sale_error(result, TokenSaleError::PoolTokenAccountMismatch);
+}
+
+#[tokio::test]
+async fn test_execute_token_sale_wrong_token_program() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let buyer = test.add_buyer(50 * ONE_USD, 100 * ONE_USD).await;
+    test.set_time(SALE_TIME).await;
+
+    let mut instruction = test.execute_instruction(&buyer, 10 * ONE_USD, u64::MAX);
+    instruction.accounts[7].pubkey = Pubkey::new_unique();
+    let result = test.process(&[instruction], &[&buyer.keypair]).await;
+    assert_sale_error(result, TokenSaleError::InvalidTokenProgram);
+}
+
+#[tokio::test]
+async fn test_execute_token_sale_wrong_system_program() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let buyer = test.add_buyer(50 * ONE_USD, 100 * ONE_USD).await;
+    test.set_time(SALE_TIME).await;
+
+    let mut instruction = test.execute_instruction(&buyer, 10 * ONE_USD, u64::MAX);
+    instruction.accounts[12].pubkey = Pubkey::new_unique();
+    let result = test.process(&[instruction], &[&buyer.keypair]).await;
+    assert_sale_error(result, TokenSaleError::InvalidSystemProgram);
+}
+
+#[tokio::test]
+async fn test_execute_token_sale_wrong_account_owner() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let buyer = test.add_buyer(50 * ONE_USD, 100 * ONE_USD).await;
+    test.set_time(SALE_TIME).await;
+    let mut account = test.get_account(&test.token_sale.pubkey()).await;
+    account.owner = Pubkey::new_unique();
+    test.context.set_account(&test.token_sale.pubkey(), &AccountSharedData::from(account));
+
+    let result = test.execute(&buyer, 10 * ONE_USD).await;
+    assert_sale_error(result, TokenSaleError::InvalidAccountOwner);
+}
+
+#[tokio::test]
+async fn test_claim_vested_tokens_wrong_purchase_record() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let buyer = test.add_buyer(50 * ONE_USD, 100 * ONE_USD).await;
+    let other_buyer = test.add_buyer(50 * ONE_USD, 100 * ONE_USD).await;
+    test.set_time(SALE_TIME).await;
+    test.execute(&other_buyer, 10 * ONE_USD).await.unwrap();
+
+    // the purchase record of another buyer
+    let mut instruction = instruction::claim_vested_tokens(
+        &test.program_id,
+        &buyer.pubkey(),
+        &test.token_sale.pubkey(),
+        &test.sale_token_account,
+        &buyer.token_account,
+    );
+    let other_instruction = instruction::claim_vested_tokens(
+        &test.program_id,
+        &other_buyer.pubkey(),
+        &test.token_sale.pubkey(),
+        &test.sale_token_account,
+        &other_buyer.token_account,
+    );
+    instruction.accounts[2].pubkey = other_instruction.accounts[2].pubkey;
+    let result = test.process(&[instruction], &[&buyer.keypair]).await;
+    assert_sale_error(result, TokenSaleError::PurchaseRecordMismatch);
+}
+
+#[tokio::test]
+async fn test_execute_token_sale_wrong_whitelist() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let buyer = test.add_buyer(50 * ONE_USD, 100 * ONE_USD).await;
+    test.set_time(SALE_TIME).await;
+
+    let mut instruction = test.execute_instruction(&buyer, 10 * ONE_USD, u64::MAX);
+    instruction.accounts[8].pubkey = Pubkey::new_unique();
+    let result = test.process(&[instruction], &[&buyer.keypair]).await;
+    assert_sale_error(result, TokenSaleError::InvalidWhitelist);
+
+    let mut instruction = test.execute_instruction(&buyer, 10 * ONE_USD, u64::MAX);
+    instruction.accounts[10].pubkey = Pubkey::new_unique();
+    let result = test.process(&[instruction], &[&buyer.keypair]).await;
+    assert_sale_error(result, TokenSaleError::InvalidWhitelist);
+}
+
+#[tokio::test]
+async fn test_withdraw_unsold_tokens_wrong_mint() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    test.set_time(SALE_END_TIME).await;
+    let destination = test.create_token_account(&test.usd_mint.clone(), &test.owner.pubkey()).await;
+
+    let result = test.withdraw_unsold(&destination).await;
+    assert_sale_error(result, TokenSaleError::MintMismatch);
+}
@@ -0,0 +1,66 @@
+use crate::helpers::*;
+use solana_sdk::signature::Signer;
+use solr_token_sale::error::TokenSaleError;
+
+const VESTING_START_TIME: u64 = SALE_END_TIME;
+const VESTING_CLIFF_TIME: u64 = SALE_END_TIME + 1000;
+const VESTING_END_TIME: u64 = SALE_END_TIME + 4000;
+
+fn vesting_params() -> SaleParams {
+    SaleParams {
+        vesting_tge_percent: 20,
+        vesting_start_time: VESTING_START_TIME,
+        vesting_cliff_time: VESTING_CLIFF_TIME,
+        vesting_end_time: VESTING_END_TIME,
+        ..SaleParams::default()
+    }
+}
+
+#[tokio::test]
+async fn test_claim_vested_tokens() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&vesting_params()).await;
+    let buyer = test.add_buyer(50 * ONE_USD, 100 * ONE_USD).await;
+    test.set_time(SALE_TIME).await;
+
+    test.execute(&buyer, 10 * ONE_USD).await.unwrap();
+    assert_eq!(test.token_balance(&buyer.token_account).await, 0);
+    assert_eq!(test.token_sale_state().await.vesting_locked_amount, 100 * ONE_TOKEN);
+
+    let result = test.claim(&buyer).await;
+    assert_sale_error(result, TokenSaleError::NothingToClaim);
+
+    test.set_time(VESTING_START_TIME).await;
+    test.claim(&buyer).await.unwrap();
+    assert_eq!(test.token_balance(&buyer.token_account).await, 20 * ONE_TOKEN);
+
+    // half way between vesting start and end after the cliff
+    test.set_time(VESTING_START_TIME + 2000).await;
+    test.claim(&buyer).await.unwrap();
+    assert_eq!(test.token_balance(&buyer.token_account).await, 60 * ONE_TOKEN);
+
+    test.set_time(VESTING_END_TIME).await;
+    test.claim(&buyer).await.unwrap();
+    assert_eq!(test.token_balance(&buyer.token_account).await, 100 * ONE_TOKEN);
+    assert_eq!(test.purchase_record(&buyer).await.claimed_amount, 100 * ONE_TOKEN);
+    assert_eq!(test.token_sale_state().await.vesting_locked_amount, 0);
+
+    let result = test.claim(&buyer).await;
+    assert_sale_error(result, TokenSaleError::NothingToClaim);
+}
+
+#[tokio::test]
+async fn test_withdraw_unsold_tokens_keeps_vesting_tokens() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&vesting_params()).await;
+    let buyer = test.add_buyer(50 * ONE_USD, 100 * ONE_USD).await;
+    let owner_pubkey = test.owner.pubkey();
+    let destination = test.create_token_account(&test.sale_mint.clone(), &owner_pubkey).await;
+    test.set_time(SALE_TIME).await;
+    test.execute(&buyer, 10 * ONE_USD).await.unwrap();
+
+    test.set_time(SALE_END_TIME).await;
+    test.withdraw_unsold(&destination).await.unwrap();
+    assert_eq!(test.token_balance(&destination).await, 900 * ONE_TOKEN);
+    assert_eq!(test.token_balance(&test.sale_token_account.clone()).await, 100 * ONE_TOKEN);
+}
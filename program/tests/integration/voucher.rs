@@ -0,0 +1,143 @@
+use crate::helpers::*;
+use solana_program::{pubkey::Pubkey, sysvar};
+use solana_sdk::signature::{Keypair, Signer};
+use solr_token_sale::{error::TokenSaleError, instruction, state::SaleMode};
+
+fn new_voucher_signer() -> ed25519_dalek::Keypair {
+    ed25519_dalek::Keypair::from_bytes(&Keypair::new().to_bytes()).unwrap()
+}
+
+fn voucher_signer_pubkey(voucher_signer: &ed25519_dalek::Keypair) -> Pubkey {
+    Pubkey::new_from_array(voucher_signer.public.to_bytes())
+}
+
+#[tokio::test]
+async fn test_execute_token_sale_with_voucher() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let voucher_signer = new_voucher_signer();
+    test.set_voucher_signer(&voucher_signer_pubkey(&voucher_signer)).await.unwrap();
+    let buyer = test.add_unlisted_buyer(100 * ONE_USD).await;
+    test.set_time(SALE_TIME).await;
+
+    let voucher_instruction = test.voucher_instruction(&voucher_signer, &buyer, 50 * ONE_USD, SALE_END_TIME);
+    test.execute_with_voucher(&buyer, voucher_instruction.clone(), 10 * ONE_USD).await.unwrap();
+    test.execute_with_voucher(&buyer, voucher_instruction.clone(), 40 * ONE_USD).await.unwrap();
+    assert_eq!(test.token_balance(&buyer.token_account).await, 500 * ONE_TOKEN);
+    assert_eq!(test.purchase_record(&buyer).await.usd_amount, 50 * ONE_USD);
+
+    // the voucher allocation is spent across purchases
+    let result = test.execute_with_voucher(&buyer, voucher_instruction, ONE_USD).await;
+    assert_sale_error(result, TokenSaleError::ExceedsAllocation);
+}
+
+#[tokio::test]
+async fn test_execute_token_sale_expired_voucher() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let voucher_signer = new_voucher_signer();
+    test.set_voucher_signer(&voucher_signer_pubkey(&voucher_signer)).await.unwrap();
+    let buyer = test.add_unlisted_buyer(100 * ONE_USD).await;
+    test.set_time(SALE_TIME + 100).await;
+
+    let voucher_instruction = test.voucher_instruction(&voucher_signer, &buyer, 50 * ONE_USD, SALE_TIME + 100);
+    let result = test.execute_with_voucher(&buyer, voucher_instruction, 10 * ONE_USD).await;
+    assert_sale_error(result, TokenSaleError::VoucherExpired);
+}
+
+#[tokio::test]
+async fn test_execute_token_sale_invalid_voucher() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let voucher_signer = new_voucher_signer();
+    test.set_voucher_signer(&voucher_signer_pubkey(&voucher_signer)).await.unwrap();
+    let buyer = test.add_unlisted_buyer(100 * ONE_USD).await;
+    let other_buyer = test.add_unlisted_buyer(100 * ONE_USD).await;
+    test.set_time(SALE_TIME).await;
+
+    // signed by another key
+    let voucher_instruction = test.voucher_instruction(&new_voucher_signer(), &buyer, 50 * ONE_USD, SALE_END_TIME);
+    let result = test.execute_with_voucher(&buyer, voucher_instruction, 10 * ONE_USD).await;
+    assert_sale_error(result, TokenSaleError::InvalidVoucher);
+
+    // issued to another buyer
+    let voucher_instruction = test.voucher_instruction(&voucher_signer, &other_buyer, 50 * ONE_USD, SALE_END_TIME);
+    let result = test.execute_with_voucher(&buyer, voucher_instruction, 10 * ONE_USD).await;
+    assert_sale_error(result, TokenSaleError::InvalidVoucher);
+
+    // not verified by the previous instruction
+    let instruction = test.voucher_execute_instruction(&buyer, 10 * ONE_USD);
+    let result = test.process(&[instruction], &[&buyer.keypair]).await;
+    assert_sale_error(result, TokenSaleError::InvalidVoucher);
+
+    // allocations are no longer read from the whitelist
+    let result = test.execute(&buyer, 10 * ONE_USD).await;
+    assert_sale_error(result, TokenSaleError::InvalidVoucher);
+}
+
+#[tokio::test]
+async fn test_execute_token_sale_tampered_voucher() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let voucher_signer = new_voucher_signer();
+    test.set_voucher_signer(&voucher_signer_pubkey(&voucher_signer)).await.unwrap();
+    let buyer = test.add_unlisted_buyer(100 * ONE_USD).await;
+    test.set_time(SALE_TIME).await;
+
+    // raising the allocation after signing fails signature verification
+    let mut voucher_instruction = test.voucher_instruction(&voucher_signer, &buyer, 5 * ONE_USD, SALE_END_TIME);
+    let allocation_offset = voucher_instruction.data.len() - 16;
+    voucher_instruction.data[allocation_offset..allocation_offset + 8]
+        .copy_from_slice(&(50 * ONE_USD).to_le_bytes());
+    let result = test.execute_with_voucher(&buyer, voucher_instruction, 10 * ONE_USD).await;
+    assert!(result.is_err());
+    assert_eq!(test.token_balance(&buyer.token_account).await, 0);
+}
+
+#[tokio::test]
+async fn test_commit_to_sale_with_voucher() {
+    let mut test = TokenSaleTest::start().await;
+    let params = SaleParams {
+        sale_mode: SaleMode::ProRata,
+        ..SaleParams::default()
+    };
+    test.init_and_fund(&params).await;
+    let voucher_signer = new_voucher_signer();
+    test.set_voucher_signer(&voucher_signer_pubkey(&voucher_signer)).await.unwrap();
+    let buyer = test.add_unlisted_buyer(100 * ONE_USD).await;
+    test.set_time(SALE_TIME).await;
+
+    let voucher_instruction = test.voucher_instruction(&voucher_signer, &buyer, 50 * ONE_USD, SALE_END_TIME);
+    let commit_instruction = |usd_amount| {
+        instruction::commit_to_sale(
+            &test.program_id,
+            &buyer.pubkey(),
+            &test.token_sale.pubkey(),
+            &buyer.usd_account,
+            &test.pool_usd_account,
+            &test.whitelist_map,
+            &sysvar::instructions::id(),
+            &test.whitelist_program_id,
+            usd_amount,
+            None,
+        )
+    };
+    let instructions = [voucher_instruction, commit_instruction(30 * ONE_USD)];
+    test.process(&instructions, &[&buyer.keypair]).await.unwrap();
+    assert_eq!(test.purchase_record(&buyer).await.usd_amount, 30 * ONE_USD);
+
+    let result = test.process(&instructions, &[&buyer.keypair]).await;
+    assert_sale_error(result, TokenSaleError::ExceedsAllocation);
+}
+
+#[tokio::test]
+async fn test_set_voucher_signer_after_start() {
+    let mut test = TokenSaleTest::start().await;
+    test.init_and_fund(&SaleParams::default()).await;
+    let buyer = test.add_buyer(50 * ONE_USD, 100 * ONE_USD).await;
+    test.set_time(SALE_TIME).await;
+    test.execute(&buyer, 10 * ONE_USD).await.unwrap();
+
+    let result = test.set_voucher_signer(&voucher_signer_pubkey(&new_voucher_signer())).await;
+    assert_sale_error(result, TokenSaleError::TokenSaleStarted);
+}